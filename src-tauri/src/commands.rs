@@ -1,26 +1,163 @@
 //! Tauri commands for the usage monitor
 
-use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use chrono::{DateTime, NaiveDate, Utc};
 use tauri::{command, State};
 
-use crate::usage::models::{AppConfig, DailyUsage, OverallStats, ProjectStats, UsageData};
-use crate::usage::pricing::PricingCalculator;
-use crate::usage::stats::{get_usage_data, FilterOptions};
+use crate::error::CommandError;
+use crate::usage::background::RefreshSignal;
+use crate::usage::config;
+use crate::usage::export;
+use crate::usage::models::{
+    AppConfig, BlendedRatePoint, ClockSkewReport, DailyCostAverage, DailyUsage, DataHealthReport,
+    DataSourceInfo, DataSourceReport, EnvDiagnostics, ExpensiveSession, ExportGranularity,
+    FootprintEstimate, Forecast, Granularity, HourlyUsage, MessageBudget, MessageCostPoint,
+    ModelDistribution, OverallStats, PeriodUsage, PlanProjection, PlanStatus,
+    ProjectCacheCostImpact, ProjectCacheEfficiency, ProjectPage, ProjectSortKey, ProjectStats,
+    SessionBlockInfo, SnapshotComparison, SpendStability, StatsSnapshot, TimeBucket,
+    TimeBucketUsage, TokenComposition, UsageData, UsageEntry, UsageSinceLastViewed,
+    WorkSessionReport,
+};
+use crate::usage::pricing::{list_plans, PlanInfo, PricingCalculator};
+use crate::usage::reader::{empty_if_missing, ScanOptions};
+use crate::usage::snapshot;
+use crate::usage::stats::{
+    calculate_avg_message_cost_trend, calculate_blended_rate_trend,
+    calculate_daily_cost_moving_average, calculate_hourly_usage, calculate_message_budget,
+    calculate_monthly_usage, calculate_plan_projections, calculate_plan_status,
+    calculate_session_blocks, calculate_spend_stability, calculate_time_bucket_usage,
+    calculate_token_composition, calculate_usage_since, calculate_weekly_usage,
+    calculate_work_sessions, daily_usage_on, estimate_footprint, find_most_expensive_session,
+    forecast_month_end, get_usage_data, group_model_distribution, load_entries,
+    paginate_projects, rank_projects_by_cache_efficiency, recent_daily_usage, recent_days_start,
+    search_project_stats, trim_daily_history, usage_date, validate_time_buckets, FilterOptions,
+    SessionConfig, MAX_RECENT_DAYS,
+};
+use crate::usage::timezone::UsageTimezone;
 use crate::AppState;
 
+/// Base filter carrying the configured scan, burn rate and cost override options
+fn base_filter() -> FilterOptions {
+    let config = get_config();
+    FilterOptions::new()
+        .with_scan_options(ScanOptions::from_config(&config))
+        .with_min_block_duration(config.min_block_duration_minutes)
+        .with_session(SessionConfig::from_config(&config))
+        .with_timezone(UsageTimezone::from_config(&config))
+        .with_cost_overrides(config.cost_override_path)
+        .with_batch_cache_multiplier(config.batch_cache_multiplier)
+        .with_cost_decimals(config.cost_decimals)
+}
+
+/// Path passed to the command, else the configured data path (env and default follow)
+fn path_or_config(data_path: Option<String>) -> Option<String> {
+    config::resolve_data_path(data_path, &get_config())
+}
+
+/// Load usage data from the resolved data path
+fn fetch_usage_data(
+    data_path: Option<String>,
+    filter: &FilterOptions,
+) -> Result<UsageData, CommandError> {
+    let source_info = data_source_info(data_path.clone());
+    let mut data = if source_info.dir_exists {
+        get_usage_data(path_or_config(data_path).as_deref(), filter)?
+    } else {
+        UsageData::default()
+    };
+    data.source_info = Some(source_info);
+    Ok(data)
+}
+
+/// Load filtered entries from the resolved data path
+fn fetch_entries(
+    data_path: Option<String>,
+    filter: &FilterOptions,
+) -> Result<Vec<UsageEntry>, CommandError> {
+    if !check_data_directory(data_path.clone())? {
+        return Ok(Vec::new());
+    }
+    load_entries(path_or_config(data_path).as_deref(), filter).map_err(CommandError::from)
+}
+
+/// Parse an optional RFC 3339 date parameter, ignoring malformed values
+fn parse_date_param(value: Option<&str>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Parse a `YYYY-MM-DD` calendar date argument
+fn parse_calendar_date(date: &str) -> Result<NaiveDate, CommandError> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| {
+        CommandError::InvalidInput(format!(
+            "Invalid date '{}' (expected YYYY-MM-DD): {}",
+            date, e
+        ))
+    })
+}
+
+/// Filter loading the day either side of `date` so any timezone offset is covered;
+/// exact filtering by date in the configured timezone follows
+fn around_date_filter(date: NaiveDate) -> FilterOptions {
+    let start = (date - chrono::Duration::days(1)).and_hms_opt(0, 0, 0).map(|d| d.and_utc());
+    let end = (date + chrono::Duration::days(2)).and_hms_opt(0, 0, 0).map(|d| d.and_utc());
+    base_filter().with_date_range(start, end)
+}
+
 /// Get complete usage statistics
 #[command]
-pub fn get_usage_stats(data_path: Option<String>) -> Result<UsageData, String> {
-    let filter = FilterOptions::new();
-    get_usage_data(data_path.as_deref(), &filter).map_err(|e| e.to_string())
+pub fn get_usage_stats(data_path: Option<String>) -> Result<UsageData, CommandError> {
+    let filter = base_filter().with_max_daily_history(get_config().max_daily_history_days);
+    fetch_usage_data(data_path, &filter)
 }
 
 /// Get list of projects with their statistics
 #[command]
-pub fn get_projects(data_path: Option<String>) -> Result<Vec<ProjectStats>, String> {
-    let filter = FilterOptions::new();
-    let data = get_usage_data(data_path.as_deref(), &filter).map_err(|e| e.to_string())?;
-    Ok(data.projects)
+pub fn get_projects(data_path: Option<String>) -> Result<Vec<ProjectStats>, CommandError> {
+    get_projects_paged(data_path, 0, u32::MAX, None).map(|page| page.projects)
+}
+
+/// Get one page of projects, sorted server-side (most recent activity first by default)
+#[command]
+pub fn get_projects_paged(
+    data_path: Option<String>,
+    offset: u32,
+    limit: u32,
+    sort_by: Option<ProjectSortKey>,
+) -> Result<ProjectPage, CommandError> {
+    let filter = base_filter();
+    let data = fetch_usage_data(data_path, &filter)?;
+    Ok(paginate_projects(data.projects, offset, limit, sort_by.unwrap_or_default()))
+}
+
+/// Find projects whose name or path contains `query`, using the usage data last loaded
+/// into the cache. Pending file changes are left for the background refresh to report.
+#[command]
+pub fn search_projects(
+    state: State<AppState>,
+    data_path: Option<String>,
+    query: String,
+) -> Result<Vec<ProjectStats>, CommandError> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let data_path = path_or_config(data_path);
+    let cached = state
+        .cache
+        .lock()?
+        .last_usage_data(data_path.as_deref())
+        .map(|data| search_project_stats(&data.projects, &query));
+    match cached {
+        Some(projects) => Ok(projects),
+        None => {
+            let data = fetch_usage_data(data_path, &base_filter())?;
+            Ok(search_project_stats(&data.projects, &query))
+        }
+    }
 }
 
 /// Get details for a specific project
@@ -28,66 +165,589 @@ pub fn get_projects(data_path: Option<String>) -> Result<Vec<ProjectStats>, Stri
 pub fn get_project_details(
     data_path: Option<String>,
     project_path: String,
-) -> Result<Option<ProjectStats>, String> {
-    let filter = FilterOptions::new().with_project(Some(project_path));
-    let data = get_usage_data(data_path.as_deref(), &filter).map_err(|e| e.to_string())?;
+) -> Result<Option<ProjectStats>, CommandError> {
+    let filter = base_filter().with_project(Some(project_path));
+    let data = fetch_usage_data(data_path, &filter)?;
     Ok(data.projects.into_iter().next())
 }
 
+/// Get projects ranked by how much of their input is served from cache
+#[command]
+pub fn get_project_cache_ranking(
+    data_path: Option<String>,
+) -> Result<Vec<ProjectCacheEfficiency>, CommandError> {
+    let filter = base_filter();
+    let data = fetch_usage_data(data_path, &filter)?;
+    Ok(rank_projects_by_cache_efficiency(&data.projects))
+}
+
+/// Get per-project cache write/read cost against the fresh input cost it avoided
+#[command]
+pub fn get_project_cache_cost_impact(
+    data_path: Option<String>,
+) -> Result<Vec<ProjectCacheCostImpact>, CommandError> {
+    let data_path = path_or_config(data_path);
+    let impact =
+        crate::usage::stats::get_project_cache_cost_impact(data_path.as_deref(), &base_filter());
+    empty_if_missing(impact).map_err(CommandError::from)
+}
+
 /// Get daily usage data
 #[command]
 pub fn get_daily_usage(
     data_path: Option<String>,
     start_date: Option<String>,
     end_date: Option<String>,
-) -> Result<Vec<DailyUsage>, String> {
-    let start = start_date
-        .as_ref()
-        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
-        .map(|dt| dt.with_timezone(&Utc));
+) -> Result<Vec<DailyUsage>, CommandError> {
+    let start = parse_date_param(start_date.as_deref());
+    let end = parse_date_param(end_date.as_deref());
 
-    let end = end_date
-        .as_ref()
-        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
-        .map(|dt| dt.with_timezone(&Utc));
-
-    let filter = FilterOptions::new().with_date_range(start, end);
-    let data = get_usage_data(data_path.as_deref(), &filter).map_err(|e| e.to_string())?;
+    let filter = base_filter().with_date_range(start, end);
+    let data = fetch_usage_data(data_path, &filter)?;
     Ok(data.daily_usage)
 }
 
+/// Get daily usage for the last `days` local dates including today (at most
+/// `MAX_RECENT_DAYS`), with empty days filled in unless `fill_gaps` is false
+#[command]
+pub fn get_recent_daily_usage(
+    data_path: Option<String>,
+    days: u32,
+    fill_gaps: Option<bool>,
+) -> Result<Vec<DailyUsage>, CommandError> {
+    if days > MAX_RECENT_DAYS {
+        return Err(CommandError::InvalidInput(format!(
+            "Recent usage covers at most {} days, got {}",
+            MAX_RECENT_DAYS, days
+        )));
+    }
+
+    let filter = base_filter();
+    let today = usage_date(&Utc::now(), &filter.timezone);
+
+    // Load from a day early so any local offset is covered; exact date filtering follows
+    let start = (recent_days_start(today, days) - chrono::Duration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .map(|d| d.and_utc());
+    let filter = filter.with_date_range(start, None);
+    let data = fetch_usage_data(data_path, &filter)?;
+    Ok(recent_daily_usage(&data.daily_usage, today, days, fill_gaps.unwrap_or(true)))
+}
+
+/// Get usage for each hour of `date` (`YYYY-MM-DD`, configured timezone)
+#[command]
+pub fn get_hourly_usage(
+    data_path: Option<String>,
+    date: String,
+) -> Result<Vec<HourlyUsage>, CommandError> {
+    let date = parse_calendar_date(&date)?;
+    let filter = around_date_filter(date);
+    let entries = fetch_entries(data_path, &filter)?;
+    Ok(calculate_hourly_usage(&entries, date, &filter.timezone, filter.cost_decimals()))
+}
+
+/// Get aggregated usage for one `date` (`YYYY-MM-DD`, configured timezone), zeros if idle
+#[command]
+pub fn get_usage_for_date(
+    data_path: Option<String>,
+    date: String,
+) -> Result<DailyUsage, CommandError> {
+    let date = parse_calendar_date(&date)?;
+    let filter = around_date_filter(date);
+    let data = fetch_usage_data(data_path, &filter)?;
+    Ok(daily_usage_on(&data.daily_usage, date))
+}
+
+/// Get usage rolled up per ISO week (configured timezone)
+#[command]
+pub fn get_weekly_usage(
+    data_path: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<Vec<PeriodUsage>, CommandError> {
+    let start = parse_date_param(start_date.as_deref());
+    let end = parse_date_param(end_date.as_deref());
+
+    let filter = base_filter().with_date_range(start, end);
+    let entries = fetch_entries(data_path, &filter)?;
+    Ok(calculate_weekly_usage(&entries, &filter.timezone, filter.cost_decimals()))
+}
+
+/// Get usage rolled up per billing month (configured timezone)
+#[command]
+pub fn get_monthly_usage(
+    data_path: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<Vec<PeriodUsage>, CommandError> {
+    let start = parse_date_param(start_date.as_deref());
+    let end = parse_date_param(end_date.as_deref());
+
+    let filter = base_filter().with_date_range(start, end);
+    let entries = fetch_entries(data_path, &filter)?;
+    let billing_cycle_start_day = get_config().billing_cycle_start_day;
+    Ok(calculate_monthly_usage(
+        &entries,
+        &filter.timezone,
+        billing_cycle_start_day,
+        filter.cost_decimals(),
+    ))
+}
+
+/// Get each day's cost with its trailing `window`-day moving average
+#[command]
+pub fn get_daily_cost_with_ma(
+    data_path: Option<String>,
+    window: u32,
+) -> Result<Vec<DailyCostAverage>, CommandError> {
+    if window == 0 {
+        return Err(CommandError::InvalidInput(
+            "Moving average window must be at least 1 day".to_string(),
+        ));
+    }
+
+    let filter = base_filter();
+    let data = fetch_usage_data(data_path, &filter)?;
+    Ok(calculate_daily_cost_moving_average(
+        &data.daily_usage,
+        window as usize,
+        filter.cost_decimals(),
+    ))
+}
+
+/// Get the percentage of tokens that are input, output, cache creation and cache read
+#[command]
+pub fn get_token_composition(
+    data_path: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<TokenComposition, CommandError> {
+    let start = parse_date_param(start_date.as_deref());
+    let end = parse_date_param(end_date.as_deref());
+
+    let filter = base_filter().with_date_range(start, end);
+    let data = fetch_usage_data(data_path, &filter)?;
+    Ok(calculate_token_composition(&data.overall_stats))
+}
+
+/// Get cost and tokens split into labeled time-of-day buckets (configured timezone)
+#[command]
+pub fn get_cost_by_time_buckets(
+    data_path: Option<String>,
+    buckets: Vec<TimeBucket>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<Vec<TimeBucketUsage>, CommandError> {
+    validate_time_buckets(&buckets).map_err(CommandError::InvalidInput)?;
+
+    let start = parse_date_param(start_date.as_deref());
+    let end = parse_date_param(end_date.as_deref());
+
+    let filter = base_filter().with_date_range(start, end);
+    let entries = fetch_entries(data_path, &filter)?;
+    Ok(calculate_time_bucket_usage(&entries, &buckets, &filter.timezone, filter.cost_decimals()))
+}
+
+/// Get the blended cost per million tokens for each day, week or month (configured timezone)
+#[command]
+pub fn get_blended_rate_trend(
+    data_path: Option<String>,
+    granularity: Granularity,
+) -> Result<Vec<BlendedRatePoint>, CommandError> {
+    let filter = base_filter();
+    let entries = fetch_entries(data_path, &filter)?;
+    Ok(calculate_blended_rate_trend(
+        &entries,
+        granularity,
+        &filter.timezone,
+        filter.cost_decimals(),
+    ))
+}
+
+/// Get a project's average cost per message for each day, week or month (configured timezone)
+#[command]
+pub fn get_avg_message_cost_trend(
+    data_path: Option<String>,
+    project_path: String,
+    granularity: Granularity,
+) -> Result<Vec<MessageCostPoint>, CommandError> {
+    let filter = base_filter().with_project(Some(project_path));
+    let entries = fetch_entries(data_path, &filter)?;
+    Ok(calculate_avg_message_cost_trend(
+        &entries,
+        granularity,
+        &filter.timezone,
+        filter.cost_decimals(),
+    ))
+}
+
+/// Idle gap that ends a work session when none is given
+const DEFAULT_WORK_SESSION_GAP_MINUTES: u32 = 30;
+
+/// Get work sessions separated by idle gaps longer than `gap_minutes`, per project and overall
+#[command]
+pub fn get_work_sessions(
+    data_path: Option<String>,
+    gap_minutes: Option<u32>,
+) -> Result<WorkSessionReport, CommandError> {
+    let filter = base_filter();
+    let gap_minutes = gap_minutes.unwrap_or(DEFAULT_WORK_SESSION_GAP_MINUTES);
+    if !check_data_directory(data_path.clone())? {
+        return Ok(calculate_work_sessions(&[], gap_minutes, filter.cost_decimals()));
+    }
+    crate::usage::stats::get_work_sessions(
+        path_or_config(data_path).as_deref(),
+        &filter,
+        gap_minutes,
+    )
+    .map_err(CommandError::from)
+}
+
 /// Get overall statistics
 #[command]
-pub fn get_overall_stats(data_path: Option<String>) -> Result<OverallStats, String> {
-    let filter = FilterOptions::new();
-    let data = get_usage_data(data_path.as_deref(), &filter).map_err(|e| e.to_string())?;
+pub fn get_overall_stats(data_path: Option<String>) -> Result<OverallStats, CommandError> {
+    let filter = base_filter();
+    let data = fetch_usage_data(data_path, &filter)?;
     Ok(data.overall_stats)
 }
 
-/// Get application configuration
+/// Get model distribution, either flat (sorted by tokens) or grouped by model family
+#[command]
+pub fn get_model_distribution(
+    data_path: Option<String>,
+    grouped: bool,
+) -> Result<ModelDistribution, CommandError> {
+    let filter = base_filter();
+    let data = fetch_usage_data(data_path, &filter)?;
+    let models = data.overall_stats.model_distribution;
+
+    if grouped {
+        Ok(ModelDistribution::Grouped(group_model_distribution(&models, filter.cost_decimals())))
+    } else {
+        Ok(ModelDistribution::Flat(models))
+    }
+}
+
+/// Default trailing window for spend stability metrics
+const DEFAULT_STABILITY_WINDOW_DAYS: u32 = 30;
+
+/// Get variance and predictability of daily spend over a trailing window (today excluded)
+#[command]
+pub fn get_spend_stability(
+    data_path: Option<String>,
+    window_days: Option<u32>,
+) -> Result<SpendStability, CommandError> {
+    let filter = base_filter();
+    let data = fetch_usage_data(data_path, &filter)?;
+    let today = usage_date(&Utc::now(), &filter.timezone);
+    Ok(calculate_spend_stability(
+        &data.daily_usage,
+        today,
+        window_days.unwrap_or(DEFAULT_STABILITY_WINDOW_DAYS),
+        filter.cost_decimals(),
+    ))
+}
+
+/// Get messages used and remaining against the plan's message limit in the current session
+#[command]
+pub fn get_message_budget(data_path: Option<String>) -> Result<MessageBudget, CommandError> {
+    let config = get_config();
+    let filter = base_filter();
+    let entries = fetch_entries(data_path, &filter)?;
+    Ok(calculate_message_budget(&entries, &config.plan_type, &Utc::now(), &filter.session))
+}
+
+/// List the plan tiers with their display names and limits, for the plan setting
+#[command]
+pub fn get_plan_types() -> Vec<PlanInfo> {
+    list_plans()
+}
+
+/// Get the active session's consumption against a plan's limits (defaults to the configured plan)
+#[command]
+pub fn get_plan_status(
+    data_path: Option<String>,
+    plan_type: Option<String>,
+) -> Result<PlanStatus, CommandError> {
+    let plan_type = plan_type.unwrap_or_else(|| get_config().plan_type);
+    let filter = base_filter();
+    let entries = fetch_entries(data_path, &filter)?;
+    Ok(calculate_plan_status(
+        &entries,
+        &plan_type,
+        &Utc::now(),
+        &filter.session,
+        filter.cost_decimals(),
+    ))
+}
+
+/// Get how recent session blocks compare against each plan tier's limits
+#[command]
+pub fn get_plan_projections(
+    data_path: Option<String>,
+) -> Result<Vec<PlanProjection>, CommandError> {
+    let filter = base_filter();
+    let entries = fetch_entries(data_path, &filter)?;
+    Ok(calculate_plan_projections(&entries, &Utc::now(), &filter.session, filter.cost_decimals()))
+}
+
+/// Get a rough energy and CO2 estimate for all token usage, using the configured factors
+#[command]
+pub fn get_footprint_estimate(
+    data_path: Option<String>,
+) -> Result<FootprintEstimate, CommandError> {
+    let config = get_config();
+    let data = fetch_usage_data(data_path, &base_filter())?;
+    let stats = &data.overall_stats;
+    Ok(estimate_footprint(
+        stats.total_input_tokens + stats.total_output_tokens,
+        config.kwh_per_million_tokens,
+        config.co2_kg_per_kwh,
+    ))
+}
+
+/// Get projected cost and tokens at the end of the current billing month
+#[command]
+pub fn get_cost_forecast(data_path: Option<String>) -> Result<Forecast, CommandError> {
+    let filter = base_filter();
+    let entries = fetch_entries(data_path, &filter)?;
+    let billing_cycle_start_day = get_config().billing_cycle_start_day;
+    Ok(forecast_month_end(
+        &entries,
+        &Utc::now(),
+        &filter.timezone,
+        billing_cycle_start_day,
+        filter.cost_decimals(),
+    ))
+}
+
+/// Get the session block with the highest cost, or None if there is no data
+#[command]
+pub fn get_most_expensive_session(
+    data_path: Option<String>,
+) -> Result<Option<ExpensiveSession>, CommandError> {
+    let filter = base_filter();
+    let entries = fetch_entries(data_path, &filter)?;
+    Ok(find_most_expensive_session(&entries, &Utc::now(), &filter.session, filter.cost_decimals()))
+}
+
+/// Get all 5-hour session blocks with their per-model breakdown
+#[command]
+pub fn get_session_blocks(
+    data_path: Option<String>,
+) -> Result<Vec<SessionBlockInfo>, CommandError> {
+    let filter = base_filter();
+    let entries = fetch_entries(data_path, &filter)?;
+    Ok(calculate_session_blocks(&entries, &Utc::now(), &filter.session, filter.cost_decimals()))
+}
+
+/// Export usage in the date range as CSV, one row per day or per project
+#[command]
+pub fn export_usage_csv(
+    data_path: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    granularity: ExportGranularity,
+) -> Result<String, CommandError> {
+    let start = parse_date_param(start_date.as_deref());
+    let end = parse_date_param(end_date.as_deref());
+
+    let filter = base_filter().with_date_range(start, end);
+    let data = fetch_usage_data(data_path, &filter)?;
+    export::usage_to_csv(&data, granularity).map_err(CommandError::from)
+}
+
+/// Export the full usage data as versioned, pretty-printed JSON
+#[command]
+pub fn export_usage_json(data_path: Option<String>) -> Result<String, CommandError> {
+    // The full daily history, not the window trimmed for the dashboard
+    let data = fetch_usage_data(data_path, &base_filter())?;
+    export::usage_to_json(&data, &Utc::now()).map_err(CommandError::from)
+}
+
+/// Configuration last loaded or saved, so commands don't re-read config.json per call
+static CONFIG: RwLock<Option<AppConfig>> = RwLock::new(None);
+
+/// Get application configuration, or the defaults when none has been saved
 #[command]
 pub fn get_config() -> AppConfig {
-    // For now, return default config
-    // In a real app, this would load from a config file
-    AppConfig::default()
+    if let Some(config) = CONFIG.read().ok().and_then(|cached| cached.clone()) {
+        return config;
+    }
+    let config = match config::get_config_path() {
+        Ok(path) => config::load_config(&path),
+        Err(_) => AppConfig::default(),
+    };
+    if let Ok(mut cached) = CONFIG.write() {
+        *cached = Some(config.clone());
+    }
+    config
+}
+
+/// Validate and save `config`, making it the configuration later commands read
+fn store_config(config: &AppConfig) -> Result<PathBuf, CommandError> {
+    let path = config::get_config_path()?;
+    config::save_config(&path, config)?;
+    *CONFIG.write()? = Some(config.clone());
+    Ok(path)
 }
 
-/// Set application configuration
+/// Validate and save application configuration. Scan, session, timezone and precision
+/// settings apply to the shared cache; a new refresh interval and data path apply to
+/// background refreshes at once.
 #[command]
-pub fn set_config(config: AppConfig) -> Result<(), String> {
-    // For now, just validate
-    // In a real app, this would save to a config file
-    log::info!("Config updated: {:?}", config);
+pub fn set_config(state: State<AppState>, config: AppConfig) -> Result<(), CommandError> {
+    let path = store_config(&config)?;
+    log::info!("Config saved to {}: {:?}", path.display(), config);
+    state.cache.lock()?.apply_config(&config);
+    signal_refresh_interval(&state, &config);
+    send_refresh_signal(&state, RefreshSignal::DataPathChanged);
     Ok(())
 }
 
-/// Check if the Claude data directory exists and is accessible
+/// Validate and save a new background refresh interval and restart the refresh wait with it
 #[command]
-pub fn check_data_directory(data_path: Option<String>) -> Result<bool, String> {
-    use crate::usage::config::get_projects_dir;
+pub fn set_refresh_interval(state: State<AppState>, seconds: u32) -> Result<(), CommandError> {
+    let config = AppConfig {
+        refresh_interval_seconds: seconds,
+        ..get_config()
+    };
+    store_config(&config)?;
+    signal_refresh_interval(&state, &config);
+    Ok(())
+}
+
+fn signal_refresh_interval(state: &AppState, config: &AppConfig) {
+    let signal = RefreshSignal::IntervalChanged(config::refresh_interval(config));
+    send_refresh_signal(state, signal);
+}
+
+fn send_refresh_signal(state: &AppState, signal: RefreshSignal) {
+    if state.refresh_signal.send(signal).is_err() {
+        log::warn!("Background refresh is not running; {:?} applies on restart", signal);
+    }
+}
+
+/// Record now as when usage was last viewed and return the saved RFC 3339 timestamp
+#[command]
+pub fn mark_viewed() -> Result<String, CommandError> {
+    let viewed_at = Utc::now().to_rfc3339();
+    let config = AppConfig {
+        last_viewed_at: Some(viewed_at.clone()),
+        ..get_config()
+    };
+    store_config(&config)?;
+    Ok(viewed_at)
+}
+
+/// Get usage accrued since `mark_viewed` was last called. Before the first call (or when
+/// the saved timestamp is malformed) nothing counts as new, so the totals are zero.
+#[command]
+pub fn get_usage_since_last_viewed(
+    data_path: Option<String>,
+) -> Result<UsageSinceLastViewed, CommandError> {
+    let Some(last_viewed_at) = parse_date_param(get_config().last_viewed_at.as_deref()) else {
+        return Ok(UsageSinceLastViewed::default());
+    };
+
+    let filter = base_filter().with_date_range(Some(last_viewed_at), None);
+    let entries = fetch_entries(data_path, &filter)?;
+    Ok(calculate_usage_since(&entries, last_viewed_at, filter.cost_decimals()))
+}
+
+/// Data directories usage is read from and whether any exists. Without an explicit path
+/// the configured extra data directories are used, as they replace the default.
+fn data_source_info(data_path: Option<String>) -> DataSourceInfo {
+    let scan = ScanOptions::from_config(&get_config());
+    let data_dirs: Vec<PathBuf> = match path_or_config(data_path).as_deref() {
+        None if !scan.data_dirs.is_empty() => scan.data_dirs.iter().map(PathBuf::from).collect(),
+        path => config::get_claude_data_dirs(path),
+    };
+
+    DataSourceInfo {
+        data_source: config::JSONL_DATA_SOURCE.to_string(),
+        dir_exists: data_dirs.iter().any(|dir| dir.join("projects").is_dir()),
+        data_dirs: data_dirs
+            .iter()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .collect(),
+    }
+}
+
+/// Check if the Claude data directory exists and is accessible.
+/// A missing directory means a fresh install, which commands report as zero usage.
+#[command]
+pub fn check_data_directory(data_path: Option<String>) -> Result<bool, CommandError> {
+    Ok(data_source_info(data_path).dir_exists)
+}
+
+/// Save the current overall statistics as a named snapshot
+#[command]
+pub fn save_snapshot(
+    name: String,
+    data_path: Option<String>,
+) -> Result<StatsSnapshot, CommandError> {
+    let data = fetch_usage_data(data_path, &base_filter())?;
+    let dir = snapshot::get_snapshots_dir()?;
+    snapshot::save_snapshot(&dir, &name, &data.overall_stats, &Utc::now())
+        .map_err(CommandError::from)
+}
+
+/// Compare a named snapshot against current overall statistics
+#[command]
+pub fn compare_snapshot(
+    name: String,
+    data_path: Option<String>,
+) -> Result<SnapshotComparison, CommandError> {
+    let dir = snapshot::get_snapshots_dir()?;
+    let saved = snapshot::load_snapshot(&dir, &name)?;
+    let data = fetch_usage_data(data_path, &base_filter())?;
+    Ok(snapshot::compare_snapshot(&saved, &data.overall_stats))
+}
+
+/// Report environment variables affecting data source selection
+#[command]
+pub fn get_env_diagnostics(data_path: Option<String>) -> Result<EnvDiagnostics, CommandError> {
+    Ok(crate::usage::config::get_env_diagnostics(path_or_config(data_path).as_deref()))
+}
+
+/// Find session files whose entries are out of timestamp order or newer than the file itself
+#[command]
+pub fn detect_clock_skew(data_path: Option<String>) -> Result<ClockSkewReport, CommandError> {
+    if !check_data_directory(data_path.clone())? {
+        return Ok(ClockSkewReport::default());
+    }
+    let data_path = path_or_config(data_path);
+    crate::usage::diagnostics::detect_clock_skew(data_path.as_deref(), &base_filter().scan)
+        .map_err(CommandError::from)
+}
 
-    let projects_dir = get_projects_dir(data_path.as_deref());
-    Ok(projects_dir.exists() && projects_dir.is_dir())
+/// List session files with lines that could not be parsed
+#[command]
+pub fn get_data_health(data_path: Option<String>) -> Result<DataHealthReport, CommandError> {
+    let config = get_config();
+    let pricing = PricingCalculator::from_config(&config);
+    let data_path = config::resolve_data_path(data_path, &config);
+    crate::usage::diagnostics::check_data_health(
+        data_path.as_deref(),
+        &base_filter().scan,
+        &pricing,
+    )
+    .map_err(CommandError::from)
+}
+
+/// Dry run of data discovery: resolved directories and how much data each step finds
+#[command]
+pub fn diagnose_data_source(data_path: Option<String>) -> Result<DataSourceReport, CommandError> {
+    let config = get_config();
+    let pricing = PricingCalculator::from_config(&config);
+    let data_path = config::resolve_data_path(data_path, &config);
+    crate::usage::diagnostics::diagnose_data_source(
+        data_path.as_deref(),
+        &base_filter().scan,
+        &pricing,
+    )
+    .map_err(CommandError::from)
 }
 
 /// Get usage statistics with incremental refresh (only reads changed files)
@@ -96,17 +756,75 @@ pub fn get_usage_stats_incremental(
     state: State<AppState>,
     data_path: Option<String>,
     force_full: Option<bool>,
-) -> Result<UsageData, String> {
-    let pricing = PricingCalculator::new();
-    let mut cache = state.cache.lock().map_err(|e| e.to_string())?;
+) -> Result<UsageData, CommandError> {
+    let config = get_config();
+    let pricing = PricingCalculator::from_config(&config);
+    let data_path = config::resolve_data_path(data_path, &config);
+    let mut cache = state.cache.lock()?;
 
-    if force_full.unwrap_or(false) {
+    let mut data = if force_full.unwrap_or(false) {
         // Force full refresh - clear cache and reload all data
-        cache.full_load(data_path.as_deref(), &pricing)
-            .map_err(|e| e.to_string())
+        empty_if_missing(cache.full_load(data_path.as_deref(), &pricing))?
     } else {
         // Incremental refresh - only read changed files
-        cache.incremental_load(data_path.as_deref(), &pricing)
-            .map_err(|e| e.to_string())
+        empty_if_missing(cache.incremental_load(data_path.as_deref(), &pricing))?
+    };
+    data.source_info = Some(data_source_info(data_path));
+
+    data.daily_usage = trim_daily_history(
+        data.daily_usage,
+        config.max_daily_history_days,
+        usage_date(&Utc::now(), &UsageTimezone::from_config(&config)),
+    );
+    Ok(data)
+}
+
+/// Drop all cached file state and reload every session file from scratch
+#[command]
+pub fn clear_cache(
+    state: State<AppState>,
+    data_path: Option<String>,
+) -> Result<UsageData, CommandError> {
+    let config = get_config();
+    let pricing = PricingCalculator::from_config(&config);
+    let data_path = config::resolve_data_path(data_path, &config);
+    let mut cache = state.cache.lock()?;
+
+    cache.clear();
+    let mut data = empty_if_missing(cache.full_load(data_path.as_deref(), &pricing))?;
+    data.source_info = Some(data_source_info(data_path));
+
+    data.daily_usage = trim_daily_history(
+        data.daily_usage,
+        config.max_daily_history_days,
+        usage_date(&Utc::now(), &UsageTimezone::from_config(&config)),
+    );
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_projects_dir_reports_empty_usage() {
+        let root =
+            std::env::temp_dir().join(format!("ccm-commands-missing-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let data_path = root.to_str().map(String::from);
+
+        assert!(!check_data_directory(data_path.clone()).unwrap());
+        let data = fetch_usage_data(data_path.clone(), &FilterOptions::new()).unwrap();
+        assert!(data.projects.is_empty());
+        assert!(data.daily_usage.is_empty());
+        assert_eq!(data.overall_stats.total_messages, 0);
+        let source_info = data.source_info.unwrap();
+        assert_eq!(source_info.data_source, config::JSONL_DATA_SOURCE);
+        assert_eq!(source_info.data_dirs, vec![root.to_string_lossy().to_string()]);
+        assert!(!source_info.dir_exists);
+
+        assert!(fetch_entries(data_path.clone(), &FilterOptions::new()).unwrap().is_empty());
+        assert_eq!(get_work_sessions(data_path.clone(), None).unwrap().session_count, 0);
+        assert_eq!(detect_clock_skew(data_path).unwrap().files_checked, 0);
     }
 }
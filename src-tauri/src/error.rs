@@ -0,0 +1,133 @@
+//! Error returned by Tauri commands
+//!
+//! Serializes as `{ "kind": "dirNotFound", "message": "..." }` so the frontend can pick
+//! guidance by `kind` and still show `message` as before.
+
+use std::io;
+use std::sync::PoisonError;
+
+use serde::Serialize;
+
+use crate::usage::config::ConfigError;
+use crate::usage::export::ExportError;
+use crate::usage::reader::ReaderError;
+use crate::usage::snapshot::SnapshotError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum CommandError {
+    /// The Claude data or projects directory does not exist
+    #[error("{0}")]
+    DirNotFound(String),
+    /// A file or directory exists but may not be read or written
+    #[error("{0}")]
+    PermissionDenied(String),
+    /// Any other filesystem failure
+    #[error("{0}")]
+    Io(String),
+    /// A data, config or snapshot file is not valid JSON
+    #[error("{0}")]
+    Parse(String),
+    /// An argument or config value was rejected
+    #[error("{0}")]
+    InvalidInput(String),
+    /// A named item, such as a snapshot, does not exist
+    #[error("{0}")]
+    NotFound(String),
+    /// An unexpected failure, such as a poisoned lock or a serialization error
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl CommandError {
+    fn from_io(e: &io::Error, message: String) -> Self {
+        match e.kind() {
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied(message),
+            io::ErrorKind::NotFound => Self::NotFound(message),
+            _ => Self::Io(message),
+        }
+    }
+}
+
+impl From<ReaderError> for CommandError {
+    fn from(e: ReaderError) -> Self {
+        let message = e.to_string();
+        match &e {
+            ReaderError::Io(io) => Self::from_io(io, message),
+            ReaderError::Json(_) => Self::Parse(message),
+            ReaderError::DirNotFound(_) => Self::DirNotFound(message),
+            ReaderError::InvalidPath(_) => Self::InvalidInput(message),
+        }
+    }
+}
+
+impl From<ConfigError> for CommandError {
+    fn from(e: ConfigError) -> Self {
+        let message = e.to_string();
+        match &e {
+            ConfigError::Io(io) => Self::from_io(io, message),
+            ConfigError::Json(_) => Self::Parse(message),
+            ConfigError::NoConfigDir => Self::DirNotFound(message),
+            _ => Self::InvalidInput(message),
+        }
+    }
+}
+
+impl From<SnapshotError> for CommandError {
+    fn from(e: SnapshotError) -> Self {
+        let message = e.to_string();
+        match &e {
+            SnapshotError::Io(io) => Self::from_io(io, message),
+            SnapshotError::Json(_) => Self::Parse(message),
+            SnapshotError::InvalidName(_) => Self::InvalidInput(message),
+            SnapshotError::NotFound(_) => Self::NotFound(message),
+            SnapshotError::NoDataDir => Self::DirNotFound(message),
+        }
+    }
+}
+
+impl From<ExportError> for CommandError {
+    fn from(e: ExportError) -> Self {
+        let message = e.to_string();
+        match &e {
+            ExportError::Io(io) => Self::from_io(io, message),
+            _ => Self::Internal(message),
+        }
+    }
+}
+
+/// A poisoned lock means an earlier command panicked while holding it
+impl<T> From<PoisonError<T>> for CommandError {
+    fn from(e: PoisonError<T>) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_errors_map_to_kinds() {
+        let missing = CommandError::from(ReaderError::DirNotFound("/home/u/.claude".to_string()));
+        assert_eq!(
+            serde_json::to_value(&missing).unwrap(),
+            serde_json::json!({
+                "kind": "dirNotFound",
+                "message": "Directory not found: /home/u/.claude",
+            })
+        );
+
+        let denied = io::Error::new(io::ErrorKind::PermissionDenied, "denied");
+        assert!(matches!(
+            CommandError::from(ReaderError::Io(denied)),
+            CommandError::PermissionDenied(_)
+        ));
+
+        let json = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        assert!(matches!(
+            CommandError::from(ReaderError::Json(json)),
+            CommandError::Parse(_)
+        ));
+    }
+}
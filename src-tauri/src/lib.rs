@@ -1,32 +1,62 @@
 //! Claude Code Usage Monitor - Tauri Application
 
 mod commands;
+mod error;
 pub mod usage;
 
+use std::sync::mpsc::{self, Sender};
 use std::sync::Mutex;
+use std::thread::JoinHandle;
+
+use tauri::Manager;
 
 use commands::{
-    check_data_directory, get_config, get_daily_usage, get_overall_stats, get_project_details,
-    get_projects, get_usage_stats, get_usage_stats_incremental, set_config,
+    check_data_directory, clear_cache, compare_snapshot, detect_clock_skew, diagnose_data_source,
+    export_usage_csv, export_usage_json, get_avg_message_cost_trend, get_blended_rate_trend,
+    get_config, get_cost_by_time_buckets, get_cost_forecast, get_daily_cost_with_ma,
+    get_daily_usage, get_data_health, get_env_diagnostics, get_footprint_estimate, get_hourly_usage,
+    get_message_budget, get_model_distribution, get_monthly_usage, get_most_expensive_session,
+    get_overall_stats, get_plan_projections, get_plan_status, get_plan_types,
+    get_project_cache_cost_impact, get_project_cache_ranking, get_project_details, get_projects,
+    get_projects_paged, get_recent_daily_usage, get_session_blocks, get_spend_stability,
+    get_token_composition, get_usage_for_date, get_usage_since_last_viewed, get_usage_stats,
+    get_usage_stats_incremental, get_weekly_usage, get_work_sessions, mark_viewed, save_snapshot,
+    search_projects, set_config, set_refresh_interval,
+};
+use usage::{
+    start_file_watcher, stop_background_refresh, AlertTracker, CacheManager, RefreshSignal,
 };
-use usage::{start_background_refresh, CacheManager};
 
 /// Application state containing the cache manager
 pub struct AppState {
     pub cache: Mutex<CacheManager>,
+    /// Plan limit thresholds already alerted on in the current session block
+    pub alerts: Mutex<AlertTracker>,
+    /// Wakes the background refresh loop, e.g. when the refresh interval changes
+    pub refresh_signal: Sender<RefreshSignal>,
+    /// Background refresh thread, joined on exit
+    pub refresh_task: Mutex<Option<JoinHandle<()>>>,
 }
 
-/// Default refresh interval in seconds
-const BACKGROUND_REFRESH_INTERVAL_SECS: u64 = 5;
-
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let config = get_config();
+    let mut cache = CacheManager::new();
+    cache.apply_config(&config);
+    let data_path = usage::resolve_data_path(None, &config);
+    let refresh_interval = usage::refresh_interval(&config);
+    let (refresh_signal, refresh_signals) = mpsc::channel();
+    let watcher_signal = refresh_signal.clone();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .manage(AppState {
-            cache: Mutex::new(CacheManager::new()),
+            cache: Mutex::new(cache),
+            alerts: Mutex::new(AlertTracker::new()),
+            refresh_signal,
+            refresh_task: Mutex::new(None),
         })
-        .setup(|app| {
+        .setup(move |app| {
             if cfg!(debug_assertions) {
                 app.handle().plugin(
                     tauri_plugin_log::Builder::default()
@@ -35,22 +65,74 @@ pub fn run() {
                 )?;
             }
 
-            // Start background refresh task
-            start_background_refresh(app.handle().clone(), BACKGROUND_REFRESH_INTERVAL_SECS);
+            // Refresh when session files change, polling if they cannot be watched
+            let task = start_file_watcher(
+                app.handle().clone(),
+                data_path,
+                refresh_interval,
+                watcher_signal,
+                refresh_signals,
+            );
+            if let Ok(mut refresh_task) = app.state::<AppState>().refresh_task.lock() {
+                *refresh_task = Some(task);
+            }
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             get_usage_stats,
             get_usage_stats_incremental,
+            clear_cache,
             get_projects,
+            get_projects_paged,
+            search_projects,
             get_project_details,
+            get_project_cache_ranking,
+            get_project_cache_cost_impact,
             get_daily_usage,
+            get_recent_daily_usage,
+            get_hourly_usage,
+            get_usage_for_date,
+            get_weekly_usage,
+            get_monthly_usage,
+            get_daily_cost_with_ma,
+            get_token_composition,
+            get_cost_by_time_buckets,
+            get_blended_rate_trend,
+            get_avg_message_cost_trend,
             get_overall_stats,
+            get_model_distribution,
+            get_spend_stability,
+            get_message_budget,
+            get_plan_projections,
+            get_plan_status,
+            get_plan_types,
+            get_footprint_estimate,
+            get_cost_forecast,
+            get_most_expensive_session,
+            get_session_blocks,
+            get_work_sessions,
+            export_usage_csv,
+            export_usage_json,
             get_config,
             set_config,
+            set_refresh_interval,
+            mark_viewed,
+            get_usage_since_last_viewed,
             check_data_directory,
+            get_env_diagnostics,
+            detect_clock_skew,
+            get_data_health,
+            diagnose_data_source,
+            save_snapshot,
+            compare_snapshot,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Stop refreshing before app state is torn down
+            if let tauri::RunEvent::Exit = event {
+                stop_background_refresh(app);
+            }
+        });
 }
@@ -0,0 +1,96 @@
+//! Plan limit threshold alerts for the active session block
+
+use crate::usage::models::{AlertMetric, PlanStatus, UsageAlert};
+
+/// Tracks which thresholds have already fired in the current session block so each
+/// crossing is reported once
+#[derive(Debug, Default)]
+pub struct AlertTracker {
+    session_start_time: Option<String>,
+    fired: Vec<(AlertMetric, f64)>,
+}
+
+impl AlertTracker {
+    /// Create a tracker with nothing fired
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Alerts for thresholds the active session block has newly crossed. Fired state is
+    /// reset whenever a different block (or no block) becomes active.
+    pub fn check(&mut self, status: &PlanStatus, thresholds: &[f64]) -> Vec<UsageAlert> {
+        if status.session_start_time != self.session_start_time {
+            self.session_start_time = status.session_start_time.clone();
+            self.fired.clear();
+        }
+        if status.session_start_time.is_none() {
+            return Vec::new();
+        }
+
+        let metrics = [
+            (AlertMetric::Tokens, status.token_percent),
+            (AlertMetric::Cost, status.cost_percent),
+            (AlertMetric::Messages, status.message_percent),
+        ];
+
+        let mut alerts = Vec::new();
+        for (metric, percent) in metrics {
+            let fraction_used = percent / 100.0;
+            for &threshold in thresholds {
+                if fraction_used < threshold || self.fired.contains(&(metric, threshold)) {
+                    continue;
+                }
+                self.fired.push((metric, threshold));
+                alerts.push(UsageAlert {
+                    metric,
+                    plan_type: status.plan_type.clone(),
+                    threshold,
+                    fraction_used,
+                    session_start_time: status.session_start_time.clone(),
+                });
+            }
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(session_start: &str, token_percent: f64, cost_percent: f64) -> PlanStatus {
+        PlanStatus {
+            plan_type: "pro".to_string(),
+            session_start_time: Some(session_start.to_string()),
+            token_percent,
+            cost_percent,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_alerts_fire_once_per_threshold_per_session() {
+        let mut tracker = AlertTracker::new();
+        let thresholds = [0.8, 0.95];
+
+        assert!(tracker.check(&status("10:00", 50.0, 10.0), &thresholds).is_empty());
+
+        let alerts = tracker.check(&status("10:00", 85.0, 10.0), &thresholds);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].metric, AlertMetric::Tokens);
+        assert_eq!(alerts[0].threshold, 0.8);
+        assert!((alerts[0].fraction_used - 0.85).abs() < 1e-9);
+
+        // Still above 80% on the next refresh: nothing new
+        assert!(tracker.check(&status("10:00", 90.0, 10.0), &thresholds).is_empty());
+
+        let alerts = tracker.check(&status("10:00", 96.0, 81.0), &thresholds);
+        let fired: Vec<_> = alerts.iter().map(|a| (a.metric, a.threshold)).collect();
+        assert_eq!(fired, vec![(AlertMetric::Tokens, 0.95), (AlertMetric::Cost, 0.8)]);
+
+        // A new session block re-arms every threshold
+        let alerts = tracker.check(&status("15:00", 85.0, 0.0), &thresholds);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].session_start_time.as_deref(), Some("15:00"));
+    }
+}
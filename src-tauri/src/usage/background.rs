@@ -1,82 +1,331 @@
 //! Background refresh task for push-based updates
 
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::JoinHandle;
 use std::time::Duration;
 
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use tauri::{AppHandle, Emitter, Manager};
-use tokio::time::interval;
 
-use crate::usage::models::UsageDataDelta;
+use crate::commands::get_config;
+use crate::usage::cache::CacheManager;
+use crate::usage::config::{get_data_dirs, get_projects_dir, get_projects_dirs, resolve_data_path};
+use crate::usage::models::{AppConfig, UsageDataDelta};
 use crate::usage::pricing::PricingCalculator;
-use crate::usage::CacheManager;
 use crate::AppState;
 
 /// Event name for usage data updates
 pub const USAGE_DATA_UPDATED_EVENT: &str = "usage-data-updated";
 
-/// Start the background refresh task
-pub fn start_background_refresh(app: AppHandle, refresh_interval_secs: u64) {
-    let app_handle = app.clone();
+/// Event name for plan limit threshold alerts
+pub const USAGE_ALERT_EVENT: &str = "usage-alert";
 
-    tauri::async_runtime::spawn(async move {
-        let mut ticker = interval(Duration::from_secs(refresh_interval_secs));
+/// Minimum time between watcher-triggered refreshes; session files are appended to
+/// many times per response
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
 
-        // Skip the first tick (immediate)
-        ticker.tick().await;
+/// Messages to the refresh loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshSignal {
+    /// A session file was written
+    SessionWrite,
+    /// Use a new refresh interval; the current wait restarts with it
+    IntervalChanged(Duration),
+    /// The config was saved: re-resolve the data path and watch its directories instead
+    DataPathChanged,
+    /// Stop the loop, e.g. on app exit
+    Shutdown,
+}
 
-        loop {
-            ticker.tick().await;
+/// Start the background refresh task, checking for changes every `interval`.
+/// The task runs until it receives `RefreshSignal::Shutdown`.
+pub fn start_background_refresh(
+    app: AppHandle,
+    path: Option<String>,
+    interval: Duration,
+    signals: Receiver<RefreshSignal>,
+) -> JoinHandle<()> {
+    std::thread::spawn(move || run_refresh_loop(&app, path, interval, &signals, None))
+}
 
-            // Get the app state
-            let state = match app_handle.try_state::<AppState>() {
-                Some(s) => s,
-                None => {
-                    log::warn!("AppState not available, skipping refresh");
-                    continue;
-                }
-            };
-
-            // Try to acquire the lock
-            let mut cache = match state.cache.lock() {
-                Ok(c) => c,
-                Err(e) => {
-                    log::warn!("Failed to acquire cache lock: {}", e);
-                    continue;
-                }
-            };
+/// Stop the refresh task started by the app and wait for an in-flight refresh to finish,
+/// so it does not touch app state while that is being torn down
+pub fn stop_background_refresh(app: &AppHandle) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    // The loop may already have stopped; joining below still cleans up
+    let _ = state.refresh_signal.send(RefreshSignal::Shutdown);
 
-            // Always check for changes and emit event (for heartbeat indicator)
-            let has_file_changes = cache.has_changes(None);
-
-            if has_file_changes {
-                // Perform incremental load and get delta
-                let pricing = PricingCalculator::default();
-                match cache.incremental_load_with_delta(None, &pricing) {
-                    Ok((_data, delta)) => {
-                        log::info!(
-                            "Emitting usage-data-updated event: {} updated projects, has_changes={}",
-                            delta.updated_projects.len(),
-                            delta.has_changes
-                        );
-
-                        if let Err(e) = app_handle.emit(USAGE_DATA_UPDATED_EVENT, &delta) {
-                            log::error!("Failed to emit event: {}", e);
+    let task = match state.refresh_task.lock() {
+        Ok(mut task) => task.take(),
+        Err(e) => {
+            log::warn!("Failed to acquire refresh task lock: {}", e);
+            return;
+        }
+    };
+    if let Some(task) = task {
+        if task.join().is_err() {
+            log::warn!("Background refresh task panicked");
+        }
+    }
+}
+
+/// Start an event-driven refresh that watches the projects directory recursively and
+/// reloads only when a session file is written, at most once per `WATCH_DEBOUNCE`.
+/// While idle a heartbeat is emitted every `interval`; if the watcher cannot be
+/// set up this falls back to polling at the same interval.
+/// `sender` must feed `signals`; the watcher posts session writes through it.
+pub fn start_file_watcher(
+    app: AppHandle,
+    path: Option<String>,
+    interval: Duration,
+    sender: Sender<RefreshSignal>,
+    signals: Receiver<RefreshSignal>,
+) -> JoinHandle<()> {
+    let watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+        match result {
+            Ok(event) if is_session_write(&event) => {
+                let _ = sender.send(RefreshSignal::SessionWrite);
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("File watcher error: {}", e),
+        }
+    });
+    let mut watcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            log::warn!("Failed to create file watcher, falling back to polling: {}", e);
+            return start_background_refresh(app, path, interval, signals);
+        }
+    };
+
+    // The watcher stops when dropped, so it lives as long as this thread
+    std::thread::spawn(move || run_refresh_loop(&app, path, interval, &signals, Some(&mut watcher)))
+}
+
+/// Data path the refresh loop loads, with the projects directories watched for it
+struct WatchedSource {
+    path: Option<String>,
+    dirs: Vec<PathBuf>,
+    /// Whether any of `dirs` is watched; if not, the files are polled
+    watching: bool,
+}
+
+impl WatchedSource {
+    fn new(path: Option<String>, watcher: Option<&mut RecommendedWatcher>) -> Self {
+        let dirs = watched_dirs(path.as_deref());
+        let watching = watcher.is_some_and(|watcher| watch_dirs(watcher, &dirs));
+        Self {
+            path,
+            dirs,
+            watching,
+        }
+    }
+
+    /// Switch to the data path resolved from the current config, moving the watches to
+    /// its projects directories. Returns whether the path or directories changed.
+    fn reload(&mut self, watcher: Option<&mut RecommendedWatcher>) -> bool {
+        let path = resolve_data_path(None, &get_config());
+        let dirs = watched_dirs(path.as_deref());
+        if path == self.path && dirs == self.dirs {
+            return false;
+        }
+
+        log::info!("Data path changed, now loading {:?}", dirs);
+        if let Some(watcher) = watcher {
+            for dir in &self.dirs {
+                // Directories that could not be watched before have nothing to remove
+                let _ = watcher.unwatch(dir);
+            }
+            self.watching = watch_dirs(watcher, &dirs);
+        }
+        self.path = path;
+        self.dirs = dirs;
+        true
+    }
+}
+
+/// Watch each directory recursively; returns whether at least one is watched
+fn watch_dirs(watcher: &mut RecommendedWatcher, dirs: &[PathBuf]) -> bool {
+    let mut watching = false;
+    for dir in dirs {
+        match watcher.watch(dir, RecursiveMode::Recursive) {
+            Ok(()) => watching = true,
+            Err(e) => log::warn!("Failed to watch {:?}: {}", dir, e),
+        }
+    }
+    if !watching {
+        log::warn!("No projects directory could be watched, falling back to polling");
+    }
+    watching
+}
+
+/// Wait for signals, refreshing after session writes, until told to shut down. Each
+/// `interval` without a signal either emits a heartbeat (while a directory is watched)
+/// or checks the files for changes (polling). The data path starts as `path` and is
+/// re-resolved from the config on `RefreshSignal::DataPathChanged`.
+fn run_refresh_loop(
+    app: &AppHandle,
+    path: Option<String>,
+    mut interval: Duration,
+    signals: &Receiver<RefreshSignal>,
+    mut watcher: Option<&mut RecommendedWatcher>,
+) {
+    let mut source = WatchedSource::new(path, watcher.as_deref_mut());
+
+    loop {
+        match signals.recv_timeout(interval) {
+            Ok(RefreshSignal::SessionWrite) => {
+                // Collapse the burst of writes arriving within the debounce window
+                std::thread::sleep(WATCH_DEBOUNCE);
+                while let Ok(signal) = signals.try_recv() {
+                    match signal {
+                        RefreshSignal::SessionWrite => {}
+                        RefreshSignal::IntervalChanged(new_interval) => interval = new_interval,
+                        RefreshSignal::DataPathChanged => {
+                            source.reload(watcher.as_deref_mut());
                         }
-                    }
-                    Err(e) => {
-                        log::warn!("Background refresh failed: {}", e);
+                        RefreshSignal::Shutdown => return,
                     }
                 }
-            } else {
-                // No changes, emit heartbeat event
-                let delta = UsageDataDelta {
-                    has_changes: false,
-                    ..Default::default()
-                };
-
-                if let Err(e) = app_handle.emit(USAGE_DATA_UPDATED_EVENT, &delta) {
-                    log::error!("Failed to emit heartbeat event: {}", e);
+
+                refresh_and_emit(app, source.path.as_deref(), false);
+            }
+            Ok(RefreshSignal::IntervalChanged(new_interval)) => {
+                log::info!("Refresh interval changed to {:?}", new_interval);
+                interval = new_interval;
+            }
+            Ok(RefreshSignal::DataPathChanged) => {
+                if source.reload(watcher.as_deref_mut()) {
+                    refresh_and_emit(app, source.path.as_deref(), false);
                 }
             }
+            Ok(RefreshSignal::Shutdown) => {
+                log::info!("Background refresh stopped");
+                break;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) if source.watching => emit_heartbeat(app),
+            // Always check for changes and emit event (for heartbeat indicator)
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                refresh_and_emit(app, source.path.as_deref(), true)
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
         }
-    });
+    }
+}
+
+/// Projects directories to watch: the given path, else every configured data directory,
+/// else the default data directory
+fn watched_dirs(path: Option<&str>) -> Vec<PathBuf> {
+    if path.is_none() {
+        let data_dirs = get_data_dirs(&get_config().data_paths);
+        if !data_dirs.is_empty() {
+            return data_dirs.iter().map(|dir| get_projects_dir(Some(dir))).collect();
+        }
+    }
+
+    get_projects_dirs(path)
+}
+
+/// Whether the event creates or modifies a session log file
+fn is_session_write(event: &Event) -> bool {
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        && event.paths.iter().any(|path| is_session_file(path))
+}
+
+fn is_session_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    name.ends_with(".jsonl") || name.ends_with(".jsonl.gz")
+}
+
+/// Reload changed session files and emit the delta. When `check_changes` is set, file
+/// changes are checked first and a heartbeat is emitted if there are none.
+fn refresh_and_emit(app_handle: &AppHandle, custom_path: Option<&str>, check_changes: bool) {
+    // Get the app state
+    let state = match app_handle.try_state::<AppState>() {
+        Some(s) => s,
+        None => {
+            log::warn!("AppState not available, skipping refresh");
+            return;
+        }
+    };
+
+    // Try to acquire the lock
+    let mut cache = match state.cache.lock() {
+        Ok(c) => c,
+        Err(e) => {
+            log::warn!("Failed to acquire cache lock: {}", e);
+            return;
+        }
+    };
+
+    if check_changes && !cache.has_changes(custom_path) {
+        emit_heartbeat(app_handle);
+        return;
+    }
+
+    // Perform incremental load and get delta
+    let config = get_config();
+    let pricing = PricingCalculator::from_config(&config);
+    match cache.incremental_load_with_delta(custom_path, &pricing) {
+        Ok((_data, delta)) => {
+            log::info!(
+                "Emitting usage-data-updated event: {} updated projects, has_changes={}",
+                delta.updated_projects.len(),
+                delta.has_changes
+            );
+
+            if let Err(e) = app_handle.emit(USAGE_DATA_UPDATED_EVENT, &delta) {
+                log::error!("Failed to emit event: {}", e);
+            }
+
+            emit_usage_alerts(app_handle, &state, &cache, &config);
+        }
+        Err(e) => {
+            log::warn!("Background refresh failed: {}", e);
+        }
+    }
+}
+
+/// Emit a `usage-alert` event for each threshold the active session block newly crossed
+fn emit_usage_alerts(
+    app_handle: &AppHandle,
+    state: &AppState,
+    cache: &CacheManager,
+    config: &AppConfig,
+) {
+    let status = cache.plan_status(&config.plan_type);
+    let alerts = match state.alerts.lock() {
+        Ok(mut tracker) => tracker.check(&status, &config.alert_thresholds),
+        Err(e) => {
+            log::warn!("Failed to acquire alert lock: {}", e);
+            return;
+        }
+    };
+
+    for alert in alerts {
+        log::info!(
+            "Emitting usage-alert event: {:?} at {:.0}% of the {} limit",
+            alert.metric,
+            alert.fraction_used * 100.0,
+            alert.plan_type
+        );
+        if let Err(e) = app_handle.emit(USAGE_ALERT_EVENT, &alert) {
+            log::error!("Failed to emit usage alert: {}", e);
+        }
+    }
+}
+
+/// Emit an event without changes so the frontend's heartbeat indicator stays live
+fn emit_heartbeat(app_handle: &AppHandle) {
+    let delta = UsageDataDelta {
+        has_changes: false,
+        ..Default::default()
+    };
+
+    if let Err(e) = app_handle.emit(USAGE_DATA_UPDATED_EVENT, &delta) {
+        log::error!("Failed to emit heartbeat event: {}", e);
+    }
 }
@@ -1,12 +1,42 @@
 //! Cache manager for incremental data refresh
 
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Instant, SystemTime};
 
-use crate::usage::models::{UsageData, UsageDataDelta, UsageEntry};
-use crate::usage::pricing::PricingCalculator;
-use crate::usage::reader::{list_projects, read_jsonl_file, ProjectData, ReaderError};
+use crate::usage::clock::{Clock, SystemClock};
+use crate::usage::models::{AppConfig, PlanStatus, UsageData, UsageDataDelta, UsageEntry};
+use crate::usage::pricing::{PricingCalculator, DEFAULT_COST_DECIMALS};
+use crate::usage::reader::{
+    dedup_entries, is_compressed, is_duplicate_entry, list_projects_with_options, read_jsonl_file,
+    read_jsonl_file_from, ProjectData, ReaderError, ScanOptions,
+};
+use crate::usage::stats::{
+    calculate_plan_status, SessionConfig, UsageFold, DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+};
+use crate::usage::timezone::UsageTimezone;
+
+/// Default seconds between directory rescans for new projects
+pub const DEFAULT_RESCAN_INTERVAL_SECS: u64 = 60;
+
+/// Largest file whose content is hashed for change detection; bigger files rely on mtime
+const MAX_HASHED_FILE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Hash a file's content, or None if it is unreadable or larger than `MAX_HASHED_FILE_BYTES`
+fn hash_file_content(file: &Path) -> Option<u64> {
+    use std::hash::{Hash, Hasher};
+
+    let size = std::fs::metadata(file).ok()?.len();
+    if size > MAX_HASHED_FILE_BYTES {
+        return None;
+    }
+
+    let content = std::fs::read(file).ok()?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    Some(hasher.finish())
+}
 
 /// Cached data for a single file
 #[derive(Debug, Clone)]
@@ -15,10 +45,46 @@ struct FileCacheEntry {
     mtime: SystemTime,
     /// Parsed entries from this file
     entries: Vec<UsageEntry>,
+    /// Hash of the file content when content hashing is enabled and the file is small enough
+    content_hash: Option<u64>,
+    /// Bytes parsed so far, where appended lines are read from. None for compressed files
+    /// and entries cached from outside, which are always re-read in full.
+    offset: Option<u64>,
+    /// Position in `entries` of each dedup key, so a repeated message replaces its entry
+    key_index: HashMap<String, usize>,
+}
+
+impl FileCacheEntry {
+    fn new(mtime: SystemTime, content_hash: Option<u64>) -> Self {
+        Self {
+            mtime,
+            entries: Vec::new(),
+            content_hash,
+            offset: None,
+            key_index: HashMap::new(),
+        }
+    }
+
+    /// Add entries read after the cached ones; a known key replaces the earlier entry
+    fn merge(&mut self, entries: Vec<(Option<String>, UsageEntry)>) {
+        for (key, entry) in entries {
+            let Some(key) = key else {
+                self.entries.push(entry);
+                continue;
+            };
+            match self.key_index.get(&key) {
+                Some(&index) => self.entries[index] = entry,
+                None => {
+                    self.key_index.insert(key, self.entries.len());
+                    self.entries.push(entry);
+                }
+            }
+        }
+    }
 }
 
 /// Cache manager for incremental data refresh
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct CacheManager {
     /// Cached data per file path
     file_cache: HashMap<PathBuf, FileCacheEntry>,
@@ -28,6 +94,44 @@ pub struct CacheManager {
     last_full_refresh: Option<Instant>,
     /// Last directory scan time (for detecting new projects)
     last_dir_scan: Option<Instant>,
+    /// How project directories are discovered
+    scan_options: ScanOptions,
+    /// Source of the current time for session timing and today's stats
+    clock: Arc<dyn Clock>,
+    /// Floor on session block duration (minutes) for burn rate
+    min_block_minutes: f64,
+    /// How entries are grouped into session blocks
+    session: SessionConfig,
+    /// Zone usage is bucketed into dates in
+    timezone: UsageTimezone,
+    /// Decimal places reported costs are rounded to
+    cost_decimals: u32,
+    /// Also compare content hashes so rewrites that preserve mtime are detected
+    content_hashing: bool,
+    /// Seconds between directory rescans for new projects (0 = every cycle)
+    rescan_interval_secs: u64,
+    /// Usage data from the last load, with the data path it was loaded from
+    last_usage: Option<(Option<String>, UsageData)>,
+}
+
+impl Default for CacheManager {
+    fn default() -> Self {
+        Self {
+            file_cache: HashMap::new(),
+            cached_projects: Vec::new(),
+            last_full_refresh: None,
+            last_dir_scan: None,
+            scan_options: ScanOptions::default(),
+            clock: Arc::new(SystemClock),
+            min_block_minutes: DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+            session: SessionConfig::default(),
+            timezone: UsageTimezone::default(),
+            cost_decimals: DEFAULT_COST_DECIMALS,
+            content_hashing: false,
+            rescan_interval_secs: DEFAULT_RESCAN_INTERVAL_SECS,
+            last_usage: None,
+        }
+    }
 }
 
 /// Result of checking file changes
@@ -47,12 +151,76 @@ impl CacheManager {
         Self::default()
     }
 
+    /// Set how project directories are discovered (takes effect on the next load)
+    pub fn set_scan_options(&mut self, options: ScanOptions) {
+        self.scan_options = options;
+    }
+
+    /// Set the clock used for time-dependent statistics
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Set the minimum block duration (minutes) used by the burn rate calculation
+    pub fn set_min_block_duration(&mut self, minutes: f64) {
+        self.min_block_minutes = minutes;
+    }
+
+    /// Set how entries are grouped into session blocks
+    pub fn set_session_config(&mut self, session: SessionConfig) {
+        self.session = session;
+    }
+
+    /// Set the zone usage is bucketed into dates in
+    pub fn set_timezone(&mut self, timezone: UsageTimezone) {
+        self.timezone = timezone;
+    }
+
+    /// Set the decimal places reported costs are rounded to
+    pub fn set_cost_decimals(&mut self, decimals: u32) {
+        self.cost_decimals = decimals;
+    }
+
+    /// Enable comparing content hashes (for files up to `MAX_HASHED_FILE_BYTES`) in
+    /// addition to mtimes when checking for changes
+    pub fn set_content_hashing(&mut self, enabled: bool) {
+        self.content_hashing = enabled;
+    }
+
+    /// Set the seconds between directory rescans for new projects (0 = every cycle)
+    pub fn set_rescan_interval(&mut self, secs: u64) {
+        self.rescan_interval_secs = secs;
+    }
+
+    /// Apply the scan, session, timezone, precision and refresh settings of `config`.
+    /// Cached file entries are kept; the last usage data is dropped as it may be stale.
+    pub fn apply_config(&mut self, config: &AppConfig) {
+        self.set_scan_options(ScanOptions::from_config(config));
+        self.set_min_block_duration(config.min_block_duration_minutes);
+        self.set_session_config(SessionConfig::from_config(config));
+        self.set_timezone(UsageTimezone::from_config(config));
+        self.set_cost_decimals(config.cost_decimals);
+        self.set_content_hashing(config.content_hash_cache);
+        self.set_rescan_interval(config.rescan_interval_secs);
+        self.last_usage = None;
+    }
+
     /// Clear all cached data
     pub fn clear(&mut self) {
         self.file_cache.clear();
         self.cached_projects.clear();
         self.last_full_refresh = None;
         self.last_dir_scan = None;
+        self.last_usage = None;
+    }
+
+    /// Usage data computed by the last load from `custom_path`, without checking for
+    /// file changes (so the next refresh still reports them)
+    pub fn last_usage_data(&self, custom_path: Option<&str>) -> Option<&UsageData> {
+        self.last_usage
+            .as_ref()
+            .filter(|(path, _)| path.as_deref() == custom_path)
+            .map(|(_, data)| data)
     }
 
     /// Check if cache is empty (first load)
@@ -69,7 +237,7 @@ impl CacheManager {
     pub fn should_rescan_dirs(&self) -> bool {
         match self.last_dir_scan {
             None => true,
-            Some(t) => t.elapsed().as_secs() >= 60, // Rescan every 60 seconds
+            Some(t) => t.elapsed().as_secs() >= self.rescan_interval_secs,
         }
     }
 
@@ -95,6 +263,12 @@ impl CacheManager {
                 Some(cached) => {
                     if current_mtime > cached.mtime {
                         changes.modified.push(file.clone());
+                    } else if self.content_hashing
+                        && cached.content_hash.is_some()
+                        && hash_file_content(file) != cached.content_hash
+                    {
+                        // Same mtime but different content (rewritten or restored file)
+                        changes.modified.push(file.clone());
                     }
                 }
                 None => {
@@ -123,11 +297,60 @@ impl CacheManager {
         let mtime = std::fs::metadata(file)
             .and_then(|m| m.modified())
             .unwrap_or_else(|_| SystemTime::now());
+        let content_hash = if self.content_hashing {
+            hash_file_content(file)
+        } else {
+            None
+        };
 
-        self.file_cache.insert(
-            file.clone(),
-            FileCacheEntry { mtime, entries },
-        );
+        let mut cached = FileCacheEntry::new(mtime, content_hash);
+        cached.entries = entries;
+        self.file_cache.insert(file.clone(), cached);
+
+        Ok(())
+    }
+
+    /// Read a new or changed file into the cache. If the file only grew since it was
+    /// cached (newer mtime, size at least the parsed offset), just the appended bytes are
+    /// parsed and merged; otherwise, such as after truncation, it is read in full.
+    fn refresh_file(
+        &mut self,
+        file: &Path,
+        pricing: &PricingCalculator,
+    ) -> Result<(), ReaderError> {
+        let meta = std::fs::metadata(file)?;
+        let mtime = meta.modified().unwrap_or_else(|_| SystemTime::now());
+        let content_hash = if self.content_hashing {
+            hash_file_content(file)
+        } else {
+            None
+        };
+
+        if is_compressed(file) {
+            let mut cached = FileCacheEntry::new(mtime, content_hash);
+            cached.entries = read_jsonl_file(file, pricing)?;
+            self.file_cache.insert(file.to_path_buf(), cached);
+            return Ok(());
+        }
+
+        let append_from = self.file_cache.get(file).and_then(|cached| {
+            cached
+                .offset
+                .filter(|&offset| mtime > cached.mtime && meta.len() >= offset)
+        });
+        let appended = read_jsonl_file_from(file, append_from.unwrap_or(0), pricing)?;
+
+        let cached = self
+            .file_cache
+            .entry(file.to_path_buf())
+            .or_insert_with(|| FileCacheEntry::new(mtime, content_hash));
+        if append_from.is_none() {
+            *cached = FileCacheEntry::new(mtime, content_hash);
+        }
+        cached.merge(appended.entries);
+        cached.mtime = mtime;
+        cached.content_hash = content_hash;
+        cached.offset = Some(appended.end_offset);
 
         Ok(())
     }
@@ -142,6 +365,27 @@ impl CacheManager {
         self.file_cache.get(file).map(|entry| &entry.entries)
     }
 
+    /// Active session block consumption against the plan's limits, from cached entries.
+    /// Blocks are built over the full history, counting a message logged in several
+    /// files or projects once, so the result matches `get_plan_status`.
+    pub fn plan_status(&self, plan_type: &str) -> PlanStatus {
+        let mut entries: Vec<UsageEntry> = self
+            .file_cache
+            .values()
+            .flat_map(|cached| cached.entries.iter())
+            .cloned()
+            .collect();
+        entries.sort_by_key(|e| e.timestamp);
+        dedup_entries(&mut entries);
+        calculate_plan_status(
+            &entries,
+            plan_type,
+            &self.clock.now(),
+            &self.session,
+            self.cost_decimals,
+        )
+    }
+
     /// Update cached project list
     pub fn update_projects(&mut self, projects: Vec<ProjectData>) {
         self.cached_projects = projects;
@@ -166,7 +410,7 @@ impl CacheManager {
         }
 
         // Get current files
-        let projects = match list_projects(custom_path) {
+        let projects = match list_projects_with_options(custom_path, &self.scan_options) {
             Ok(p) => p,
             Err(_) => return false,
         };
@@ -211,7 +455,7 @@ impl CacheManager {
 
         // Check if we should rescan directories
         let projects = if self.should_rescan_dirs() {
-            let new_projects = list_projects(custom_path)?;
+            let new_projects = list_projects_with_options(custom_path, &self.scan_options)?;
             self.update_projects(
                 new_projects
                     .iter()
@@ -225,7 +469,7 @@ impl CacheManager {
             );
             new_projects
         } else {
-            list_projects(custom_path)?
+            list_projects_with_options(custom_path, &self.scan_options)?
         };
 
         // Collect all current files
@@ -261,40 +505,12 @@ impl CacheManager {
 
         // Process modified and new files
         for file in changes.modified.iter().chain(changes.new_files.iter()) {
-            match read_jsonl_file(file, pricing) {
-                Ok(entries) => {
-                    self.update_file_cache(file, entries)?;
-                }
-                Err(e) => {
-                    log::warn!("Failed to read file {:?}: {}", file, e);
-                }
+            if let Err(e) = self.refresh_file(file, pricing) {
+                log::warn!("Failed to read file {:?}: {}", file, e);
             }
         }
 
-        // Build usage data from cache
-        let mut all_data: Vec<(ProjectData, Vec<UsageEntry>)> = Vec::new();
-
-        for project in &projects {
-            let mut project_entries = Vec::new();
-
-            for session_file in &project.session_files {
-                if let Some(entries) = self.get_file_entries(session_file) {
-                    project_entries.extend(entries.clone());
-                }
-            }
-
-            all_data.push((
-                ProjectData {
-                    encoded_path: project.encoded_path.clone(),
-                    decoded_path: project.decoded_path.clone(),
-                    display_name: project.display_name.clone(),
-                    session_files: project.session_files.clone(),
-                },
-                project_entries,
-            ));
-        }
-
-        let data = calculate_usage_data(all_data)?;
+        let data = self.calculate_usage_data(custom_path, &projects, pricing);
 
         // Build delta with only changed projects
         let updated_projects: Vec<_> = data
@@ -335,43 +551,25 @@ impl CacheManager {
         self.clear();
 
         // Load projects
-        let projects = list_projects(custom_path)?;
+        let projects = list_projects_with_options(custom_path, &self.scan_options)?;
 
         // Load all files and populate cache
-        let mut all_data: Vec<(ProjectData, Vec<UsageEntry>)> = Vec::new();
-
-        for project in projects {
-            let mut project_entries = Vec::new();
-
+        for project in &projects {
             for session_file in &project.session_files {
-                match read_jsonl_file(session_file, pricing) {
-                    Ok(entries) => {
-                        self.update_file_cache(session_file, entries.clone())?;
-                        project_entries.extend(entries);
-                    }
-                    Err(e) => {
-                        log::warn!("Failed to read session file {:?}: {}", session_file, e);
-                    }
+                if let Err(e) = self.refresh_file(session_file, pricing) {
+                    log::warn!("Failed to read session file {:?}: {}", session_file, e);
                 }
             }
-
-            all_data.push((project, project_entries));
         }
 
+        // Calculate statistics
+        let data = self.calculate_usage_data(custom_path, &projects, pricing);
+
         // Update project cache
-        let projects: Vec<ProjectData> = all_data.iter().map(|(p, _)| {
-            ProjectData {
-                encoded_path: p.encoded_path.clone(),
-                decoded_path: p.decoded_path.clone(),
-                display_name: p.display_name.clone(),
-                session_files: p.session_files.clone(),
-            }
-        }).collect();
         self.update_projects(projects);
         self.mark_full_refresh();
 
-        // Calculate statistics
-        calculate_usage_data(all_data)
+        Ok(data)
     }
 
     /// Perform incremental load (only read changed files)
@@ -387,7 +585,7 @@ impl CacheManager {
 
         // Check if we should rescan directories
         let projects = if self.should_rescan_dirs() {
-            let new_projects = list_projects(custom_path)?;
+            let new_projects = list_projects_with_options(custom_path, &self.scan_options)?;
             self.update_projects(new_projects.iter().map(|p| ProjectData {
                 encoded_path: p.encoded_path.clone(),
                 decoded_path: p.decoded_path.clone(),
@@ -397,7 +595,7 @@ impl CacheManager {
             new_projects
         } else {
             // Use cached projects but refresh session file list
-            list_projects(custom_path)?
+            list_projects_with_options(custom_path, &self.scan_options)?
         };
 
         // Collect all current files
@@ -416,443 +614,437 @@ impl CacheManager {
 
         // Process modified and new files
         for file in changes.modified.iter().chain(changes.new_files.iter()) {
-            match read_jsonl_file(file, pricing) {
-                Ok(entries) => {
-                    self.update_file_cache(file, entries)?;
-                }
-                Err(e) => {
-                    log::warn!("Failed to read file {:?}: {}", file, e);
-                }
+            if let Err(e) = self.refresh_file(file, pricing) {
+                log::warn!("Failed to read file {:?}: {}", file, e);
             }
         }
 
-        // Build usage data from cache
-        let mut all_data: Vec<(ProjectData, Vec<UsageEntry>)> = Vec::new();
+        Ok(self.calculate_usage_data(custom_path, &projects, pricing))
+    }
 
-        for project in &projects {
-            let mut project_entries = Vec::new();
+    /// Calculate usage data from the cached entries of `projects`' session files with this
+    /// cache's clock, session and timezone options, and keep it as the last usage data
+    /// loaded from `custom_path`. Entries are folded straight from the file cache; one also
+    /// found in an earlier project or session file is counted once.
+    fn calculate_usage_data(
+        &mut self,
+        custom_path: Option<&str>,
+        projects: &[ProjectData],
+        pricing: &PricingCalculator,
+    ) -> UsageData {
+        let now = self.clock.now();
+        let mut fold = UsageFold::new(&now, &self.session, &self.timezone, self.cost_decimals);
+        let mut seen = HashSet::new();
 
-            for session_file in &project.session_files {
-                if let Some(entries) = self.get_file_entries(session_file) {
-                    project_entries.extend(entries.clone());
+        for project in projects {
+            fold.start_project(project);
+            let entries = project
+                .session_files
+                .iter()
+                .filter_map(|file| self.get_file_entries(file))
+                .flatten();
+            for entry in entries {
+                if !is_duplicate_entry(&mut seen, entry) {
+                    fold.add(entry);
                 }
             }
-
-            all_data.push((
-                ProjectData {
-                    encoded_path: project.encoded_path.clone(),
-                    decoded_path: project.decoded_path.clone(),
-                    display_name: project.display_name.clone(),
-                    session_files: project.session_files.clone(),
-                },
-                project_entries,
-            ));
         }
 
-        calculate_usage_data(all_data)
+        let data = fold.finish(&now, self.min_block_minutes, pricing);
+        self.last_usage = Some((custom_path.map(str::to_string), data.clone()));
+        data
     }
 }
 
-/// Session duration in minutes (5 hours)
-const SESSION_DURATION_MINUTES: i64 = 300;
-
-/// Session block for proportional burn rate calculation
-#[derive(Debug)]
-struct SessionBlock {
-    start_time: chrono::DateTime<chrono::Utc>,
-    actual_end_time: chrono::DateTime<chrono::Utc>,
-    total_tokens: u64,
-    total_cost: f64,
-    is_active: bool,
-}
-
-/// Transform entries into session blocks (5-hour blocks starting at hour boundary)
-fn transform_to_blocks(entries: &[UsageEntry]) -> Vec<SessionBlock> {
-    use chrono::{Duration, Timelike, Utc};
-
-    if entries.is_empty() {
-        return Vec::new();
-    }
-
-    let mut blocks: Vec<SessionBlock> = Vec::new();
-    let session_duration = Duration::hours(5);
-    let mut current_block: Option<SessionBlock> = None;
-
-    for entry in entries {
-        let should_create_new = match &current_block {
-            None => true,
-            Some(block) => entry.timestamp >= block.start_time + session_duration,
-        };
-
-        if should_create_new {
-            if let Some(block) = current_block.take() {
-                blocks.push(block);
-            }
-
-            let start_time = entry.timestamp
-                .with_minute(0).unwrap()
-                .with_second(0).unwrap()
-                .with_nanosecond(0).unwrap();
-
-            current_block = Some(SessionBlock {
-                start_time,
-                actual_end_time: entry.timestamp,
-                total_tokens: 0,
-                total_cost: 0.0,
-                is_active: false,
-            });
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage::stats::calculate_usage_data;
+    use crate::usage::clock::FixedClock;
+    use chrono::{Local, TimeZone, Utc};
+
+    fn entry_at(timestamp: chrono::DateTime<Utc>) -> UsageEntry {
+        UsageEntry {
+            timestamp,
+            input_tokens: 100,
+            output_tokens: 200,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            cost_usd: 0.01,
+            model: "claude-sonnet-4-20250514".to_string(),
+            message_id: String::new(),
+            request_id: None,
         }
+    }
 
-        if let Some(ref mut block) = current_block {
-            block.total_tokens += entry.input_tokens + entry.output_tokens;
-            block.total_cost += entry.cost_usd;
-            block.actual_end_time = entry.timestamp;
+    fn project() -> ProjectData {
+        ProjectData {
+            encoded_path: "D--code-project".to_string(),
+            decoded_path: "D:\\code\\project".to_string(),
+            display_name: "project".to_string(),
+            session_files: vec![PathBuf::from("session.jsonl")],
         }
     }
 
-    if let Some(mut block) = current_block {
-        let now = Utc::now();
-        if block.start_time + session_duration > now {
-            block.is_active = true;
-        }
-        blocks.push(block);
+    #[test]
+    fn test_session_timing_with_fixed_clock() {
+        let now = FixedClock(Utc.with_ymd_and_hms(2025, 3, 10, 14, 40, 0).unwrap()).now();
+        let entries = vec![
+            entry_at(Utc.with_ymd_and_hms(2025, 3, 9, 2, 0, 0).unwrap()),
+            entry_at(Utc.with_ymd_and_hms(2025, 3, 10, 13, 5, 0).unwrap()),
+            entry_at(Utc.with_ymd_and_hms(2025, 3, 10, 14, 30, 0).unwrap()),
+        ];
+        let today_local = now.with_timezone(&Local).date_naive();
+        let expected_today = entries
+            .iter()
+            .filter(|e| e.timestamp.with_timezone(&Local).date_naive() == today_local)
+            .count() as u32;
+
+        let data = calculate_usage_data(
+            vec![(project(), entries)],
+            &now,
+            DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+            &SessionConfig::default(),
+            &UsageTimezone::default(),
+            &PricingCalculator::new(),
+            DEFAULT_COST_DECIMALS,
+        );
+        let stats = data.overall_stats;
+
+        let session_start = Utc.with_ymd_and_hms(2025, 3, 10, 13, 0, 0).unwrap();
+        assert_eq!(stats.session_start_time, Some(session_start.to_rfc3339()));
+        // 100 minutes into the 300 minute block
+        assert_eq!(stats.time_to_reset_minutes, 200);
+        assert!(stats.burn_rate.is_some());
+        // Today's stats follow the injected clock rather than the system time
+        assert_eq!(stats.today_stats.message_count, expected_today);
+        assert!(expected_today > 0);
     }
 
-    blocks
-}
+    #[test]
+    fn test_today_stats_match_last_daily_entry_at_local_midnight() {
+        // Local midnight generally falls on a different UTC date than the UTC midnight
+        let local_midnight = Local
+            .with_ymd_and_hms(2025, 3, 10, 0, 0, 0)
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc);
+        let entries = vec![
+            entry_at(local_midnight - chrono::Duration::minutes(1)),
+            entry_at(local_midnight + chrono::Duration::minutes(1)),
+            entry_at(local_midnight + chrono::Duration::minutes(30)),
+        ];
+        let now = local_midnight + chrono::Duration::hours(2);
+
+        let data = calculate_usage_data(
+            vec![(project(), entries)],
+            &now,
+            DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+            &SessionConfig::default(),
+            &UsageTimezone::default(),
+            &PricingCalculator::new(),
+            DEFAULT_COST_DECIMALS,
+        );
+        let today = &data.overall_stats.today_stats;
+        let last_day = data.daily_usage.last().unwrap();
+
+        assert_eq!(data.daily_usage.len(), 2);
+        assert_eq!(last_day.date, "2025-03-10");
+        assert_eq!(last_day.message_count, today.message_count);
+        assert_eq!(last_day.input_tokens, today.input_tokens);
+        assert_eq!(last_day.output_tokens, today.output_tokens);
+        assert_eq!(last_day.cost_usd, today.cost_usd);
+        assert_eq!(today.message_count, 2);
+    }
 
-/// Calculate hourly burn rate using block-based proportional allocation
-fn calculate_hourly_burn_rate(blocks: &[SessionBlock], current_time: &chrono::DateTime<chrono::Utc>) -> (f64, f64) {
-    use chrono::Duration;
+    #[test]
+    fn test_streaming_aggregation_matches_combined_entries() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 10, 14, 40, 0).unwrap();
+        let mut opus = entry_at(Utc.with_ymd_and_hms(2025, 3, 10, 14, 0, 0).unwrap());
+        opus.model = "claude-opus-4-20250514".to_string();
+        opus.cost_usd = 0.5;
+        let all_data = vec![
+            (
+                project(),
+                vec![
+                    entry_at(Utc.with_ymd_and_hms(2025, 3, 8, 9, 0, 0).unwrap()),
+                    entry_at(Utc.with_ymd_and_hms(2025, 3, 10, 12, 15, 0).unwrap()),
+                    opus,
+                ],
+            ),
+            (
+                ProjectData {
+                    decoded_path: "D:\\code\\other".to_string(),
+                    ..project()
+                },
+                vec![
+                    entry_at(Utc.with_ymd_and_hms(2025, 3, 9, 23, 30, 0).unwrap()),
+                    entry_at(Utc.with_ymd_and_hms(2025, 3, 10, 14, 20, 0).unwrap()),
+                ],
+            ),
+        ];
+
+        // Reference: the same entries read as a single project
+        let pricing = PricingCalculator::new();
+        let combined: Vec<_> = all_data.iter().flat_map(|(_, e)| e.clone()).collect();
+        let mut expected = calculate_usage_data(
+            vec![(project(), combined)],
+            &now,
+            DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+            &SessionConfig::default(),
+            &UsageTimezone::default(),
+            &pricing,
+            DEFAULT_COST_DECIMALS,
+        );
+        // Only the per-project counts differ
+        expected.overall_stats.project_count = 2;
+        expected.overall_stats.total_sessions = 2;
+
+        let data = calculate_usage_data(
+            all_data,
+            &now,
+            DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+            &SessionConfig::default(),
+            &UsageTimezone::default(),
+            &pricing,
+            DEFAULT_COST_DECIMALS,
+        );
 
-    if blocks.is_empty() {
-        return (0.0, 0.0);
+        assert_eq!(
+            serde_json::to_value(&data.daily_usage).unwrap(),
+            serde_json::to_value(&expected.daily_usage).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(&data.overall_stats).unwrap(),
+            serde_json::to_value(&expected.overall_stats).unwrap()
+        );
+        assert!(data.overall_stats.burn_rate.is_some());
     }
 
-    let one_hour_ago = *current_time - Duration::hours(1);
-    let mut total_tokens: f64 = 0.0;
-    let mut total_cost: f64 = 0.0;
+    #[test]
+    fn test_content_hashing_detects_change_with_preserved_mtime() {
+        let dir = std::env::temp_dir().join(format!("ccm-cache-hash-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("session.jsonl");
+        let files = vec![file.clone()];
+        std::fs::write(&file, "{\"a\":1}").unwrap();
+        let mtime = std::fs::metadata(&file).unwrap().modified().unwrap();
+
+        let mut plain = CacheManager::new();
+        plain.update_file_cache(&file, Vec::new()).unwrap();
+        let mut hashed = CacheManager::new();
+        hashed.set_content_hashing(true);
+        hashed.update_file_cache(&file, Vec::new()).unwrap();
+        assert!(hashed.check_file_changes(&files).unwrap().modified.is_empty());
+
+        // Rewrite the content, then restore the original mtime
+        std::fs::write(&file, "{\"a\":2}").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+
+        assert!(plain.check_file_changes(&files).unwrap().modified.is_empty());
+        assert_eq!(hashed.check_file_changes(&files).unwrap().modified, files);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 
-    for block in blocks {
-        let session_actual_end = if block.is_active {
-            *current_time
-        } else {
-            block.actual_end_time
+    #[test]
+    fn test_refresh_reads_only_appended_lines() {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("ccm-cache-append-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("session.jsonl");
+        let line = |request_id: &str, output_tokens: u64| {
+            let event = serde_json::json!({
+                "type": "assistant",
+                "timestamp": "2025-03-10T14:00:00Z",
+                "requestId": request_id,
+                "message": {
+                    "id": format!("msg_{}", request_id),
+                    "model": "claude-sonnet-4-20250514",
+                    "usage": {"input_tokens": 10, "output_tokens": output_tokens}
+                }
+            });
+            format!("{}\n", event)
         };
-
-        if session_actual_end < one_hour_ago {
-            continue;
-        }
-
-        let session_start_in_hour = if block.start_time > one_hour_ago {
-            block.start_time
-        } else {
-            one_hour_ago
+        let bump_mtime = |file: &Path| {
+            let mtime = std::fs::metadata(file).unwrap().modified().unwrap();
+            let handle = std::fs::File::options().write(true).open(file).unwrap();
+            handle.set_modified(mtime + std::time::Duration::from_secs(5)).unwrap();
         };
-
-        let session_end_in_hour = if session_actual_end < *current_time {
-            session_actual_end
-        } else {
-            *current_time
+        let output_tokens = |cache: &CacheManager| -> u64 {
+            let entries = cache.get_file_entries(&file).unwrap();
+            entries.iter().map(|e| e.output_tokens).sum()
         };
-
-        if session_end_in_hour <= session_start_in_hour {
-            continue;
-        }
-
-        let total_session_duration = (session_actual_end - block.start_time).num_seconds() as f64 / 60.0;
-        let hour_duration = (session_end_in_hour - session_start_in_hour).num_seconds() as f64 / 60.0;
-
-        if total_session_duration > 0.0 {
-            let proportion = hour_duration / total_session_duration;
-            total_tokens += block.total_tokens as f64 * proportion;
-            total_cost += block.total_cost * proportion;
-        }
-    }
-
-    if total_tokens > 0.0 {
-        (total_tokens / 60.0, total_cost / 60.0 * 60.0)
-    } else {
-        (0.0, 0.0)
+        let pricing = PricingCalculator::new();
+        let mut cache = CacheManager::new();
+
+        // The last line is still being written and is left for the next read
+        let partial = line("req_3", 100);
+        let first = line("req_1", 5) + &line("req_2", 7);
+        std::fs::write(&file, first.clone() + &partial[..20]).unwrap();
+        cache.refresh_file(&file, &pricing).unwrap();
+        assert_eq!(output_tokens(&cache), 12);
+        assert_eq!(cache.file_cache[&file].offset, Some(first.len() as u64));
+
+        // Finish the partial line and re-log req_1 with its final usage
+        let mut handle = std::fs::File::options().append(true).open(&file).unwrap();
+        write!(handle, "{}{}", &partial[20..], line("req_1", 50)).unwrap();
+        drop(handle);
+        bump_mtime(&file);
+        cache.refresh_file(&file, &pricing).unwrap();
+        assert_eq!(cache.get_file_entries(&file).unwrap().len(), 3);
+        assert_eq!(output_tokens(&cache), 157);
+
+        // A truncated file is read again from the start
+        std::fs::write(&file, line("req_9", 1)).unwrap();
+        bump_mtime(&file);
+        cache.refresh_file(&file, &pricing).unwrap();
+        assert_eq!(output_tokens(&cache), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
-}
 
-/// Calculate time to reset based on session start time
-fn calculate_time_to_reset(session_start: Option<&chrono::DateTime<chrono::Utc>>, now: &chrono::DateTime<chrono::Utc>) -> u32 {
-    match session_start {
-        Some(start) => {
-            let elapsed_minutes = (*now - *start).num_minutes();
-            if elapsed_minutes < 0 {
-                return SESSION_DURATION_MINUTES as u32;
+    #[test]
+    fn test_plan_status_counts_message_in_two_projects_once() {
+        use crate::usage::reader::load_all_entries;
+
+        let root = std::env::temp_dir().join(format!("ccm-cache-plan-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let line = serde_json::json!({
+            "type": "assistant",
+            "timestamp": "2025-03-10T14:00:00Z",
+            "requestId": "req_1",
+            "message": {
+                "id": "msg_1",
+                "model": "claude-sonnet-4-20250514",
+                "usage": {"input_tokens": 400, "output_tokens": 600}
             }
-            let remaining = SESSION_DURATION_MINUTES - (elapsed_minutes % SESSION_DURATION_MINUTES);
-            remaining.max(0) as u32
-        }
-        None => SESSION_DURATION_MINUTES as u32,
-    }
-}
-
-/// Normalize model name for consistent grouping
-fn normalize_model_name(model: &str) -> String {
-    let model_lower = model.to_lowercase();
-
-    // Keep new claude-4 model names as-is
-    if model_lower.contains("claude-opus-4-")
-        || model_lower.contains("claude-sonnet-4-")
-        || model_lower.contains("claude-haiku-4-")
-        || model_lower.contains("opus-4-")
-        || model_lower.contains("sonnet-4-")
-        || model_lower.contains("haiku-4-")
-    {
-        return model_lower;
-    }
-
-    // Normalize older model names
-    if model_lower.contains("opus") {
-        if model_lower.contains("4-") {
-            return model_lower;
-        }
-        return "claude-3-opus".to_string();
-    }
-    if model_lower.contains("sonnet") {
-        if model_lower.contains("4-") {
-            return model_lower;
-        }
-        if model_lower.contains("3.5") || model_lower.contains("3-5") {
-            return "claude-3-5-sonnet".to_string();
-        }
-        return "claude-3-sonnet".to_string();
-    }
-    if model_lower.contains("haiku") {
-        if model_lower.contains("3.5") || model_lower.contains("3-5") {
-            return "claude-3-5-haiku".to_string();
-        }
-        return "claude-3-haiku".to_string();
-    }
-
-    model.to_string()
-}
-
-/// Calculate model distribution from entries
-fn calculate_model_distribution(entries: &[UsageEntry]) -> Vec<crate::usage::models::ModelStats> {
-    use std::collections::HashMap;
-    use crate::usage::models::ModelStats;
-
-    let mut model_map: HashMap<String, ModelStats> = HashMap::new();
-    let mut total_tokens: u64 = 0;
-
-    for entry in entries {
-        let model_key = normalize_model_name(&entry.model);
-        let entry_total = entry.input_tokens + entry.output_tokens;
-        total_tokens += entry_total;
-
-        let stats = model_map.entry(model_key.clone()).or_insert_with(|| ModelStats {
-            model: model_key,
-            ..Default::default()
-        });
-
-        stats.input_tokens += entry.input_tokens;
-        stats.output_tokens += entry.output_tokens;
-        stats.cache_creation_tokens += entry.cache_creation_tokens;
-        stats.cache_read_tokens += entry.cache_read_tokens;
-        stats.cost_usd += entry.cost_usd;
-        stats.message_count += 1;
-        stats.total_tokens += entry_total;
-    }
-
-    // Calculate percentages and round costs
-    let mut model_list: Vec<_> = model_map
-        .into_values()
-        .map(|mut m| {
-            m.percentage = if total_tokens > 0 {
-                (m.total_tokens as f64 / total_tokens as f64) * 100.0
-            } else {
-                0.0
-            };
-            m.cost_usd = (m.cost_usd * 1_000_000.0).round() / 1_000_000.0;
-            m.percentage = (m.percentage * 100.0).round() / 100.0;
-            m
         })
-        .collect();
-
-    // Sort by total tokens descending
-    model_list.sort_by(|a, b| b.total_tokens.cmp(&a.total_tokens));
-    model_list
-}
-
-/// Calculate UsageData from project entries (reuse logic from stats.rs)
-fn calculate_usage_data(
-    all_data: Vec<(ProjectData, Vec<UsageEntry>)>,
-) -> Result<UsageData, ReaderError> {
-    use std::collections::HashMap;
-    use chrono::{Datelike, Duration, Local, Timelike, Utc};
-    use crate::usage::models::{BurnRate, DailyUsage, OverallStats, ProjectStats, TodayStats};
-
-    let mut all_entries: Vec<UsageEntry> = Vec::new();
-    let mut projects: Vec<ProjectStats> = Vec::new();
-
-    for (project, entries) in all_data {
-        if entries.is_empty() {
-            continue;
+        .to_string();
+        for project in ["-work-app", "-work-app-worktree"] {
+            let dir = root.join("projects").join(project);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("session.jsonl"), &line).unwrap();
         }
+        let now = Utc.with_ymd_and_hms(2025, 3, 10, 14, 30, 0).unwrap();
+        let pricing = PricingCalculator::new();
+        let mut cache = CacheManager::new();
+        cache.set_clock(std::sync::Arc::new(FixedClock(now)));
+        cache.full_load(root.to_str(), &pricing).unwrap();
+
+        let entries: Vec<UsageEntry> = load_all_entries(root.to_str(), &pricing)
+            .unwrap()
+            .into_iter()
+            .flat_map(|(_, entries)| entries)
+            .collect();
+        let expected = calculate_plan_status(
+            &entries,
+            "pro",
+            &now,
+            &SessionConfig::default(),
+            DEFAULT_COST_DECIMALS,
+        );
+        let status = cache.plan_status("pro");
+        assert_eq!(status.tokens_used, 1_000);
+        assert_eq!(status.tokens_used, expected.tokens_used);
+        assert_eq!(status.messages_used, 1);
 
-        all_entries.extend(entries.clone());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
 
-        // Calculate project stats
-        let mut stats = ProjectStats {
-            project_path: project.decoded_path.clone(),
-            display_name: project.display_name.clone(),
-            session_count: project.session_files.len() as u32,
-            ..Default::default()
+    #[test]
+    fn test_last_usage_data_leaves_changes_pending() {
+        let root = std::env::temp_dir().join(format!("ccm-cache-last-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let dir = root.join("projects").join("-work-app");
+        std::fs::create_dir_all(&dir).unwrap();
+        let line = |request_id: &str| {
+            serde_json::json!({
+                "type": "assistant",
+                "timestamp": "2025-03-10T14:00:00Z",
+                "requestId": request_id,
+                "message": {
+                    "id": format!("msg_{}", request_id),
+                    "model": "claude-sonnet-4-20250514",
+                    "usage": {"input_tokens": 10, "output_tokens": 20}
+                }
+            })
+            .to_string()
         };
-
-        for entry in &entries {
-            stats.total_input_tokens += entry.input_tokens;
-            stats.total_output_tokens += entry.output_tokens;
-            stats.cache_creation_tokens += entry.cache_creation_tokens;
-            stats.cache_read_tokens += entry.cache_read_tokens;
-            stats.total_cost_usd += entry.cost_usd;
-            stats.message_count += 1;
-
-            let ts = entry.timestamp.to_rfc3339();
-            match &stats.first_activity {
-                None => stats.first_activity = Some(ts.clone()),
-                Some(first) if ts < *first => stats.first_activity = Some(ts.clone()),
-                _ => {}
-            }
-            match &stats.last_activity {
-                None => stats.last_activity = Some(ts.clone()),
-                Some(last) if ts > *last => stats.last_activity = Some(ts.clone()),
-                _ => {}
-            }
-        }
-
-        stats.total_cost_usd = (stats.total_cost_usd * 1_000_000.0).round() / 1_000_000.0;
-        projects.push(stats);
+        let file = dir.join("session.jsonl");
+        std::fs::write(&file, line("req_1")).unwrap();
+        let custom_path = root.to_str();
+        let pricing = PricingCalculator::new();
+        let mut cache = CacheManager::new();
+        assert!(cache.last_usage_data(custom_path).is_none());
+        cache.full_load(custom_path, &pricing).unwrap();
+
+        std::fs::write(&file, [line("req_1"), line("req_2")].join("\n")).unwrap();
+        let handle = std::fs::File::options().write(true).open(&file).unwrap();
+        let mtime = handle.metadata().unwrap().modified().unwrap();
+        handle.set_modified(mtime + std::time::Duration::from_secs(5)).unwrap();
+
+        let last = cache.last_usage_data(custom_path).unwrap();
+        assert_eq!(last.overall_stats.total_messages, 1);
+        assert!(cache.last_usage_data(None).is_none());
+        let (data, delta) = cache.incremental_load_with_delta(custom_path, &pricing).unwrap();
+        assert!(delta.has_changes);
+        assert_eq!(data.overall_stats.total_messages, 2);
+        assert_eq!(cache.last_usage_data(custom_path).unwrap().overall_stats.total_messages, 2);
+
+        std::fs::remove_dir_all(&root).unwrap();
     }
 
-    // Calculate daily usage
-    let mut daily_map: HashMap<String, DailyUsage> = HashMap::new();
-
-    for entry in &all_entries {
-        let date_key = format!(
-            "{:04}-{:02}-{:02}",
-            entry.timestamp.year(),
-            entry.timestamp.month(),
-            entry.timestamp.day()
-        );
-
-        let daily = daily_map.entry(date_key.clone()).or_insert_with(|| DailyUsage {
-            date: date_key,
-            ..Default::default()
+    #[test]
+    fn test_apply_config_affects_next_load() {
+        let root = std::env::temp_dir().join(format!("ccm-cache-config-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let dir = root.join("projects").join("-work-app");
+        std::fs::create_dir_all(&dir).unwrap();
+        let line = serde_json::json!({
+            "type": "assistant",
+            "timestamp": "2025-03-10T14:00:00Z",
+            "message": {
+                "model": "claude-sonnet-4-20250514",
+                "usage": {"input_tokens": 10, "output_tokens": 20}
+            }
+        });
+        std::fs::write(dir.join("session.jsonl"), line.to_string()).unwrap();
+        let custom_path = root.to_str();
+        let pricing = PricingCalculator::new();
+        let mut cache = CacheManager::new();
+        let data = cache.full_load(custom_path, &pricing).unwrap();
+        assert_eq!(data.overall_stats.total_cost_usd, 0.00033);
+
+        cache.apply_config(&AppConfig {
+            cost_decimals: 2,
+            ..AppConfig::default()
         });
+        assert!(cache.last_usage_data(custom_path).is_none());
+        let (data, delta) = cache.incremental_load_with_delta(custom_path, &pricing).unwrap();
+        assert!(!delta.has_changes);
+        assert_eq!(data.overall_stats.total_cost_usd, 0.0);
 
-        daily.input_tokens += entry.input_tokens;
-        daily.output_tokens += entry.output_tokens;
-        daily.cache_creation_tokens += entry.cache_creation_tokens;
-        daily.cache_read_tokens += entry.cache_read_tokens;
-        daily.cost_usd += entry.cost_usd;
-        daily.message_count += 1;
+        std::fs::remove_dir_all(&root).unwrap();
     }
 
-    let mut daily_usage: Vec<_> = daily_map
-        .into_values()
-        .map(|mut d| {
-            d.cost_usd = (d.cost_usd * 1_000_000.0).round() / 1_000_000.0;
-            d
-        })
-        .collect();
-    daily_usage.sort_by(|a, b| a.date.cmp(&b.date));
-
-    // Calculate overall stats
-    let mut overall_stats = OverallStats {
-        project_count: projects.len() as u32,
-        ..Default::default()
-    };
-
-    for project in &projects {
-        overall_stats.total_input_tokens += project.total_input_tokens;
-        overall_stats.total_output_tokens += project.total_output_tokens;
-        overall_stats.cache_creation_tokens += project.cache_creation_tokens;
-        overall_stats.cache_read_tokens += project.cache_read_tokens;
-        overall_stats.total_cost_usd += project.total_cost_usd;
-        overall_stats.total_messages += project.message_count;
-        overall_stats.total_sessions += project.session_count;
-    }
-    overall_stats.total_cost_usd = (overall_stats.total_cost_usd * 1_000_000.0).round() / 1_000_000.0;
-
-    // Calculate model distribution
-    overall_stats.model_distribution = calculate_model_distribution(&all_entries);
-
-    // Calculate today's stats (since local midnight)
-    let today_local = Local::now().date_naive();
-    let mut today_stats = TodayStats::default();
-
-    for entry in &all_entries {
-        // Convert UTC timestamp to local date for comparison
-        let entry_local_date = entry.timestamp.with_timezone(&Local).date_naive();
-        if entry_local_date == today_local {
-            today_stats.input_tokens += entry.input_tokens;
-            today_stats.output_tokens += entry.output_tokens;
-            today_stats.cost_usd += entry.cost_usd;
-            today_stats.message_count += 1;
-        }
-    }
-    today_stats.total_tokens = today_stats.input_tokens + today_stats.output_tokens;
-    today_stats.cost_usd = (today_stats.cost_usd * 1_000_000.0).round() / 1_000_000.0;
-    overall_stats.today_stats = today_stats;
+    #[test]
+    fn test_rescan_interval_zero_rescans_every_cycle() {
+        let mut cache = CacheManager::new();
+        assert!(cache.should_rescan_dirs());
 
-    // Calculate session timing and burn rate (matches stats.rs logic)
-    if !all_entries.is_empty() {
-        let now = Utc::now();
-        let window_start = now - Duration::minutes(SESSION_DURATION_MINUTES);
+        cache.update_projects(Vec::new());
+        assert!(!cache.should_rescan_dirs());
 
-        // Sort entries by timestamp for proper processing
-        all_entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-
-        let recent_entries: Vec<_> = all_entries
-            .iter()
-            .filter(|e| e.timestamp >= window_start)
-            .collect();
-
-        if !recent_entries.is_empty() {
-            let first_entry_time = recent_entries.iter().map(|e| e.timestamp).min().unwrap();
-
-            let session_block_start = first_entry_time
-                .with_minute(0).unwrap()
-                .with_second(0).unwrap()
-                .with_nanosecond(0).unwrap();
-
-            overall_stats.session_start_time = Some(session_block_start.to_rfc3339());
-            overall_stats.time_to_reset_minutes = calculate_time_to_reset(Some(&session_block_start), &now);
-
-            // Calculate hourly burn rate using block-based proportional allocation
-            let blocks = transform_to_blocks(&all_entries);
-            let (tokens_per_min, cost_per_hour) = calculate_hourly_burn_rate(&blocks, &now);
-
-            if tokens_per_min > 0.0 {
-                overall_stats.burn_rate = Some(BurnRate {
-                    tokens_per_minute: (tokens_per_min * 100.0).round() / 100.0,
-                    cost_per_hour: (cost_per_hour * 10000.0).round() / 10000.0,
-                });
-            }
-        } else {
-            overall_stats.time_to_reset_minutes = SESSION_DURATION_MINUTES as u32;
-        }
-    } else {
-        overall_stats.time_to_reset_minutes = SESSION_DURATION_MINUTES as u32;
-    }
-
-    // Sort projects by last activity
-    projects.sort_by(|a, b| {
-        let a_time = a.last_activity.as_deref().unwrap_or("");
-        let b_time = b.last_activity.as_deref().unwrap_or("");
-        b_time.cmp(a_time)
-    });
-
-    Ok(UsageData {
-        projects,
-        daily_usage,
-        overall_stats,
-    })
+        cache.set_rescan_interval(0);
+        assert!(cache.should_rescan_dirs());
+    }
 }
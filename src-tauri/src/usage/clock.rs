@@ -0,0 +1,30 @@
+//! Clock abstraction so time-dependent statistics can be computed against a fixed time
+
+use std::fmt::Debug;
+
+use chrono::{DateTime, Utc};
+
+/// Source of the current time
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Clock backed by the system time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock that always returns the same instant (for tests and reproducible reports)
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
@@ -1,28 +1,129 @@
 //! Configuration and data directory discovery
 
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::env;
+use std::time::Duration;
 
-/// Get the Claude data directory path
-/// Priority: 1. Custom path from config, 2. CLAUDE_CONFIG_DIR env var, 3. Default ~/.claude
+use log::warn;
+
+use crate::usage::models::{AppConfig, EnvDiagnostics, EnvVarDiagnostic};
+use crate::usage::pricing::PRICING_FILE_ENV;
+use crate::usage::timezone::UsageTimezone;
+
+/// Error type for loading and saving the app configuration
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Refresh interval must be between {min} and {max} seconds, got {0}",
+        min = MIN_REFRESH_INTERVAL_SECS, max = MAX_REFRESH_INTERVAL_SECS)]
+    InvalidRefreshInterval(u32),
+    #[error("Alert thresholds must be greater than 0 and at most 1, got {0}")]
+    InvalidAlertThreshold(f64),
+    #[error("Session duration must be at least 1 minute")]
+    InvalidSessionDuration,
+    #[error("Cost decimals must be at most {max}, got {0}", max = MAX_COST_DECIMALS)]
+    InvalidCostDecimals(u32),
+    #[error("Batch cache multiplier must be a non-negative number, got {0}")]
+    InvalidBatchCacheMultiplier(f64),
+    #[error("Unknown timezone: {0}")]
+    InvalidTimezone(String),
+    #[error("Config directory is unavailable")]
+    NoConfigDir,
+}
+
+/// Shortest refresh interval accepted in the saved config. Short intervals re-list and
+/// stat every session file more often, which adds IO on large data directories.
+pub const MIN_REFRESH_INTERVAL_SECS: u32 = 10;
+
+/// Longest refresh interval accepted in the saved config
+pub const MAX_REFRESH_INTERVAL_SECS: u32 = 3600;
+
+/// Most decimal places accepted for reported costs
+pub const MAX_COST_DECIMALS: u32 = 10;
+
+/// Environment variable overriding the Claude data directory
+pub const CLAUDE_CONFIG_DIR_ENV: &str = "CLAUDE_CONFIG_DIR";
+
+/// Environment variable with a glob matching session files below the projects directory
+pub const SESSION_GLOB_ENV: &str = "CCM_SESSION_GLOB";
+
+/// Environment variable naming the XDG base configuration directory (default ~/.config)
+pub const XDG_CONFIG_HOME_ENV: &str = "XDG_CONFIG_HOME";
+
+/// Environment variable listing several Claude data directories, comma-separated
+pub const DATA_DIRS_ENV: &str = "CCM_DATA_DIRS";
+
+/// Telemetry-related variables users commonly set; this app does not read them
+const TELEMETRY_ENV_VARS: &[&str] = &["CLAUDE_CODE_ENABLE_TELEMETRY", "CCM_COLLECTOR_PORT"];
+
+/// Data source reported by the diagnostics; session JSONL logs are the only source
+pub const JSONL_DATA_SOURCE: &str = "jsonl";
+
+/// Get the Claude data directory path (the first of `get_claude_data_dirs`)
 pub fn get_claude_data_dir(custom_path: Option<&str>) -> PathBuf {
+    get_claude_data_dirs(custom_path).swap_remove(0)
+}
+
+/// Get every Claude data directory to read, never empty
+/// Priority: 1. Custom path from config, 2. CLAUDE_CONFIG_DIR env var, 3. Default ~/.claude
+/// and the XDG location ~/.config/claude used by newer Claude Code versions: both when both
+/// have a `projects` directory, else whichever has one, else ~/.claude
+pub fn get_claude_data_dirs(custom_path: Option<&str>) -> Vec<PathBuf> {
+    resolve_claude_data_dirs(
+        custom_path,
+        env::var(CLAUDE_CONFIG_DIR_ENV).ok(),
+        dirs::home_dir(),
+        env::var(XDG_CONFIG_HOME_ENV).ok(),
+    )
+}
+
+fn resolve_claude_data_dirs(
+    custom_path: Option<&str>,
+    config_dir_env: Option<String>,
+    home: Option<PathBuf>,
+    xdg_config_home: Option<String>,
+) -> Vec<PathBuf> {
     // 1. Custom path takes highest priority
     if let Some(path) = custom_path {
-        return PathBuf::from(path);
+        return vec![PathBuf::from(path)];
     }
 
     // 2. Check CLAUDE_CONFIG_DIR environment variable
-    if let Ok(env_path) = env::var("CLAUDE_CONFIG_DIR") {
-        return PathBuf::from(env_path);
+    if let Some(env_path) = config_dir_env {
+        return vec![PathBuf::from(env_path)];
     }
 
-    // 3. Default to ~/.claude
-    if let Some(home) = dirs::home_dir() {
-        return home.join(".claude");
+    // 3. Default to ~/.claude, with the XDG location as an alternative
+    let Some(home) = home else {
+        // Fallback for edge cases
+        return vec![PathBuf::from(".claude")];
+    };
+    let legacy = home.join(".claude");
+    let xdg = xdg_config_home
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".config"))
+        .join("claude");
+
+    match (legacy.join("projects").is_dir(), xdg.join("projects").is_dir()) {
+        (true, true) => vec![legacy, xdg],
+        (false, true) => vec![xdg],
+        _ => vec![legacy],
     }
+}
 
-    // Fallback for edge cases
-    PathBuf::from(".claude")
+/// Resolve the data path a command reads from
+/// Priority: 1. Path passed to the command, 2. `data_path` from config. When neither is
+/// set, `get_claude_data_dirs` falls back to CLAUDE_CONFIG_DIR and then the default dirs.
+pub fn resolve_data_path(argument: Option<String>, config: &AppConfig) -> Option<String> {
+    let non_empty = |path: &String| !path.trim().is_empty();
+    argument
+        .filter(non_empty)
+        .or_else(|| config.data_path.clone().filter(non_empty))
 }
 
 /// Get the projects directory within the Claude data directory
@@ -30,21 +131,256 @@ pub fn get_projects_dir(custom_path: Option<&str>) -> PathBuf {
     get_claude_data_dir(custom_path).join("projects")
 }
 
+/// Get the projects directory within each of `get_claude_data_dirs`
+pub fn get_projects_dirs(custom_path: Option<&str>) -> Vec<PathBuf> {
+    get_claude_data_dirs(custom_path)
+        .into_iter()
+        .map(|dir| dir.join("projects"))
+        .collect()
+}
+
+/// Get the extra data directories to scan together
+/// Priority: 1. `data_paths` from config, 2. CCM_DATA_DIRS env var
+pub fn get_data_dirs(config_paths: &[String]) -> Vec<String> {
+    resolve_data_dirs(config_paths, env::var(DATA_DIRS_ENV).ok())
+}
+
+fn resolve_data_dirs(config_paths: &[String], data_dirs_env: Option<String>) -> Vec<String> {
+    let paths: Vec<String> = if config_paths.is_empty() {
+        data_dirs_env
+            .map(|value| value.split(',').map(|p| p.trim().to_string()).collect())
+            .unwrap_or_default()
+    } else {
+        config_paths.iter().map(|p| p.trim().to_string()).collect()
+    };
+
+    let mut dirs: Vec<String> = Vec::new();
+    for path in paths {
+        if !path.is_empty() && !dirs.contains(&path) {
+            dirs.push(path);
+        }
+    }
+    dirs
+}
+
+/// Session file glob: the configured one, else CCM_SESSION_GLOB.
+/// None when neither is set or the glob is invalid, so the default layout is read.
+pub fn get_session_glob(configured: Option<&str>) -> Option<String> {
+    resolve_session_glob(configured, env::var(SESSION_GLOB_ENV).ok())
+}
+
+fn resolve_session_glob(configured: Option<&str>, glob_env: Option<String>) -> Option<String> {
+    let pattern = configured
+        .map(str::to_string)
+        .or(glob_env)
+        .map(|pattern| pattern.trim().to_string())
+        .filter(|pattern| !pattern.is_empty())?;
+
+    match glob::Pattern::new(&pattern) {
+        Ok(_) => Some(pattern),
+        Err(e) => {
+            warn!("Invalid session glob '{}', using the default layout: {}", pattern, e);
+            None
+        }
+    }
+}
+
+/// Report the environment variables that influence where usage data is read from
+pub fn get_env_diagnostics(custom_path: Option<&str>) -> EnvDiagnostics {
+    env_diagnostics_with(custom_path, |name| env::var(name).ok())
+}
+
+/// Build env diagnostics using `lookup` to read variable values
+pub fn env_diagnostics_with<F>(custom_path: Option<&str>, lookup: F) -> EnvDiagnostics
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let config_dir = lookup(CLAUDE_CONFIG_DIR_ENV);
+    let config_dir_interpretation = match (&config_dir, custom_path) {
+        (Some(_), Some(_)) => "Ignored: a custom data path is configured",
+        (Some(_), None) => "Used as the Claude data directory",
+        (None, _) => "Not set: using ~/.claude and/or ~/.config/claude",
+    };
+
+    let mut variables = vec![EnvVarDiagnostic {
+        name: CLAUDE_CONFIG_DIR_ENV.to_string(),
+        value: config_dir.clone(),
+        interpretation: config_dir_interpretation.to_string(),
+    }];
+
+    let data_dirs = lookup(DATA_DIRS_ENV);
+    let data_dirs_interpretation = match (&data_dirs, custom_path) {
+        (Some(_), Some(_)) => "Ignored: a custom data path is configured",
+        (Some(_), None) => "Scanned together (ignored if dataPaths is set in the config)",
+        (None, _) => "Not set: scanning a single data directory",
+    };
+    variables.push(EnvVarDiagnostic {
+        name: DATA_DIRS_ENV.to_string(),
+        value: data_dirs,
+        interpretation: data_dirs_interpretation.to_string(),
+    });
+
+    let session_glob = lookup(SESSION_GLOB_ENV);
+    let session_glob_interpretation = match &session_glob {
+        Some(pattern) if glob::Pattern::new(pattern).is_err() => {
+            "Invalid glob: reading the default <project>/*.jsonl layout"
+        }
+        Some(_) => "Session files matched below projects (ignored if sessionGlob is configured)",
+        None => "Not set: reading the default <project>/*.jsonl layout",
+    };
+    variables.push(EnvVarDiagnostic {
+        name: SESSION_GLOB_ENV.to_string(),
+        value: session_glob,
+        interpretation: session_glob_interpretation.to_string(),
+    });
+
+    let pricing_file = lookup(PRICING_FILE_ENV);
+    let pricing_interpretation = if pricing_file.is_some() {
+        "Custom pricing merged over built-in rates (ignored if the file fails to load)"
+    } else {
+        "Not set: using built-in pricing"
+    };
+    variables.push(EnvVarDiagnostic {
+        name: PRICING_FILE_ENV.to_string(),
+        value: pricing_file,
+        interpretation: pricing_interpretation.to_string(),
+    });
+
+    for name in TELEMETRY_ENV_VARS {
+        let value = lookup(name);
+        let interpretation = if value.is_some() {
+            "Not used: usage is always read from JSONL session logs"
+        } else {
+            "Not set (not used by this app)"
+        };
+        variables.push(EnvVarDiagnostic {
+            name: name.to_string(),
+            value,
+            interpretation: interpretation.to_string(),
+        });
+    }
+
+    EnvDiagnostics {
+        variables,
+        active_data_source: JSONL_DATA_SOURCE.to_string(),
+        data_dir: resolve_claude_data_dirs(
+            custom_path,
+            config_dir,
+            dirs::home_dir(),
+            lookup(XDG_CONFIG_HOME_ENV),
+        )
+        .swap_remove(0)
+        .to_string_lossy()
+        .to_string(),
+    }
+}
+
+/// Background refresh interval from the config, clamped to the accepted range in case
+/// the file was edited by hand
+pub fn refresh_interval(config: &AppConfig) -> Duration {
+    let secs = config
+        .refresh_interval_seconds
+        .clamp(MIN_REFRESH_INTERVAL_SECS, MAX_REFRESH_INTERVAL_SECS);
+    Duration::from_secs(secs.into())
+}
+
+/// Get the path of the saved app configuration
+pub fn get_config_path() -> Result<PathBuf, ConfigError> {
+    dirs::config_dir()
+        .map(|dir| dir.join("claude-code-usage-tracker").join("config.json"))
+        .ok_or(ConfigError::NoConfigDir)
+}
+
+/// Load the app configuration from `path`, falling back to the defaults when the
+/// file is absent or invalid. Fields missing from the file take their defaults.
+pub fn load_config(path: &Path) -> AppConfig {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return AppConfig::default(),
+    };
+    match serde_json::from_str(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            warn!("Ignoring invalid config {}: {}", path.display(), e);
+            AppConfig::default()
+        }
+    }
+}
+
+/// Check that configuration values are within accepted ranges
+pub fn validate_config(config: &AppConfig) -> Result<(), ConfigError> {
+    let interval = config.refresh_interval_seconds;
+    if !(MIN_REFRESH_INTERVAL_SECS..=MAX_REFRESH_INTERVAL_SECS).contains(&interval) {
+        return Err(ConfigError::InvalidRefreshInterval(interval));
+    }
+    let invalid_threshold = config.alert_thresholds.iter().find(|t| !(**t > 0.0 && **t <= 1.0));
+    if let Some(&threshold) = invalid_threshold {
+        return Err(ConfigError::InvalidAlertThreshold(threshold));
+    }
+    if config.session_duration_minutes == 0 {
+        return Err(ConfigError::InvalidSessionDuration);
+    }
+    if config.cost_decimals > MAX_COST_DECIMALS {
+        return Err(ConfigError::InvalidCostDecimals(config.cost_decimals));
+    }
+    let multiplier = config.batch_cache_multiplier;
+    if !(multiplier.is_finite() && multiplier >= 0.0) {
+        return Err(ConfigError::InvalidBatchCacheMultiplier(multiplier));
+    }
+    if let Some(name) = config.timezone.as_deref() {
+        if UsageTimezone::parse(name).is_none() {
+            return Err(ConfigError::InvalidTimezone(name.to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Validate `config` and write it to `path` as pretty-printed JSON
+pub fn save_config(path: &Path, config: &AppConfig) -> Result<(), ConfigError> {
+    validate_config(config)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(config)?)?;
+    Ok(())
+}
+
 /// Decode an encoded project path (Claude Code custom encoding)
-/// Claude Code encodes paths: `--` represents `:\` and `-` represents `\`
+/// Windows: `--` after the drive letter represents `:\` and `-` represents `\`
+/// (`D--code-project` -> `D:\code\project`).
+/// Unix: the leading `/` and every separator become `-`
+/// (`-Users-me-code-project` -> `/Users/me/code/project`).
 pub fn decode_project_path(encoded: &str) -> String {
+    if encoded.starts_with('-') {
+        return encoded.replace('-', "/");
+    }
+
     // First replace `--` with `:\` (drive letter separator on Windows)
     let result = encoded.replace("--", ":\\");
     // Then replace remaining `-` with `\` (path separator)
     result.replace("-", "\\")
 }
 
+/// Whether the host filesystem usually treats paths that differ only in case as the same
+pub const PATHS_CASE_INSENSITIVE: bool = cfg!(any(windows, target_os = "macos"));
+
+/// Key used to group projects whose decoded paths refer to the same directory.
+/// Paths are compared case-insensitively when `case_insensitive` is set.
+pub fn project_group_key(decoded_path: &str, case_insensitive: bool) -> String {
+    if case_insensitive {
+        decoded_path.to_lowercase()
+    } else {
+        decoded_path.to_string()
+    }
+}
+
 /// Extract a display-friendly name from a project path
 pub fn get_display_name(project_path: &str) -> String {
-    // Get the last component of the path as display name
-    let path = PathBuf::from(project_path);
-    path.file_name()
-        .and_then(|n| n.to_str())
+    // Get the last component of the path as display name, accepting either separator
+    // so Windows paths display correctly on every platform
+    project_path
+        .rsplit(['/', '\\'])
+        .find(|component| !component.is_empty())
         .unwrap_or(project_path)
         .to_string()
 }
@@ -69,9 +405,206 @@ mod tests {
         assert_eq!(decoded, "D:\\code\\work\\YueShan\\react");
     }
 
+    #[test]
+    fn test_env_diagnostics_with_telemetry_enabled() {
+        let diagnostics = env_diagnostics_with(None, |name| match name {
+            "CLAUDE_CODE_ENABLE_TELEMETRY" => Some("1".to_string()),
+            "CLAUDE_CONFIG_DIR" => Some("/data/claude".to_string()),
+            _ => None,
+        });
+
+        // Telemetry is reported as set but ignored; JSONL remains the data source
+        let telemetry = diagnostics
+            .variables
+            .iter()
+            .find(|v| v.name == "CLAUDE_CODE_ENABLE_TELEMETRY")
+            .unwrap();
+        assert_eq!(telemetry.value.as_deref(), Some("1"));
+        assert!(telemetry.interpretation.starts_with("Not used"));
+        assert_eq!(diagnostics.active_data_source, "jsonl");
+        assert_eq!(diagnostics.data_dir, PathBuf::from("/data/claude").to_string_lossy());
+
+        let custom = env_diagnostics_with(Some("/custom"), |_| Some("/data/claude".to_string()));
+        assert_eq!(custom.data_dir, PathBuf::from("/custom").to_string_lossy());
+        assert!(custom.variables[0].interpretation.starts_with("Ignored"));
+    }
+
+    #[test]
+    fn test_decode_unix_project_path() {
+        let decoded = decode_project_path("-Users-me-code-project");
+        assert_eq!(decoded, "/Users/me/code/project");
+        assert!(!decoded.contains(':'));
+        assert!(!decoded.contains('\\'));
+    }
+
     #[test]
     fn test_get_display_name() {
         let path = "D:\\code\\my-project";
         assert_eq!(get_display_name(path), "my-project");
     }
+
+    #[test]
+    fn test_get_display_name_unix() {
+        assert_eq!(get_display_name("/Users/me/code/project"), "project");
+        assert_eq!(get_display_name(&decode_project_path("-home-dev-app")), "app");
+    }
+
+    #[test]
+    fn test_refresh_interval_is_clamped() {
+        let with_interval = |secs| AppConfig {
+            refresh_interval_seconds: secs,
+            ..Default::default()
+        };
+        assert_eq!(refresh_interval(&AppConfig::default()), Duration::from_secs(300));
+        assert_eq!(refresh_interval(&with_interval(0)), Duration::from_secs(10));
+        assert_eq!(refresh_interval(&with_interval(86_400)), Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_config_round_trip_and_fallbacks() {
+        let dir = std::env::temp_dir().join(format!("ccm-config-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("nested").join("config.json");
+
+        // Absent file gives the defaults
+        assert_eq!(load_config(&path).refresh_interval_seconds, 300);
+
+        let config = AppConfig {
+            refresh_interval_seconds: 60,
+            plan_type: "max5".to_string(),
+            billing_cycle_start_day: 15,
+            ..Default::default()
+        };
+        save_config(&path, &config).unwrap();
+        let loaded = load_config(&path);
+        assert_eq!(loaded.refresh_interval_seconds, 60);
+        assert_eq!(loaded.plan_type, "max5");
+        assert_eq!(loaded.billing_cycle_start_day, 15);
+
+        // Out-of-range intervals are rejected without touching the saved file
+        let too_fast = AppConfig {
+            refresh_interval_seconds: 1,
+            ..Default::default()
+        };
+        assert!(matches!(
+            save_config(&path, &too_fast),
+            Err(ConfigError::InvalidRefreshInterval(1))
+        ));
+        assert_eq!(load_config(&path).refresh_interval_seconds, 60);
+        let negative_multiplier = AppConfig {
+            batch_cache_multiplier: -0.5,
+            ..Default::default()
+        };
+        assert!(matches!(
+            save_config(&path, &negative_multiplier),
+            Err(ConfigError::InvalidBatchCacheMultiplier(_))
+        ));
+
+        // Invalid JSON falls back to the defaults; missing fields take their defaults
+        fs::write(&path, "{not json").unwrap();
+        assert_eq!(load_config(&path).plan_type, "pro");
+        fs::write(&path, r#"{"planType": "max20"}"#).unwrap();
+        let partial = load_config(&path);
+        assert_eq!(partial.plan_type, "max20");
+        assert_eq!(partial.refresh_interval_seconds, 300);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_resolve_data_path_precedence() {
+        let config = AppConfig {
+            data_path: Some("/mnt/d/.claude".to_string()),
+            ..Default::default()
+        };
+        let argument = Some("/custom/.claude".to_string());
+        assert_eq!(resolve_data_path(argument, &config).as_deref(), Some("/custom/.claude"));
+        assert_eq!(resolve_data_path(None, &config).as_deref(), Some("/mnt/d/.claude"));
+        assert_eq!(
+            resolve_data_path(Some(String::new()), &config).as_deref(),
+            Some("/mnt/d/.claude")
+        );
+        assert_eq!(resolve_data_path(None, &AppConfig::default()), None);
+    }
+
+    #[test]
+    fn test_resolve_data_dirs_prefers_config() {
+        let env_value = " /a/.claude, /b/.claude,,/a/.claude".to_string();
+        let from_env = resolve_data_dirs(&[], Some(env_value));
+        assert_eq!(from_env, vec!["/a/.claude", "/b/.claude"]);
+
+        let configured = vec!["/c/.claude".to_string()];
+        let from_config = resolve_data_dirs(&configured, Some("/a/.claude".to_string()));
+        assert_eq!(from_config, vec!["/c/.claude"]);
+
+        assert!(resolve_data_dirs(&[], None).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_session_glob() {
+        let env_glob = Some("**/sessions/*.jsonl".to_string());
+        assert_eq!(
+            resolve_session_glob(None, env_glob.clone()).as_deref(),
+            Some("**/sessions/*.jsonl")
+        );
+        assert_eq!(
+            resolve_session_glob(Some("*/logs/*.jsonl"), env_glob).as_deref(),
+            Some("*/logs/*.jsonl")
+        );
+        // Invalid or blank globs fall back to the default layout
+        assert_eq!(resolve_session_glob(Some("a/***/b"), None), None);
+        assert_eq!(resolve_session_glob(None, Some("  ".to_string())), None);
+        assert_eq!(resolve_session_glob(None, None), None);
+    }
+
+    #[test]
+    fn test_resolve_claude_data_dirs_discovery() {
+        let home = std::env::temp_dir().join(format!("ccm-home-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home).unwrap();
+        let legacy = home.join(".claude");
+        let xdg = home.join(".config").join("claude");
+        let resolve = |xdg_home: Option<String>| {
+            resolve_claude_data_dirs(None, None, Some(home.clone()), xdg_home)
+        };
+
+        // Explicit locations win without touching the filesystem
+        let custom = resolve_claude_data_dirs(
+            Some("/custom/.claude"),
+            Some("/env/.claude".to_string()),
+            Some(home.clone()),
+            None,
+        );
+        assert_eq!(custom, vec![PathBuf::from("/custom/.claude")]);
+        let env_dir = Some("/env/.claude".to_string());
+        let from_env = resolve_claude_data_dirs(None, env_dir, Some(home.clone()), None);
+        assert_eq!(from_env, vec![PathBuf::from("/env/.claude")]);
+        assert_eq!(
+            resolve_claude_data_dirs(None, None, None, None),
+            vec![PathBuf::from(".claude")]
+        );
+
+        // Neither has projects: default to ~/.claude
+        assert_eq!(resolve(None), vec![legacy.clone()]);
+
+        // Only the XDG location has projects
+        fs::create_dir_all(xdg.join("projects")).unwrap();
+        assert_eq!(resolve(None), vec![xdg.clone()]);
+
+        // Both have projects: scan both, ~/.claude first
+        fs::create_dir_all(legacy.join("projects")).unwrap();
+        assert_eq!(resolve(None), vec![legacy.clone(), xdg.clone()]);
+
+        // Only ~/.claude has projects
+        fs::remove_dir_all(&xdg).unwrap();
+        assert_eq!(resolve(None), vec![legacy.clone()]);
+
+        // XDG_CONFIG_HOME moves the XDG location
+        let custom_xdg = home.join("xdg");
+        fs::create_dir_all(custom_xdg.join("claude").join("projects")).unwrap();
+        let dirs = resolve(Some(custom_xdg.to_string_lossy().to_string()));
+        assert_eq!(dirs, vec![legacy, custom_xdg.join("claude")]);
+
+        let _ = fs::remove_dir_all(&home);
+    }
 }
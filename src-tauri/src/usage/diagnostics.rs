@@ -0,0 +1,369 @@
+//! Data quality diagnostics for session files
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::usage::config::{get_claude_data_dirs, get_projects_dir};
+use crate::usage::models::{
+    ClockSkewReport, DataDirReport, DataHealthReport, DataSourceReport, DedupReport,
+    FileClockSkew, FileParseErrors, UsageEntry,
+};
+use crate::usage::pricing::PricingCalculator;
+use crate::usage::reader::{
+    list_projects_with_options, read_jsonl_file_report, read_line_timestamps, ReaderError,
+    ScanOptions,
+};
+use crate::usage::stats::SessionConfig;
+
+/// How far past the file's modification time an entry may be before it is flagged.
+/// Allows for coarse filesystem timestamps and writes racing the mtime update.
+const MTIME_SKEW_TOLERANCE_SECONDS: i64 = 300;
+
+/// Check line timestamps of one file for backwards jumps and entries newer than `mtime`
+pub fn analyze_file_timestamps(
+    file_path: &str,
+    timestamps: &[DateTime<Utc>],
+    mtime: Option<DateTime<Utc>>,
+) -> FileClockSkew {
+    let mut skew = FileClockSkew {
+        file_path: file_path.to_string(),
+        entry_count: timestamps.len() as u32,
+        out_of_order_count: 0,
+        max_backward_seconds: 0,
+        after_mtime_count: 0,
+    };
+
+    let mut latest: Option<DateTime<Utc>> = None;
+    for &timestamp in timestamps {
+        if let Some(latest) = latest.filter(|latest| timestamp < *latest) {
+            skew.out_of_order_count += 1;
+            let backward_seconds = (latest - timestamp).num_seconds();
+            skew.max_backward_seconds = skew.max_backward_seconds.max(backward_seconds);
+        }
+        latest = Some(latest.map_or(timestamp, |latest| latest.max(timestamp)));
+
+        if let Some(mtime) = mtime {
+            if timestamp > mtime + Duration::seconds(MTIME_SKEW_TOLERANCE_SECONDS) {
+                skew.after_mtime_count += 1;
+            }
+        }
+    }
+
+    skew
+}
+
+/// Scan every session file for entries written out of order or timestamped after
+/// the file was last modified, both signs the system clock was wrong
+pub fn detect_clock_skew(
+    custom_path: Option<&str>,
+    options: &ScanOptions,
+) -> Result<ClockSkewReport, ReaderError> {
+    let mut report = ClockSkewReport::default();
+
+    for project in list_projects_with_options(custom_path, options)? {
+        for file in &project.session_files {
+            let timestamps = match read_line_timestamps(file) {
+                Ok(timestamps) => timestamps,
+                Err(e) => {
+                    log::warn!("Failed to read {:?}: {}", file, e);
+                    continue;
+                }
+            };
+            let mtime = file_mtime(file);
+
+            report.files_checked += 1;
+            let skew = analyze_file_timestamps(&file.to_string_lossy(), &timestamps, mtime);
+            if skew.out_of_order_count > 0 || skew.after_mtime_count > 0 {
+                report.total_out_of_order += skew.out_of_order_count;
+                report.total_after_mtime += skew.after_mtime_count;
+                report.affected_files.push(skew);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Read every session file and list those with lines that could not be parsed, to tell
+/// "no usage" apart from data the parser cannot read
+pub fn check_data_health(
+    custom_path: Option<&str>,
+    options: &ScanOptions,
+    pricing: &PricingCalculator,
+) -> Result<DataHealthReport, ReaderError> {
+    let mut report = DataHealthReport::default();
+
+    for project in list_projects_with_options(custom_path, options)? {
+        for file in &project.session_files {
+            report.files_checked += 1;
+            let file_report = match read_jsonl_file_report(file, pricing) {
+                Ok(file_report) => file_report,
+                Err(e) => {
+                    log::warn!("Failed to read {:?}: {}", file, e);
+                    report.unreadable_files.push(file.to_string_lossy().into_owned());
+                    continue;
+                }
+            };
+
+            if file_report.parse_errors > 0 {
+                report.total_parse_errors += file_report.parse_errors;
+                report.affected_files.push(FileParseErrors {
+                    file_path: file.to_string_lossy().into_owned(),
+                    entry_count: file_report.entries.len() as u32,
+                    parse_errors: file_report.parse_errors,
+                });
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Walk every data directory the app would read and count what is found at each step,
+/// so an empty dashboard can be traced to a wrong path, no session files or unparsable lines
+pub fn diagnose_data_source(
+    custom_path: Option<&str>,
+    options: &ScanOptions,
+    pricing: &PricingCalculator,
+) -> Result<DataSourceReport, ReaderError> {
+    let roots: Vec<String> = match custom_path {
+        Some(path) => vec![path.to_string()],
+        None if !options.data_dirs.is_empty() => options.data_dirs.clone(),
+        None => get_claude_data_dirs(None)
+            .iter()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .collect(),
+    };
+    let single_root = ScanOptions {
+        data_dirs: Vec::new(),
+        ..options.clone()
+    };
+
+    let mut report = DataSourceReport::default();
+    for root in &roots {
+        let dir_report = diagnose_data_dir(root, &single_root, pricing)?;
+        report.project_count += dir_report.project_count;
+        report.session_file_count += dir_report.session_file_count;
+        report.total_lines += dir_report.total_lines;
+        report.parsed_lines += dir_report.parsed_lines;
+        report.usage_entries += dir_report.usage_entries;
+        report.data_dirs.push(dir_report);
+    }
+
+    Ok(report)
+}
+
+fn diagnose_data_dir(
+    root: &str,
+    options: &ScanOptions,
+    pricing: &PricingCalculator,
+) -> Result<DataDirReport, ReaderError> {
+    let projects_dir = get_projects_dir(Some(root));
+    let mut report = DataDirReport {
+        data_dir: root.to_string(),
+        projects_dir: projects_dir.to_string_lossy().into_owned(),
+        exists: projects_dir.is_dir(),
+        ..Default::default()
+    };
+    if !report.exists {
+        return Ok(report);
+    }
+
+    for project in list_projects_with_options(Some(root), options)? {
+        report.project_count += 1;
+        for file in &project.session_files {
+            report.session_file_count += 1;
+            match read_jsonl_file_report(file, pricing) {
+                Ok(file_report) => {
+                    report.total_lines += file_report.lines.total_lines;
+                    report.parsed_lines += file_report.lines.parsed_lines;
+                    report.usage_entries += file_report.entries.len() as u64;
+                }
+                Err(e) => {
+                    log::warn!("Failed to read {:?}: {}", file, e);
+                    report.unreadable_files.push(file.to_string_lossy().into_owned());
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Compare tokens counted line by line with tokens left after deduplicating entries
+/// across all files by `message_id:request_id`, plus the current session window, to check
+/// that repeated log lines are not inflating usage
+pub fn analyze_dedup(
+    custom_path: Option<&str>,
+    options: &ScanOptions,
+    pricing: &PricingCalculator,
+    session: &SessionConfig,
+    now: DateTime<Utc>,
+) -> Result<DedupReport, ReaderError> {
+    let mut report = DedupReport {
+        window_minutes: session.duration_minutes,
+        ..Default::default()
+    };
+    let mut entries = Vec::new();
+
+    for project in list_projects_with_options(custom_path, options)? {
+        for file in &project.session_files {
+            match read_jsonl_file_report(file, pricing) {
+                Ok(file_report) => {
+                    let counts = file_report.lines;
+                    report.total_lines += counts.total_lines;
+                    report.parsed_lines += counts.parsed_lines;
+                    report.usage_lines += counts.usage_lines;
+                    report.raw_tokens += counts.raw_tokens;
+                    entries.extend(file_report.entries);
+                }
+                Err(e) => log::warn!("Failed to read {:?}: {}", file, e),
+            }
+        }
+    }
+
+    add_deduped_entries(&mut report, &entries, now - session.duration());
+    Ok(report)
+}
+
+/// Fill the deduplicated and session window fields of `report` from per-file entries
+fn add_deduped_entries(report: &mut DedupReport, entries: &[UsageEntry], since: DateTime<Utc>) {
+    let mut seen = HashSet::new();
+    for entry in entries {
+        let key = format!(
+            "{}:{}",
+            entry.message_id,
+            entry.request_id.as_deref().unwrap_or_default()
+        );
+        if !seen.insert(key) {
+            continue;
+        }
+
+        let tokens = entry.input_tokens
+            + entry.output_tokens
+            + entry.cache_creation_tokens
+            + entry.cache_read_tokens;
+        report.unique_entries += 1;
+        report.deduped_tokens += tokens;
+
+        if entry.timestamp >= since {
+            report.window_entries += 1;
+            report.window_tokens += tokens;
+            let start = report.window_start.map_or(entry.timestamp, |t| t.min(entry.timestamp));
+            report.window_start = Some(start);
+            report.window_end = report.window_end.max(Some(entry.timestamp));
+        }
+    }
+
+    if report.usage_lines > 0 {
+        report.dedup_ratio = report.unique_entries as f64 / report.usage_lines as f64;
+    }
+    if let (Some(start), Some(end)) = (report.window_start, report.window_end) {
+        let minutes = (end - start).num_seconds() as f64 / 60.0;
+        if minutes >= 1.0 {
+            report.window_tokens_per_minute = Some(report.window_tokens as f64 / minutes);
+        }
+    }
+}
+
+fn file_mtime(path: &Path) -> Option<DateTime<Utc>> {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(DateTime::<Utc>::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_out_of_order_entry_is_flagged() {
+        let at = |m: u32| Utc.with_ymd_and_hms(2025, 1, 1, 10, m, 0).unwrap();
+        let timestamps = vec![at(0), at(10), at(5), at(20)];
+
+        let skew = analyze_file_timestamps("session.jsonl", &timestamps, Some(at(30)));
+        assert_eq!(skew.entry_count, 4);
+        assert_eq!(skew.out_of_order_count, 1);
+        assert_eq!(skew.max_backward_seconds, 300);
+        assert_eq!(skew.after_mtime_count, 0);
+    }
+
+    #[test]
+    fn test_diagnose_data_source_counts_each_step() {
+        let root = std::env::temp_dir().join(format!("ccm-diagnose-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let project = root.join("projects").join("d--code-app");
+        fs::create_dir_all(&project).unwrap();
+        let usage_line = concat!(
+            r#"{"type":"assistant","timestamp":"2025-01-01T10:00:00Z","requestId":"r1","#,
+            r#""message":{"id":"m1","model":"claude-sonnet-4","#,
+            r#""usage":{"input_tokens":1,"output_tokens":2}}}"#
+        );
+        let lines = [usage_line, r#"{"type":"user"}"#, "", "not json"].join("\n");
+        fs::write(project.join("session.jsonl"), lines).unwrap();
+
+        let missing = root.join("missing").to_string_lossy().into_owned();
+        let options = ScanOptions::new()
+            .with_data_dirs(vec![root.to_string_lossy().into_owned(), missing]);
+        let report = diagnose_data_source(None, &options, &PricingCalculator::new()).unwrap();
+
+        assert_eq!(report.data_dirs.len(), 2);
+        assert!(report.data_dirs[0].exists);
+        assert!(!report.data_dirs[1].exists);
+        assert_eq!(report.project_count, 1);
+        assert_eq!(report.session_file_count, 1);
+        assert_eq!(report.total_lines, 3);
+        assert_eq!(report.parsed_lines, 2);
+        assert_eq!(report.usage_entries, 1);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_dedup_counts_repeated_entries_once() {
+        let at = |h: u32| Utc.with_ymd_and_hms(2025, 1, 1, h, 0, 0).unwrap();
+        let entry = |id: &str, h: u32| UsageEntry {
+            timestamp: at(h),
+            input_tokens: 10,
+            output_tokens: 20,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            cost_usd: 0.0,
+            model: "claude-sonnet-4".to_string(),
+            message_id: id.to_string(),
+            request_id: Some(format!("req-{}", id)),
+        };
+        // m2 was logged in two session files
+        let entries = vec![entry("m1", 2), entry("m2", 9), entry("m2", 9), entry("m3", 11)];
+        let mut report = DedupReport {
+            usage_lines: 4,
+            raw_tokens: 120,
+            ..Default::default()
+        };
+
+        add_deduped_entries(&mut report, &entries, at(8));
+        assert_eq!(report.unique_entries, 3);
+        assert_eq!(report.deduped_tokens, 90);
+        assert!((report.dedup_ratio - 0.75).abs() < 1e-9);
+        assert_eq!(report.window_entries, 2);
+        assert_eq!(report.window_tokens, 60);
+        assert_eq!(report.window_start, Some(at(9)));
+        assert_eq!(report.window_end, Some(at(11)));
+        assert_eq!(report.window_tokens_per_minute, Some(0.5));
+    }
+
+    #[test]
+    fn test_entry_after_mtime_is_flagged() {
+        let at = |h: u32| Utc.with_ymd_and_hms(2025, 1, 1, h, 0, 0).unwrap();
+        let timestamps = vec![at(9), at(10), at(15)];
+
+        let skew = analyze_file_timestamps("session.jsonl", &timestamps, Some(at(10)));
+        assert_eq!(skew.out_of_order_count, 0);
+        assert_eq!(skew.after_mtime_count, 1);
+    }
+}
@@ -0,0 +1,162 @@
+//! Exports of usage data for spreadsheets and external tooling
+
+use chrono::{DateTime, Utc};
+
+use crate::usage::config::JSONL_DATA_SOURCE;
+use crate::usage::models::{ExportGranularity, UsageData, UsageExport};
+
+/// Version of the JSON export format
+pub const USAGE_EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Error type for export operations
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Export is not valid UTF-8: {0}")]
+    Utf8(#[from] std::string::FromUtf8Error),
+}
+
+/// Token, cost and message columns shared by every CSV row
+const USAGE_COLUMNS: [&str; 6] = [
+    "input_tokens",
+    "output_tokens",
+    "cache_creation_tokens",
+    "cache_read_tokens",
+    "cost_usd",
+    "message_count",
+];
+
+/// Render `data` as CSV with one row per day or per project. Fields are quoted and
+/// escaped by the CSV writer, so project paths containing commas or quotes stay intact.
+pub fn usage_to_csv(
+    data: &UsageData,
+    granularity: ExportGranularity,
+) -> Result<String, ExportError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    match granularity {
+        ExportGranularity::Day => {
+            writer.write_record(["date"].iter().chain(USAGE_COLUMNS.iter()))?;
+            for day in &data.daily_usage {
+                writer.write_record([
+                    day.date.clone(),
+                    day.input_tokens.to_string(),
+                    day.output_tokens.to_string(),
+                    day.cache_creation_tokens.to_string(),
+                    day.cache_read_tokens.to_string(),
+                    day.cost_usd.to_string(),
+                    day.message_count.to_string(),
+                ])?;
+            }
+        }
+        ExportGranularity::Project => {
+            let header = ["project_path", "display_name"];
+            writer.write_record(header.iter().chain(USAGE_COLUMNS.iter()))?;
+            for project in &data.projects {
+                writer.write_record([
+                    project.project_path.clone(),
+                    project.display_name.clone(),
+                    project.total_input_tokens.to_string(),
+                    project.total_output_tokens.to_string(),
+                    project.cache_creation_tokens.to_string(),
+                    project.cache_read_tokens.to_string(),
+                    project.total_cost_usd.to_string(),
+                    project.message_count.to_string(),
+                ])?;
+            }
+        }
+    }
+
+    let bytes = writer.into_inner().map_err(|e| ExportError::Io(e.into_error()))?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+/// Render the full usage data as pretty-printed JSON tagged with the schema version
+pub fn usage_to_json(data: &UsageData, now: &DateTime<Utc>) -> Result<String, ExportError> {
+    let export = UsageExport {
+        schema_version: USAGE_EXPORT_SCHEMA_VERSION,
+        exported_at: now.to_rfc3339(),
+        data_source: JSONL_DATA_SOURCE.to_string(),
+        usage: data.clone(),
+    };
+    Ok(serde_json::to_string_pretty(&export)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage::models::{DailyUsage, ProjectStats};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_usage_csv_per_day_and_per_project() {
+        let data = UsageData {
+            projects: vec![ProjectStats {
+                project_path: "D:\\code\\a, \"quoted\" app".to_string(),
+                display_name: "a, \"quoted\" app".to_string(),
+                total_input_tokens: 100,
+                total_output_tokens: 50,
+                total_cost_usd: 0.25,
+                message_count: 3,
+                ..Default::default()
+            }],
+            daily_usage: vec![DailyUsage {
+                date: "2025-01-15".to_string(),
+                input_tokens: 100,
+                output_tokens: 50,
+                cache_read_tokens: 7,
+                cost_usd: 0.25,
+                message_count: 3,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let daily = usage_to_csv(&data, ExportGranularity::Day).unwrap();
+        let lines: Vec<_> = daily.lines().collect();
+        assert_eq!(lines[0], format!("date,{}", USAGE_COLUMNS.join(",")));
+        assert_eq!(lines[1], "2025-01-15,100,50,0,7,0.25,3");
+
+        let projects = usage_to_csv(&data, ExportGranularity::Project).unwrap();
+        let lines: Vec<_> = projects.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("project_path,display_name,input_tokens"));
+        assert_eq!(
+            lines[1],
+            r#""D:\code\a, ""quoted"" app","a, ""quoted"" app",100,50,0,0,0.25,3"#
+        );
+
+        // Round-trips through a CSV reader
+        let mut reader = csv::Reader::from_reader(projects.as_bytes());
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!(&record[0], "D:\\code\\a, \"quoted\" app");
+    }
+
+    #[test]
+    fn test_usage_json_includes_schema_version() {
+        let data = UsageData {
+            projects: vec![ProjectStats {
+                project_path: "/home/me/app".to_string(),
+                message_count: 2,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let now = Utc.with_ymd_and_hms(2025, 1, 15, 12, 0, 0).unwrap();
+
+        let json = usage_to_json(&data, &now).unwrap();
+        assert!(json.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["schemaVersion"], USAGE_EXPORT_SCHEMA_VERSION);
+        assert_eq!(value["dataSource"], "jsonl");
+        assert_eq!(value["exportedAt"], now.to_rfc3339());
+        assert_eq!(value["projects"][0]["projectPath"], "/home/me/app");
+        assert!(value["dailyUsage"].is_array());
+        assert!(value["overallStats"].is_object());
+    }
+}
@@ -7,6 +7,12 @@ pub mod pricing;
 pub mod config;
 pub mod cache;
 pub mod background;
+pub mod clock;
+pub mod snapshot;
+pub mod diagnostics;
+pub mod export;
+pub mod alerts;
+pub mod timezone;
 
 pub use models::*;
 pub use reader::*;
@@ -15,3 +21,9 @@ pub use pricing::*;
 pub use config::*;
 pub use cache::*;
 pub use background::*;
+pub use clock::*;
+pub use snapshot::*;
+pub use diagnostics::*;
+pub use export::*;
+pub use alerts::*;
+pub use timezone::*;
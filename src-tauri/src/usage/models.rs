@@ -10,7 +10,7 @@ pub struct SessionEvent {
     pub event_type: Option<String>,
     pub message: Option<Message>,
     pub timestamp: Option<String>,
-    #[serde(alias = "costUSD", alias = "cost_usd")]
+    #[serde(default, alias = "costUSD", alias = "cost_usd", deserialize_with = "deserialize_cost")]
     pub cost: Option<f64>,
     pub usage: Option<Usage>,
     pub message_id: Option<String>,
@@ -20,6 +20,26 @@ pub struct SessionEvent {
     pub uuid: Option<String>,
 }
 
+/// Accept a cost written either as a JSON number or as a numeric string (`"0.0123"`).
+/// Strings that do not parse as a number are treated as absent.
+fn deserialize_cost<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawCost {
+        Number(f64),
+        Text(String),
+    }
+
+    Ok(match Option::<RawCost>::deserialize(deserializer)? {
+        Some(RawCost::Number(cost)) => Some(cost),
+        Some(RawCost::Text(text)) => text.trim().parse().ok(),
+        None => None,
+    })
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Message {
     pub role: Option<String>,
@@ -39,6 +59,34 @@ pub struct Usage {
     pub cache_creation_tokens: Option<u64>,
     #[serde(default, alias = "cache_read_input_tokens", alias = "cacheReadInputTokens")]
     pub cache_read_tokens: Option<u64>,
+    /// API service tier, "batch" for Batch API requests
+    #[serde(default, alias = "serviceTier")]
+    pub service_tier: Option<String>,
+    /// Cache writes split by cache lifetime, when reported
+    #[serde(default, alias = "cacheCreation")]
+    pub cache_creation: Option<CacheCreation>,
+}
+
+/// Cache write tokens by cache lifetime
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct CacheCreation {
+    #[serde(default)]
+    pub ephemeral_5m_input_tokens: Option<u64>,
+    #[serde(default)]
+    pub ephemeral_1h_input_tokens: Option<u64>,
+}
+
+impl CacheCreation {
+    pub fn total(&self) -> u64 {
+        self.ephemeral_5m_input_tokens.unwrap_or(0) + self.ephemeral_1h_input_tokens.unwrap_or(0)
+    }
+}
+
+impl Usage {
+    /// Whether these tokens were billed through the Batch API
+    pub fn is_batch(&self) -> bool {
+        self.service_tier.as_deref() == Some("batch")
+    }
 }
 
 /// Processed usage entry with extracted token counts
@@ -52,7 +100,8 @@ pub struct UsageEntry {
     pub cost_usd: f64,
     pub model: String,
     pub message_id: String,
-    pub request_id: String,
+    /// Request ID, or None when the event didn't carry one
+    pub request_id: Option<String>,
 }
 
 /// Statistics for a single project
@@ -70,6 +119,88 @@ pub struct ProjectStats {
     pub session_count: u32,
     pub first_activity: Option<String>,
     pub last_activity: Option<String>,
+    /// Cost per message (0 without messages)
+    pub avg_cost_per_message: f64,
+    /// Input plus output tokens per message (0 without messages)
+    pub avg_tokens_per_message: f64,
+}
+
+/// Order of a paged project listing; every key sorts descending
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectSortKey {
+    #[default]
+    LastActivity,
+    TotalCost,
+    /// Input plus output tokens
+    TotalTokens,
+}
+
+/// One page of the project listing
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectPage {
+    pub projects: Vec<ProjectStats>,
+    /// Number of projects across all pages
+    pub total: u32,
+    pub offset: u32,
+    pub limit: u32,
+}
+
+/// Cache effectiveness for a single project
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectCacheEfficiency {
+    pub project_path: String,
+    pub display_name: String,
+    pub fresh_input_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    /// Share of input-side tokens served from cache (0.0 - 1.0)
+    pub cache_read_ratio: f64,
+}
+
+/// Cost of a project's prompt caching compared with sending the same tokens as fresh input
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectCacheCostImpact {
+    pub project_path: String,
+    pub display_name: String,
+    pub fresh_input_cost_usd: f64,
+    pub cache_creation_cost_usd: f64,
+    pub cache_read_cost_usd: f64,
+    /// What the cached tokens would have cost at the model's fresh input rate
+    pub avoided_input_cost_usd: f64,
+    /// Avoided input cost minus cache cost; negative when caching cost more than it saved
+    pub net_savings_usd: f64,
+    /// Cache tokens of models without cache pricing, left out of the figures above
+    pub unpriced_cache_tokens: u64,
+}
+
+/// Share of all tokens by type over a range, in percent (0-100)
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenComposition {
+    /// Input + output + cache creation + cache read tokens
+    pub total_tokens: u64,
+    pub input_percent: f64,
+    pub output_percent: f64,
+    pub cache_creation_percent: f64,
+    pub cache_read_percent: f64,
+}
+
+/// Rough energy and carbon estimate for token usage. The factors are coarse
+/// assumptions, not measurements, so treat the result as an order of magnitude.
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FootprintEstimate {
+    /// Input + output tokens the estimate is based on
+    pub total_tokens: u64,
+    pub energy_kwh: f64,
+    pub co2_kg: f64,
+    /// Factors used for the estimate
+    pub kwh_per_million_tokens: f64,
+    pub co2_kg_per_kwh: f64,
 }
 
 /// Daily usage statistics
@@ -85,9 +216,199 @@ pub struct DailyUsage {
     pub message_count: u32,
 }
 
-/// Statistics for a specific model
+/// Usage rolled up over a week or month
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodUsage {
+    /// ISO week (`2025-W03`) or month (`2025-01`)
+    pub period: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cost_usd: f64,
+    pub message_count: u32,
+}
+
+/// Usage accrued since the app was last viewed
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSinceLastViewed {
+    /// When usage was last viewed (None before the first `mark_viewed`, with zero usage)
+    pub last_viewed_at: Option<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    pub cost_usd: f64,
+    pub message_count: u32,
+}
+
+/// Usage within one hour of a day (local time)
 #[derive(Debug, Clone, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
+pub struct HourlyUsage {
+    /// Hour of the day (0-23)
+    pub hour: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    /// Input + output tokens
+    pub total_tokens: u64,
+    pub cost_usd: f64,
+    pub message_count: u32,
+}
+
+/// Month-end projection from month-to-date usage (local dates)
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Forecast {
+    /// First day of the period being forecast (YYYY-MM-DD)
+    pub period_start: String,
+    /// Last day of the period being forecast (YYYY-MM-DD)
+    pub period_end: String,
+    /// Days so far, including today
+    pub days_elapsed: u32,
+    pub days_remaining: u32,
+    pub cost_to_date: f64,
+    /// Input + output tokens so far
+    pub tokens_to_date: u64,
+    pub projected_cost: f64,
+    pub projected_tokens: u64,
+    /// Projection using the slower of the last-7-days and month-to-date daily rates
+    pub projected_cost_low: f64,
+    /// Projection using the faster of the last-7-days and month-to-date daily rates
+    pub projected_cost_high: f64,
+}
+
+/// A day's cost with its trailing moving average
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyCostAverage {
+    pub date: String,
+    pub cost_usd: f64,
+    /// Average over the trailing window; None until a full window is available
+    pub moving_average: Option<f64>,
+}
+
+/// Period length used when bucketing usage over time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    Day,
+    /// ISO weeks starting on Monday
+    Week,
+    Month,
+}
+
+/// Row grouping for CSV exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportGranularity {
+    /// One row per local date
+    Day,
+    /// One row per project
+    Project,
+}
+
+/// Blended cost per million tokens for one period
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BlendedRatePoint {
+    /// First day of the period (YYYY-MM-DD)
+    pub period_start: String,
+    /// Input, output and cache tokens combined
+    pub total_tokens: u64,
+    pub cost_usd: f64,
+    pub cost_per_million_tokens: f64,
+}
+
+/// Average cost per message for one period
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageCostPoint {
+    /// First day of the period (YYYY-MM-DD)
+    pub period_start: String,
+    pub message_count: u32,
+    pub cost_usd: f64,
+    /// None for periods without messages
+    pub avg_cost_per_message: Option<f64>,
+}
+
+/// A labeled range of local hours, `start_hour` inclusive to `end_hour` exclusive.
+/// Ranges where `start_hour > end_hour` wrap past midnight (e.g. 18 -> 9).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeBucket {
+    pub label: String,
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+impl TimeBucket {
+    /// Check whether a local hour (0-23) falls inside this bucket
+    pub fn contains(&self, hour: u32) -> bool {
+        if self.start_hour < self.end_hour {
+            hour >= self.start_hour && hour < self.end_hour
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+/// Usage attributed to a time-of-day bucket
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeBucketUsage {
+    pub label: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cost_usd: f64,
+    pub message_count: u32,
+}
+
+/// A stretch of activity with no idle gap longer than the configured threshold
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkSession {
+    pub start_time: String,
+    pub end_time: String,
+    /// Time from the first to the last message (0 for a single message)
+    pub duration_minutes: f64,
+    pub message_count: u32,
+    pub cost_usd: f64,
+}
+
+/// Work sessions for a single project
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectWorkSessions {
+    pub project_path: String,
+    pub display_name: String,
+    pub session_count: u32,
+    pub total_duration_minutes: f64,
+    pub sessions: Vec<WorkSession>,
+}
+
+/// Work sessions per project and across all projects
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkSessionReport {
+    pub gap_minutes: u32,
+    pub session_count: u32,
+    pub total_duration_minutes: f64,
+    /// Sessions across all projects combined, so concurrent work counts once
+    pub sessions: Vec<WorkSession>,
+    pub projects: Vec<ProjectWorkSessions>,
+}
+
+/// Statistics for a specific model
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct ModelStats {
     pub model: String,
     pub input_tokens: u64,
@@ -98,19 +419,169 @@ pub struct ModelStats {
     pub cost_usd: f64,
     pub message_count: u32,
     pub percentage: f64,
+    /// Cache read tokens as a share of cache read + fresh input tokens (0-100)
+    #[serde(default)]
+    pub cache_hit_percent: f64,
+    /// Cost avoided by reading from cache instead of paying the full input rate
+    #[serde(default)]
+    pub cache_savings_usd: f64,
 }
 
-/// Burn rate metrics for current session
+/// Model statistics rolled up by model family (opus, sonnet, haiku, ...)
 #[derive(Debug, Clone, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
+pub struct ModelFamilyStats {
+    pub family: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    pub cost_usd: f64,
+    pub message_count: u32,
+    pub percentage: f64,
+    /// Variants within this family, sorted by total tokens descending
+    pub models: Vec<ModelStats>,
+}
+
+/// Model distribution as either a flat list or grouped by family
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum ModelDistribution {
+    Flat(Vec<ModelStats>),
+    Grouped(Vec<ModelFamilyStats>),
+}
+
+/// Burn rate metrics for current session
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct BurnRate {
+    /// Tokens attributed to the last hour, averaged over the full hour
     pub tokens_per_minute: f64,
+    /// Cost attributed to the last hour divided by the minutes of activity in it,
+    /// extrapolated to an hour
     pub cost_per_hour: f64,
 }
 
-/// Today's usage statistics (since local midnight)
+/// The single most expensive session block
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpensiveSession {
+    pub start_time: String,
+    pub end_time: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cost_usd: f64,
+    pub message_count: u32,
+    /// Model that accounted for the most cost in the block
+    pub dominant_model: String,
+}
+
+/// A 5-hour session block with its per-model breakdown, or an idle gap between blocks
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionBlockInfo {
+    /// Block start, aligned down to the hour
+    pub start_time: String,
+    /// When the block resets (start + 5 hours); for gaps, when the next block starts
+    pub end_time: String,
+    /// Timestamp of the last message in the block
+    pub actual_end_time: String,
+    /// The block containing the current time
+    pub is_active: bool,
+    /// Idle period of at least one session length with no messages
+    pub is_gap: bool,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    /// Input + output tokens
+    pub total_tokens: u64,
+    pub cost_usd: f64,
+    pub message_count: u32,
+    /// Per-model breakdown, most expensive first
+    pub models: Vec<ModelStats>,
+}
+
+/// Messages used against the plan's message limit in the active session block
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageBudget {
+    pub plan_type: String,
+    pub message_limit: u32,
+    pub messages_used: u32,
+    pub messages_remaining: u32,
+    pub session_start_time: Option<String>,
+    /// Message rate in the active block so far
+    pub messages_per_hour: f64,
+    /// When the limit will be reached at the current rate, if before the block resets
+    pub projected_limit_time: Option<String>,
+}
+
+/// How recent session blocks would fare against one plan tier's limits
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanProjection {
+    pub plan_type: String,
+    pub token_limit: u64,
+    pub cost_limit: f64,
+    pub message_limit: u32,
+    /// Session blocks in the projection window
+    pub sessions_analyzed: u32,
+    /// Blocks that would have reached any of the plan's limits
+    pub sessions_over_limit: u32,
+    /// Share of analyzed blocks over the limit (0-100)
+    pub limit_hit_percent: f64,
+    /// Cost incurred beyond the plan's cost limit, summed over blocks
+    pub estimated_overage_usd: f64,
+}
+
+/// Active session block consumption against one plan tier's limits
 #[derive(Debug, Clone, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
+pub struct PlanStatus {
+    pub plan_type: String,
+    pub token_limit: u64,
+    pub cost_limit: f64,
+    pub message_limit: u32,
+    /// Start of the active session block, if any
+    pub session_start_time: Option<String>,
+    pub tokens_used: u64,
+    pub cost_used_usd: f64,
+    pub messages_used: u32,
+    /// Share of each limit used in the active block (may exceed 100)
+    pub token_percent: f64,
+    pub cost_percent: f64,
+    pub message_percent: f64,
+}
+
+/// Plan limit a usage alert refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertMetric {
+    Tokens,
+    Cost,
+    Messages,
+}
+
+/// Payload of the `usage-alert` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageAlert {
+    pub metric: AlertMetric,
+    pub plan_type: String,
+    /// Threshold that was crossed, as a fraction of the limit
+    pub threshold: f64,
+    /// Fraction of the limit used in the active session block
+    pub fraction_used: f64,
+    pub session_start_time: Option<String>,
+}
+
+/// Today's usage statistics (since local midnight)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct TodayStats {
     pub cost_usd: f64,
     pub input_tokens: u64,
@@ -120,7 +591,7 @@ pub struct TodayStats {
 }
 
 /// Overall statistics across all projects
-#[derive(Debug, Clone, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct OverallStats {
     pub total_input_tokens: u64,
@@ -137,6 +608,18 @@ pub struct OverallStats {
     pub time_to_reset_minutes: u32,
     pub burn_rate: Option<BurnRate>,
     pub today_stats: TodayStats,
+    /// Cache read tokens as a share of cache read + fresh input tokens (0-100)
+    #[serde(default)]
+    pub cache_hit_percent: f64,
+    /// Cost avoided by reading from cache instead of paying the full input rate
+    #[serde(default)]
+    pub cache_savings_usd: f64,
+    /// Cost per message (0 without messages)
+    #[serde(default)]
+    pub avg_cost_per_message: f64,
+    /// Input plus output tokens per message (0 without messages)
+    #[serde(default)]
+    pub avg_tokens_per_message: f64,
 }
 
 /// Complete usage data response
@@ -146,6 +629,34 @@ pub struct UsageData {
     pub projects: Vec<ProjectStats>,
     pub daily_usage: Vec<DailyUsage>,
     pub overall_stats: OverallStats,
+    /// Where the data was read from; set by the commands
+    pub source_info: Option<DataSourceInfo>,
+}
+
+/// Where usage data was read from, so a missing data directory can be told apart
+/// from a directory without usage yet
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DataSourceInfo {
+    /// Data source in use; always "jsonl" since session logs are the only source
+    pub data_source: String,
+    /// Resolved Claude data directories
+    pub data_dirs: Vec<String>,
+    /// Whether any of them has a projects directory
+    pub dir_exists: bool,
+}
+
+/// Versioned dump of the full usage data for external tooling
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageExport {
+    /// Bumped whenever the exported format changes incompatibly
+    pub schema_version: u32,
+    pub exported_at: String,
+    /// Data source the usage was read from ("jsonl")
+    pub data_source: String,
+    #[serde(flatten)]
+    pub usage: UsageData,
 }
 
 /// Incremental update payload for push notifications
@@ -164,6 +675,186 @@ pub struct UsageDataDelta {
     pub daily_usage: Option<Vec<DailyUsage>>,
 }
 
+/// Classification of how predictable daily spend is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpendClassification {
+    Stable,
+    Variable,
+    Erratic,
+    /// Not enough days of history to judge
+    InsufficientData,
+}
+
+/// Variance and predictability of daily spend over a trailing window
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendStability {
+    pub window_days: u32,
+    /// Number of days that contributed to the statistics
+    pub days_counted: u32,
+    pub mean_daily_cost: f64,
+    pub std_dev_daily_cost: f64,
+    /// Standard deviation divided by mean (0 when mean is 0)
+    pub coefficient_of_variation: f64,
+    pub classification: SpendClassification,
+}
+
+/// An environment variable the app inspects and how its value is interpreted
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvVarDiagnostic {
+    pub name: String,
+    pub value: Option<String>,
+    pub interpretation: String,
+}
+
+/// Environment variables affecting data source selection, and the outcome
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvDiagnostics {
+    pub variables: Vec<EnvVarDiagnostic>,
+    /// Data source in use; always "jsonl" since session logs are the only source
+    pub active_data_source: String,
+    /// Resolved Claude data directory
+    pub data_dir: String,
+}
+
+/// Overall statistics saved under a name for later comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsSnapshot {
+    pub name: String,
+    pub created_at: String,
+    pub stats: OverallStats,
+}
+
+/// Change in a single metric between a snapshot and current stats
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricDelta {
+    pub metric: String,
+    pub before: f64,
+    pub after: f64,
+    pub delta: f64,
+    /// Relative change in percent; None when the snapshot value was zero
+    pub percent_change: Option<f64>,
+}
+
+/// Comparison of a saved snapshot against current stats
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotComparison {
+    pub name: String,
+    pub created_at: String,
+    pub deltas: Vec<MetricDelta>,
+}
+
+/// Timestamp anomalies found in a single session file
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileClockSkew {
+    pub file_path: String,
+    pub entry_count: u32,
+    /// Entries timestamped earlier than a preceding line
+    pub out_of_order_count: u32,
+    /// Largest backwards jump between lines, in seconds
+    pub max_backward_seconds: i64,
+    /// Entries timestamped after the file was last modified
+    pub after_mtime_count: u32,
+}
+
+/// Clock skew findings across all session files
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockSkewReport {
+    pub files_checked: u32,
+    pub total_out_of_order: u32,
+    pub total_after_mtime: u32,
+    /// Only files with at least one anomaly
+    pub affected_files: Vec<FileClockSkew>,
+}
+
+/// Lines of a single session file that could not be read or parsed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileParseErrors {
+    pub file_path: String,
+    pub entry_count: u32,
+    pub parse_errors: u32,
+}
+
+/// Parse failures across all session files
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DataHealthReport {
+    pub files_checked: u32,
+    /// Files that could not be opened at all
+    pub unreadable_files: Vec<String>,
+    pub total_parse_errors: u32,
+    /// Only files with at least one skipped line
+    pub affected_files: Vec<FileParseErrors>,
+}
+
+/// What data discovery found in one Claude data directory
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DataDirReport {
+    pub data_dir: String,
+    pub projects_dir: String,
+    /// Whether `projects_dir` exists; every count is zero when it does not
+    pub exists: bool,
+    pub project_count: u32,
+    pub session_file_count: u32,
+    /// Non-empty lines across all session files
+    pub total_lines: u64,
+    /// Lines that parsed as session events
+    pub parsed_lines: u64,
+    /// Usage entries left after per-file deduplication
+    pub usage_entries: u64,
+    /// Files that could not be opened at all
+    pub unreadable_files: Vec<String>,
+}
+
+/// Dry run of data discovery: where the app looks for session logs and what it finds
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DataSourceReport {
+    pub data_dirs: Vec<DataDirReport>,
+    pub project_count: u32,
+    pub session_file_count: u32,
+    pub total_lines: u64,
+    pub parsed_lines: u64,
+    pub usage_entries: u64,
+}
+
+/// Raw versus deduplicated token accounting, see `analyze_dedup`
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupReport {
+    /// Non-empty lines across all session files
+    pub total_lines: u64,
+    /// Lines that parsed as session events
+    pub parsed_lines: u64,
+    /// Parsed lines carrying token usage
+    pub usage_lines: u64,
+    /// Tokens summed over every usage line, duplicates included
+    pub raw_tokens: u64,
+    /// Entries left after deduplicating across all files
+    pub unique_entries: u64,
+    pub deduped_tokens: u64,
+    /// `unique_entries / usage_lines`, 0 when there are no usage lines
+    pub dedup_ratio: f64,
+    /// Length of the session window ending now
+    pub window_minutes: u32,
+    pub window_entries: u64,
+    pub window_tokens: u64,
+    pub window_start: Option<DateTime<Utc>>,
+    pub window_end: Option<DateTime<Utc>>,
+    /// Tokens per minute between the first and last window entry, None under a minute
+    pub window_tokens_per_minute: Option<f64>,
+}
+
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -174,6 +865,61 @@ pub struct AppConfig {
     pub refresh_interval_seconds: u32,
     #[serde(default = "default_plan_type")]
     pub plan_type: String,
+    /// Discover projects nested below immediate subdirectories of `projects`
+    #[serde(default)]
+    pub recursive_project_scan: bool,
+    /// Shortest duration (minutes) a session block is treated as lasting for burn rate
+    #[serde(default = "default_min_block_duration_minutes")]
+    pub min_block_duration_minutes: f64,
+    /// CSV of `request_id,cost_usd` whose costs supersede computed ones
+    #[serde(default)]
+    pub cost_override_path: Option<String>,
+    /// Several Claude data directories scanned together when no single data path is given
+    #[serde(default)]
+    pub data_paths: Vec<String>,
+    /// Most recent days of daily usage included in the main stats response (None = unlimited)
+    #[serde(default)]
+    pub max_daily_history_days: Option<u32>,
+    /// Assumed energy use (kWh) per million tokens for the footprint estimate
+    #[serde(default = "default_kwh_per_million_tokens")]
+    pub kwh_per_million_tokens: f64,
+    /// Assumed grid carbon intensity (kg CO2 per kWh) for the footprint estimate
+    #[serde(default = "default_co2_kg_per_kwh")]
+    pub co2_kg_per_kwh: f64,
+    /// Detect changed session files by content hash as well as mtime
+    #[serde(default)]
+    pub content_hash_cache: bool,
+    /// Seconds between rescans for new project directories (0 = every refresh)
+    #[serde(default = "default_rescan_interval_secs")]
+    pub rescan_interval_secs: u64,
+    /// Day of the month (1-31) a billing cycle starts on, for monthly rollups and forecasts
+    #[serde(default = "default_billing_cycle_start_day")]
+    pub billing_cycle_start_day: u8,
+    /// Fractions (0-1] of each plan limit at which a `usage-alert` event fires
+    #[serde(default = "default_alert_thresholds")]
+    pub alert_thresholds: Vec<f64>,
+    /// Length of a session (usage reset window) in minutes
+    #[serde(default = "default_session_duration_minutes")]
+    pub session_duration_minutes: u32,
+    /// Also end a session block after this many idle minutes (None = fixed-length blocks only)
+    #[serde(default)]
+    pub session_gap_minutes: Option<u32>,
+    /// Decimal places of reported cost totals (internal sums stay at full precision)
+    #[serde(default = "default_cost_decimals")]
+    pub cost_decimals: u32,
+    /// Multiplier applied to the cache rates of Batch API requests (1.0 = full price)
+    #[serde(default = "default_batch_cache_multiplier")]
+    pub batch_cache_multiplier: f64,
+    /// IANA timezone (e.g. `Europe/Berlin`) usage is bucketed into dates in (None = system zone)
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Glob below each projects directory matching session files, such as
+    /// `**/sessions/*.jsonl`; files in the same directory form one project
+    #[serde(default)]
+    pub session_glob: Option<String>,
+    /// RFC 3339 time usage was last viewed, set by `mark_viewed` (None = never)
+    #[serde(default)]
+    pub last_viewed_at: Option<String>,
 }
 
 fn default_data_path() -> Option<String> {
@@ -188,12 +934,66 @@ fn default_plan_type() -> String {
     "pro".to_string()
 }
 
+fn default_min_block_duration_minutes() -> f64 {
+    1.0
+}
+
+fn default_rescan_interval_secs() -> u64 {
+    60
+}
+
+fn default_billing_cycle_start_day() -> u8 {
+    1
+}
+
+fn default_alert_thresholds() -> Vec<f64> {
+    vec![0.8]
+}
+
+fn default_session_duration_minutes() -> u32 {
+    300 // 5 hours
+}
+
+fn default_cost_decimals() -> u32 {
+    6
+}
+
+fn default_batch_cache_multiplier() -> f64 {
+    1.0
+}
+
+fn default_kwh_per_million_tokens() -> f64 {
+    0.3
+}
+
+fn default_co2_kg_per_kwh() -> f64 {
+    0.4
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             data_path: None,
             refresh_interval_seconds: 300,
             plan_type: "pro".to_string(),
+            recursive_project_scan: false,
+            min_block_duration_minutes: 1.0,
+            cost_override_path: None,
+            data_paths: Vec::new(),
+            max_daily_history_days: None,
+            kwh_per_million_tokens: 0.3,
+            co2_kg_per_kwh: 0.4,
+            content_hash_cache: false,
+            rescan_interval_secs: 60,
+            billing_cycle_start_day: 1,
+            alert_thresholds: vec![0.8],
+            session_duration_minutes: 300,
+            session_gap_minutes: None,
+            cost_decimals: 6,
+            batch_cache_multiplier: 1.0,
+            timezone: None,
+            session_glob: None,
+            last_viewed_at: None,
         }
     }
 }
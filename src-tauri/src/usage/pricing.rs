@@ -1,31 +1,191 @@
 //! Pricing calculation for Claude models
 
 use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::usage::models::{AppConfig, Usage};
+
+/// Environment variable pointing at a JSON file of custom model pricing
+pub const PRICING_FILE_ENV: &str = "CCM_PRICING_FILE";
+
+/// Error type for loading custom pricing
+#[derive(Debug, thiserror::Error)]
+pub enum PricingError {
+    #[error("Failed to read pricing file {0}: {1}")]
+    Io(String, #[source] std::io::Error),
+    #[error("Invalid pricing file {0}: {1}")]
+    Json(String, #[source] serde_json::Error),
+    #[error("Invalid pricing for model {0}: prices must be finite and non-negative")]
+    InvalidPrice(String),
+    #[error("Invalid cost override file {0} at line {1}: expected request_id,cost_usd")]
+    InvalidOverride(String, usize),
+}
+
+/// Load audited costs from a CSV of `request_id,cost_usd` rows.
+/// A header row is allowed, fields may be quoted and blank lines are skipped.
+pub fn load_cost_overrides(path: &Path) -> Result<HashMap<String, f64>, PricingError> {
+    let display = path.display().to_string();
+    let content = fs::read_to_string(path).map_err(|e| PricingError::Io(display.clone(), e))?;
+    parse_cost_overrides(&content).map_err(|line| PricingError::InvalidOverride(display, line))
+}
+
+/// Parse cost override CSV content, returning the 1-based line number of the first bad row
+fn parse_cost_overrides(content: &str) -> Result<HashMap<String, f64>, usize> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    let mut overrides = HashMap::new();
+    // csv positions do not count blank lines, so derive the line from the byte offset,
+    // which may point at blank lines before the record
+    let line_of = |position: Option<&csv::Position>| {
+        let start = position.map_or(0, |p| p.byte() as usize);
+        let record = content[start..].trim_start();
+        content[..content.len() - record.len()].matches('\n').count() + 1
+    };
+
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| line_of(e.position()))?;
+        let line = line_of(record.position());
+        let (request_id, cost) = match (record.get(0), record.get(1), record.get(2)) {
+            (Some(request_id), Some(cost), None) if !request_id.is_empty() => (request_id, cost),
+            _ => return Err(line),
+        };
+
+        match cost.parse::<f64>() {
+            Ok(cost) if cost.is_finite() && cost >= 0.0 => {
+                overrides.insert(request_id.to_string(), cost);
+            }
+            // Tolerate a header row
+            Err(_) if index == 0 => continue,
+            _ => return Err(line),
+        }
+    }
+
+    Ok(overrides)
+}
+
+/// Detect a Claude 4.5 generation model (`opus-4-5`, `sonnet-4.5`, dated or not)
+/// and return its family name
+pub fn claude_4_5_family(model: &str) -> Option<&'static str> {
+    let model_lower = model.to_lowercase();
+    ["opus", "sonnet", "haiku"].into_iter().find(|family| {
+        [format!("{}-4-5", family), format!("{}-4.5", family)]
+            .iter()
+            .any(|marker| match model_lower.find(marker.as_str()) {
+                // Reject longer version numbers such as `4-50`
+                Some(pos) => !model_lower[pos + marker.len()..]
+                    .starts_with(|c: char| c.is_ascii_digit()),
+                None => false,
+            })
+    })
+}
 
 /// Pricing per million tokens (USD)
 #[derive(Debug, Clone)]
 pub struct ModelPricing {
     pub input: f64,
     pub output: f64,
-    pub cache_creation: f64,
+    /// 5-minute prompt cache writes
+    pub cache_creation_5m: f64,
+    /// 1-hour prompt cache writes
+    pub cache_creation_1h: f64,
     pub cache_read: f64,
 }
 
 impl ModelPricing {
-    pub fn new(input: f64, output: f64, cache_creation: f64, cache_read: f64) -> Self {
+    pub fn new(
+        input: f64,
+        output: f64,
+        cache_creation_5m: f64,
+        cache_creation_1h: f64,
+        cache_read: f64,
+    ) -> Self {
         Self {
             input,
             output,
-            cache_creation,
+            cache_creation_5m,
+            cache_creation_1h,
             cache_read,
         }
     }
+
+    /// Whether the model bills prompt cache tokens at all
+    pub fn has_cache_pricing(&self) -> bool {
+        self.cache_creation_5m > 0.0 || self.cache_creation_1h > 0.0 || self.cache_read > 0.0
+    }
+}
+
+/// Multiplier of the input rate charged for 1-hour cache writes when a pricing
+/// file only gives a single cache creation rate
+const CACHE_WRITE_1H_INPUT_MULTIPLIER: f64 = 2.0;
+
+/// Model pricing as written in a custom pricing file
+#[derive(Debug, Deserialize)]
+struct PricingFileEntry {
+    input: f64,
+    output: f64,
+    #[serde(alias = "cache_creation")]
+    cache_creation_5m: f64,
+    #[serde(default)]
+    cache_creation_1h: Option<f64>,
+    cache_read: f64,
+}
+
+impl From<PricingFileEntry> for ModelPricing {
+    fn from(entry: PricingFileEntry) -> Self {
+        let cache_creation_1h = entry
+            .cache_creation_1h
+            .unwrap_or(entry.input * CACHE_WRITE_1H_INPUT_MULTIPLIER);
+        Self::new(
+            entry.input,
+            entry.output,
+            entry.cache_creation_5m,
+            cache_creation_1h,
+            entry.cache_read,
+        )
+    }
+}
+
+/// Token counts to be billed, with cache writes split by cache lifetime
+#[derive(Debug, Clone, Copy, Default)]
+struct BillableTokens {
+    input: u64,
+    output: u64,
+    cache_creation_5m: u64,
+    cache_creation_1h: u64,
+    cache_read: u64,
+}
+
+/// Decimal places costs are rounded to unless configured otherwise
+pub const DEFAULT_COST_DECIMALS: u32 = 6;
+
+/// Round a cost to `decimals` places. Sums are accumulated at full precision and only
+/// rounded as a final step, so rounding error does not compound across entries.
+pub fn round_cost(value: f64, decimals: u32) -> f64 {
+    let scale = 10f64.powi(decimals as i32);
+    (value * scale).round() / scale
 }
 
+/// Multiplier the Batch API applies to input and output token rates
+pub const BATCH_DISCOUNT: f64 = 0.5;
+
 /// Calculator for API costs based on token usage
+#[derive(Clone)]
 pub struct PricingCalculator {
     pricing: HashMap<String, ModelPricing>,
     default_pricing: ModelPricing,
+    /// Multiplier applied to cache rates for batch requests (1.0 = full price)
+    batch_cache_multiplier: f64,
+    /// Audited costs keyed by request_id that supersede computed costs
+    cost_overrides: HashMap<String, f64>,
 }
 
 impl Default for PricingCalculator {
@@ -35,35 +195,128 @@ impl Default for PricingCalculator {
 }
 
 impl PricingCalculator {
+    /// Create a calculator with built-in pricing, merged with the file named by
+    /// `CCM_PRICING_FILE` when set. The file is read once per process; one that fails to
+    /// load is logged and ignored.
     pub fn new() -> Self {
+        static FROM_ENV: OnceLock<Option<PricingCalculator>> = OnceLock::new();
+        let from_env = FROM_ENV.get_or_init(|| {
+            let path = env::var(PRICING_FILE_ENV).ok()?;
+            Self::from_file(Path::new(&path))
+                .inspect_err(|e| warn!("Ignoring {}: {}", PRICING_FILE_ENV, e))
+                .ok()
+        });
+        from_env.clone().unwrap_or_else(Self::builtin)
+    }
+
+    /// Create a calculator as `new()` does, adding the configured cost overrides and
+    /// batch cache multiplier
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self::new()
+            .with_cost_override_file(config.cost_override_path.as_deref())
+            .with_batch_cache_multiplier(config.batch_cache_multiplier)
+    }
+
+    /// Load cost overrides from `path` when given. A file that fails to load is
+    /// logged and ignored so estimates are still shown.
+    pub fn with_cost_override_file(self, path: Option<&str>) -> Self {
+        match path.map(|p| load_cost_overrides(Path::new(p))) {
+            Some(Ok(overrides)) => self.with_cost_overrides(overrides),
+            Some(Err(e)) => {
+                warn!("Ignoring cost overrides: {}", e);
+                self
+            }
+            None => self,
+        }
+    }
+
+    /// Use audited costs keyed by request_id in place of computed costs
+    pub fn with_cost_overrides(mut self, overrides: HashMap<String, f64>) -> Self {
+        self.cost_overrides = overrides;
+        self
+    }
+
+    /// Get the audited cost for a request, if one was loaded
+    pub fn cost_override(&self, request_id: &str) -> Option<f64> {
+        self.cost_overrides.get(request_id).copied()
+    }
+
+    /// Create a calculator from a JSON map of model name to
+    /// `{input, output, cache_creation_5m, cache_creation_1h, cache_read}` (USD per
+    /// million tokens), merged over the built-in pricing. `cache_creation` is accepted
+    /// for the 5-minute rate; a missing 1-hour rate defaults to twice the input rate.
+    pub fn from_file(path: &Path) -> Result<Self, PricingError> {
+        let display = path.display().to_string();
+        let content =
+            fs::read_to_string(path).map_err(|e| PricingError::Io(display.clone(), e))?;
+        let custom: HashMap<String, PricingFileEntry> =
+            serde_json::from_str(&content).map_err(|e| PricingError::Json(display, e))?;
+
+        let mut calculator = Self::builtin();
+        for (model, entry) in custom {
+            let pricing = ModelPricing::from(entry);
+            let prices = [
+                pricing.input,
+                pricing.output,
+                pricing.cache_creation_5m,
+                pricing.cache_creation_1h,
+                pricing.cache_read,
+            ];
+            if prices.iter().any(|p| !p.is_finite() || *p < 0.0) {
+                return Err(PricingError::InvalidPrice(model));
+            }
+            calculator.pricing.insert(model.to_lowercase(), pricing);
+        }
+        Ok(calculator)
+    }
+
+    fn builtin() -> Self {
         let mut pricing = HashMap::new();
 
+        // Claude 4.5 generation pricing
+        pricing.insert("claude-opus-4-5".to_string(), ModelPricing::new(5.0, 25.0, 6.25, 10.0, 0.5));
+        pricing.insert("claude-sonnet-4-5".to_string(), ModelPricing::new(3.0, 15.0, 3.75, 6.0, 0.3));
+        pricing.insert("claude-haiku-4-5".to_string(), ModelPricing::new(1.0, 5.0, 1.25, 2.0, 0.1));
+
         // Opus pricing
-        let opus = ModelPricing::new(15.0, 75.0, 18.75, 1.5);
+        let opus = ModelPricing::new(15.0, 75.0, 18.75, 30.0, 1.5);
         pricing.insert("claude-3-opus".to_string(), opus.clone());
         pricing.insert("claude-opus-4".to_string(), opus.clone());
 
         // Sonnet pricing (default)
-        let sonnet = ModelPricing::new(3.0, 15.0, 3.75, 0.3);
+        let sonnet = ModelPricing::new(3.0, 15.0, 3.75, 6.0, 0.3);
         pricing.insert("claude-3-sonnet".to_string(), sonnet.clone());
         pricing.insert("claude-3-5-sonnet".to_string(), sonnet.clone());
         pricing.insert("claude-sonnet-4".to_string(), sonnet.clone());
 
         // Haiku pricing
-        let haiku = ModelPricing::new(0.25, 1.25, 0.3, 0.03);
+        let haiku = ModelPricing::new(0.25, 1.25, 0.3, 0.5, 0.03);
         pricing.insert("claude-3-haiku".to_string(), haiku.clone());
         pricing.insert("claude-3-5-haiku".to_string(), haiku);
 
         Self {
             pricing,
             default_pricing: sonnet, // Default to Sonnet pricing
+            batch_cache_multiplier: 1.0,
+            cost_overrides: HashMap::new(),
         }
     }
 
+    /// Set the multiplier applied to cache rates for batch requests
+    pub fn with_batch_cache_multiplier(mut self, multiplier: f64) -> Self {
+        self.batch_cache_multiplier = multiplier;
+        self
+    }
+
     /// Normalize model name for pricing lookup
     fn normalize_model_name(&self, model: &str) -> String {
         let model_lower = model.to_lowercase();
 
+        // Handle Claude 4.5 models before the broader Claude 4 patterns
+        if let Some(family) = claude_4_5_family(&model_lower) {
+            return format!("claude-{}-4-5", family);
+        }
+
         // Handle Claude 4 models
         if model_lower.contains("opus-4") || model_lower.contains("claude-opus-4") {
             return "claude-opus-4".to_string();
@@ -93,13 +346,16 @@ impl PricingCalculator {
         "claude-3-5-sonnet".to_string()
     }
 
-    /// Get pricing for a model
-    fn get_pricing(&self, model: &str) -> &ModelPricing {
+    /// Get pricing for a model, preferring an exact (custom) entry over the normalized family
+    pub fn get_pricing(&self, model: &str) -> &ModelPricing {
+        if let Some(pricing) = self.pricing.get(&model.to_lowercase()) {
+            return pricing;
+        }
         let normalized = self.normalize_model_name(model);
         self.pricing.get(&normalized).unwrap_or(&self.default_pricing)
     }
 
-    /// Calculate cost for token usage
+    /// Calculate cost for token usage. Cache writes are billed at the 5-minute rate.
     pub fn calculate_cost(
         &self,
         model: &str,
@@ -107,29 +363,126 @@ impl PricingCalculator {
         output_tokens: u64,
         cache_creation_tokens: u64,
         cache_read_tokens: u64,
+    ) -> f64 {
+        let tokens = BillableTokens {
+            input: input_tokens,
+            output: output_tokens,
+            cache_creation_5m: cache_creation_tokens,
+            cache_creation_1h: 0,
+            cache_read: cache_read_tokens,
+        };
+        self.cost_with_multipliers(model, tokens, 1.0, 1.0)
+    }
+
+    /// Calculate cost for a Batch API request: input and output are discounted by
+    /// `BATCH_DISCOUNT`, cache tokens use the configured batch cache multiplier
+    pub fn calculate_cost_batch(
+        &self,
+        model: &str,
+        input_tokens: u64,
+        output_tokens: u64,
+        cache_creation_tokens: u64,
+        cache_read_tokens: u64,
+    ) -> f64 {
+        let tokens = BillableTokens {
+            input: input_tokens,
+            output: output_tokens,
+            cache_creation_5m: cache_creation_tokens,
+            cache_creation_1h: 0,
+            cache_read: cache_read_tokens,
+        };
+        self.cost_with_multipliers(model, tokens, BATCH_DISCOUNT, self.batch_cache_multiplier)
+    }
+
+    /// Calculate cost for a usage record, billing 5-minute and 1-hour cache writes at
+    /// their own rates when the breakdown is present (otherwise all cache writes use
+    /// the 5-minute rate) and applying the Batch API discount for batch requests.
+    pub fn calculate_usage_cost(&self, model: &str, usage: &Usage) -> f64 {
+        let (cache_creation_5m, cache_creation_1h) = match &usage.cache_creation {
+            Some(breakdown) if breakdown.total() > 0 => (
+                breakdown.ephemeral_5m_input_tokens.unwrap_or(0),
+                breakdown.ephemeral_1h_input_tokens.unwrap_or(0),
+            ),
+            _ => (usage.cache_creation_tokens.unwrap_or(0), 0),
+        };
+        let tokens = BillableTokens {
+            input: usage.input_tokens.unwrap_or(0),
+            output: usage.output_tokens.unwrap_or(0),
+            cache_creation_5m,
+            cache_creation_1h,
+            cache_read: usage.cache_read_tokens.unwrap_or(0),
+        };
+
+        if usage.is_batch() {
+            self.cost_with_multipliers(model, tokens, BATCH_DISCOUNT, self.batch_cache_multiplier)
+        } else {
+            self.cost_with_multipliers(model, tokens, 1.0, 1.0)
+        }
+    }
+
+    fn cost_with_multipliers(
+        &self,
+        model: &str,
+        tokens: BillableTokens,
+        io_multiplier: f64,
+        cache_multiplier: f64,
     ) -> f64 {
         let pricing = self.get_pricing(model);
+        let per_million = |count: u64, rate: f64| (count as f64 / 1_000_000.0) * rate;
 
-        let input_cost = (input_tokens as f64 / 1_000_000.0) * pricing.input;
-        let output_cost = (output_tokens as f64 / 1_000_000.0) * pricing.output;
-        let cache_creation_cost =
-            (cache_creation_tokens as f64 / 1_000_000.0) * pricing.cache_creation;
-        let cache_read_cost = (cache_read_tokens as f64 / 1_000_000.0) * pricing.cache_read;
+        let input_cost = per_million(tokens.input, pricing.input) * io_multiplier;
+        let output_cost = per_million(tokens.output, pricing.output) * io_multiplier;
+        let cache_creation_cost = (per_million(tokens.cache_creation_5m, pricing.cache_creation_5m)
+            + per_million(tokens.cache_creation_1h, pricing.cache_creation_1h))
+            * cache_multiplier;
+        let cache_read_cost = per_million(tokens.cache_read, pricing.cache_read) * cache_multiplier;
 
-        // Round to 6 decimal places
-        ((input_cost + output_cost + cache_creation_cost + cache_read_cost) * 1_000_000.0).round()
-            / 1_000_000.0
+        input_cost + output_cost + cache_creation_cost + cache_read_cost
     }
 }
 
+/// Plan tiers with distinct limits, from smallest to largest
+pub const PLAN_TYPES: &[&str] = &["pro", "max5", "max20"];
+
 /// Plan limits
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PlanLimits {
     pub token_limit: u64,
     pub cost_limit: f64,
     pub message_limit: u32,
 }
 
+/// A plan tier as offered in the settings
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanInfo {
+    /// Value stored in `AppConfig.plan_type`
+    pub id: String,
+    pub display_name: String,
+    pub limits: PlanLimits,
+}
+
+/// Every plan in `PLAN_TYPES` with its display name and limits
+pub fn list_plans() -> Vec<PlanInfo> {
+    PLAN_TYPES
+        .iter()
+        .map(|plan_type| PlanInfo {
+            id: plan_type.to_string(),
+            display_name: plan_display_name(plan_type).to_string(),
+            limits: get_plan_limits(plan_type),
+        })
+        .collect()
+}
+
+fn plan_display_name(plan_type: &str) -> &'static str {
+    match plan_type {
+        "max5" => "Max (5x)",
+        "max20" => "Max (20x)",
+        _ => "Pro",
+    }
+}
+
 /// Get plan limits by plan type
 pub fn get_plan_limits(plan_type: &str) -> PlanLimits {
     match plan_type.to_lowercase().as_str() {
@@ -160,12 +513,160 @@ pub fn get_plan_limits(plan_type: &str) -> PlanLimits {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_list_plans_matches_plan_types() {
+        let plans = list_plans();
+        let ids: Vec<&str> = plans.iter().map(|plan| plan.id.as_str()).collect();
+        assert_eq!(ids, PLAN_TYPES);
+        assert_eq!(plans[2].display_name, "Max (20x)");
+        assert_eq!(plans[2].limits.token_limit, 220_000);
+
+        let json = serde_json::to_value(&plans[0]).unwrap();
+        assert_eq!(json["limits"]["messageLimit"], 250);
+    }
+
     #[test]
     fn test_calculate_cost_sonnet() {
         let calculator = PricingCalculator::new();
         let cost = calculator.calculate_cost("claude-3-5-sonnet", 1_000_000, 1_000_000, 0, 0);
         // Expected: 3.0 + 15.0 = 18.0
         assert!((cost - 18.0).abs() < 0.001);
+
+        // Per-entry costs are not rounded; one cache read costs $0.0000003
+        let cost = calculator.calculate_cost("claude-3-5-sonnet", 0, 0, 0, 1);
+        assert!((cost - 0.000_000_3).abs() < 1e-12);
+    }
+
+    fn write_pricing_file(name: &str, content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("ccm-pricing-{}-{}.json", name, std::process::id()));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_round_cost() {
+        assert_eq!(round_cost(1.234_567_89, DEFAULT_COST_DECIMALS), 1.234_568);
+        assert_eq!(round_cost(1.235, 2), 1.24);
+        assert_eq!(round_cost(1.5, 0), 2.0);
+    }
+
+    #[test]
+    fn test_pricing_from_file() {
+        let path = write_pricing_file(
+            "custom",
+            r#"{
+                "claude-new-model": {"input": 2.0, "output": 10.0, "cache_creation": 2.5, "cache_read": 0.2},
+                "claude-opus-4": {"input": 5.0, "output": 25.0, "cache_creation": 6.25, "cache_read": 0.5}
+            }"#,
+        );
+        let calculator = PricingCalculator::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // Unknown model from the file is priced directly instead of falling back to Sonnet
+        let cost = calculator.calculate_cost("claude-new-model", 1_000_000, 1_000_000, 0, 0);
+        assert!((cost - 12.0).abs() < 0.001);
+        // Overrides apply to every name normalizing to the family
+        let cost = calculator.calculate_cost("claude-opus-4-20250514", 1_000_000, 0, 0, 0);
+        assert!((cost - 5.0).abs() < 0.001);
+        // Built-in entries not in the file are kept
+        let cost = calculator.calculate_cost("claude-3-5-haiku", 1_000_000, 0, 0, 0);
+        assert!((cost - 0.25).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_pricing_from_malformed_file() {
+        let path = write_pricing_file("malformed", r#"{"claude-new-model": {"input": 2.0}}"#);
+        let result = PricingCalculator::from_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(PricingError::Json(..))));
+
+        let path = write_pricing_file(
+            "negative",
+            r#"{"m": {"input": -1.0, "output": 1.0, "cache_creation": 1.0, "cache_read": 1.0}}"#,
+        );
+        let result = PricingCalculator::from_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(PricingError::InvalidPrice(_))));
+
+        let missing = std::env::temp_dir().join("ccm-pricing-does-not-exist.json");
+        assert!(matches!(PricingCalculator::from_file(&missing), Err(PricingError::Io(..))));
+    }
+
+    #[test]
+    fn test_normalize_claude_4_5_models() {
+        let calculator = PricingCalculator::new();
+        assert_eq!(calculator.normalize_model_name("claude-opus-4-5-20251101"), "claude-opus-4-5");
+        assert_eq!(calculator.normalize_model_name("claude-sonnet-4-5-20250929"), "claude-sonnet-4-5");
+        assert_eq!(calculator.normalize_model_name("claude-haiku-4-5-20251001"), "claude-haiku-4-5");
+        assert_eq!(calculator.normalize_model_name("claude-haiku-4-5"), "claude-haiku-4-5");
+        // Claude 4 dated names are not mistaken for 4.5
+        assert_eq!(calculator.normalize_model_name("claude-opus-4-20250514"), "claude-opus-4");
+
+        let cost = calculator.calculate_cost("claude-opus-4-5-20251101", 1_000_000, 1_000_000, 0, 0);
+        assert!((cost - 30.0).abs() < 0.001);
+        let cost = calculator.calculate_cost("claude-haiku-4-5-20251001", 1_000_000, 1_000_000, 0, 0);
+        assert!((cost - 6.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_calculate_cost_batch() {
+        let calculator = PricingCalculator::new();
+        let cost = calculator.calculate_cost_batch("claude-3-5-sonnet", 1_000_000, 1_000_000, 1_000_000, 0);
+        // Input/output halved (1.5 + 7.5), cache creation at full 3.75
+        assert!((cost - 12.75).abs() < 0.001);
+
+        let calculator = PricingCalculator::from_config(&AppConfig {
+            batch_cache_multiplier: 0.5,
+            ..AppConfig::default()
+        });
+        let cost = calculator.calculate_cost_batch("claude-3-5-sonnet", 1_000_000, 1_000_000, 1_000_000, 0);
+        assert!((cost - 10.875).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_cost_override_csv() {
+        let path = write_pricing_file(
+            "overrides",
+            "request_id,cost_usd\nreq_audited,0.42\n\n\"req_quoted\", 1.5\n\"req,comma\",\"2.5\"\n",
+        );
+        let overrides = load_cost_overrides(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let calculator = PricingCalculator::new().with_cost_overrides(overrides);
+        assert_eq!(calculator.cost_override("req_audited"), Some(0.42));
+        assert_eq!(calculator.cost_override("req_quoted"), Some(1.5));
+        assert_eq!(calculator.cost_override("req,comma"), Some(2.5));
+        assert_eq!(calculator.cost_override("req_other"), None);
+
+        assert_eq!(parse_cost_overrides("req_1,0.1\nreq_2,abc"), Err(2));
+        assert_eq!(parse_cost_overrides("req_1,0.1,extra"), Err(1));
+        assert_eq!(parse_cost_overrides("req_1,0.1\n\nreq_2,-1"), Err(3));
+    }
+
+    #[test]
+    fn test_cache_write_breakdown_pricing() {
+        let calculator = PricingCalculator::new();
+        let usage: Usage = serde_json::from_value(serde_json::json!({
+            "input_tokens": 0,
+            "output_tokens": 0,
+            "cache_creation_input_tokens": 2_000_000,
+            "cache_creation": {
+                "ephemeral_5m_input_tokens": 1_000_000,
+                "ephemeral_1h_input_tokens": 1_000_000
+            }
+        }))
+        .unwrap();
+        // Sonnet: 3.75 for 5-minute writes + 6.0 for 1-hour writes
+        let cost = calculator.calculate_usage_cost("claude-sonnet-4-20250514", &usage);
+        assert!((cost - 9.75).abs() < 0.001);
+
+        // Without the breakdown every cache write is billed at the 5-minute rate
+        let combined = Usage {
+            cache_creation_tokens: Some(2_000_000),
+            ..Default::default()
+        };
+        let cost = calculator.calculate_usage_cost("claude-sonnet-4-20250514", &combined);
+        assert!((cost - 7.5).abs() < 0.001);
     }
 
     #[test]
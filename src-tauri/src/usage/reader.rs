@@ -1,16 +1,23 @@
 //! JSONL file reading and parsing
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
 use glob::glob;
 use log::{debug, warn};
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
 
-use crate::usage::config::{decode_project_path, get_display_name, get_projects_dir};
-use crate::usage::models::{SessionEvent, Usage, UsageEntry};
+use crate::usage::config::{
+    decode_project_path, get_claude_data_dirs, get_data_dirs, get_display_name, get_projects_dir,
+    get_session_glob, project_group_key, PATHS_CASE_INSENSITIVE,
+};
+use crate::usage::models::{AppConfig, SessionEvent, Usage, UsageEntry};
 use crate::usage::pricing::PricingCalculator;
 
 /// Error type for reader operations
@@ -26,6 +33,18 @@ pub enum ReaderError {
     InvalidPath(String),
 }
 
+/// Treat a missing projects directory as zero usage so a fresh install shows an empty
+/// dashboard; every other error is passed through
+pub fn empty_if_missing<T: Default>(result: Result<T, ReaderError>) -> Result<T, ReaderError> {
+    match result {
+        Err(ReaderError::DirNotFound(dir)) => {
+            debug!("Projects directory not found, reporting no usage: {}", dir);
+            Ok(T::default())
+        }
+        other => other,
+    }
+}
+
 /// Project with its sessions
 #[derive(Debug)]
 pub struct ProjectData {
@@ -35,8 +54,122 @@ pub struct ProjectData {
     pub session_files: Vec<PathBuf>,
 }
 
+/// Usage entries read from one session file, with the number of lines skipped
+#[derive(Debug, Default)]
+pub struct FileReadReport {
+    pub entries: Vec<UsageEntry>,
+    /// Lines that could not be read or were not valid JSON
+    pub parse_errors: u32,
+    /// Raw line counts from the same pass, before deduplication
+    pub lines: SessionLineCounts,
+}
+
+/// Raw line counts of one session file, see `FileReadReport`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SessionLineCounts {
+    /// Non-empty lines, including ones that could not be read
+    pub total_lines: u64,
+    /// Lines that parsed as session events
+    pub parsed_lines: u64,
+    /// Parsed lines carrying token usage
+    pub usage_lines: u64,
+    /// Tokens summed over every usage line, duplicates included
+    pub raw_tokens: u64,
+}
+
+/// Usage entries parsed from a byte offset of a plain session file
+#[derive(Debug, Default)]
+pub struct AppendedEntries {
+    /// Entries in file order with their dedup keys (None for entries never deduplicated).
+    /// Repeats of a key are not collapsed here; the later one should win.
+    pub entries: Vec<(Option<String>, UsageEntry)>,
+    /// Offset just past the last line parsed; the next read resumes here
+    pub end_offset: u64,
+}
+
+/// Maximum directory depth walked below `projects` when recursive scanning is enabled
+const MAX_PROJECT_SCAN_DEPTH: usize = 8;
+
+/// Upper bound on threads used to parse session files, however many projects there are
+const MAX_PARSE_THREADS: usize = 8;
+
+/// Session file name patterns; rotated sessions may be gzip-compressed
+const SESSION_FILE_PATTERNS: &[&str] = &["*.jsonl", "*.jsonl.gz"];
+
+/// Session files at least this large are read twice when streamed instead of buffered,
+/// so their entries are never held in memory all at once
+const STREAM_FILE_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Options controlling how project directories are discovered
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    /// Walk the projects tree to any depth (bounded by `MAX_PROJECT_SCAN_DEPTH`)
+    /// instead of only looking at immediate subdirectories
+    pub recursive: bool,
+    /// Claude data directories scanned together when no custom path is given
+    pub data_dirs: Vec<String>,
+    /// Glob below the projects directory matching session files, replacing the
+    /// `<project>/*.jsonl` layout (see `get_session_glob`)
+    pub session_glob: Option<String>,
+}
+
+impl ScanOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build scan options from the application configuration
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            recursive: config.recursive_project_scan,
+            data_dirs: get_data_dirs(&config.data_paths),
+            session_glob: get_session_glob(config.session_glob.as_deref()),
+        }
+    }
+
+    pub fn with_recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    pub fn with_data_dirs(mut self, data_dirs: Vec<String>) -> Self {
+        self.data_dirs = data_dirs;
+        self
+    }
+
+    pub fn with_session_glob(mut self, session_glob: Option<String>) -> Self {
+        self.session_glob = session_glob;
+        self
+    }
+}
+
 /// List all projects in the Claude data directory
 pub fn list_projects(custom_path: Option<&str>) -> Result<Vec<ProjectData>, ReaderError> {
+    list_projects_with_options(custom_path, &ScanOptions::default())
+}
+
+/// List all projects in the Claude data directory using the given scan options
+pub fn list_projects_with_options(
+    custom_path: Option<&str>,
+    options: &ScanOptions,
+) -> Result<Vec<ProjectData>, ReaderError> {
+    if custom_path.is_none() && !options.data_dirs.is_empty() {
+        let roots: Vec<&str> = options.data_dirs.iter().map(String::as_str).collect();
+        return list_projects_multi(&roots, options);
+    }
+
+    if custom_path.is_none() {
+        let default_dirs = get_claude_data_dirs(None);
+        if default_dirs.len() > 1 {
+            let dirs: Vec<String> = default_dirs
+                .iter()
+                .map(|dir| dir.to_string_lossy().to_string())
+                .collect();
+            let roots: Vec<&str> = dirs.iter().map(String::as_str).collect();
+            return list_projects_multi(&roots, options);
+        }
+    }
+
     let projects_dir = get_projects_dir(custom_path);
 
     if !projects_dir.exists() {
@@ -46,57 +179,334 @@ pub fn list_projects(custom_path: Option<&str>) -> Result<Vec<ProjectData>, Read
     }
 
     let mut projects = Vec::new();
+    let globbed = options
+        .session_glob
+        .as_deref()
+        .and_then(|pattern| scan_projects_glob(&projects_dir, pattern));
 
-    // Read all subdirectories in the projects folder
-    for entry in fs::read_dir(&projects_dir)? {
-        let entry = entry?;
-        let path = entry.path();
+    if let Some(globbed) = globbed {
+        projects = globbed;
+    } else if options.recursive {
+        scan_projects_recursive(&projects_dir, 1, &mut projects)?;
+    } else {
+        // Read all subdirectories in the projects folder
+        for entry in fs::read_dir(&projects_dir)? {
+            let path = entry?.path();
+
+            if path.is_dir() {
+                if let Some(project) = read_project_dir(&path) {
+                    projects.push(project);
+                }
+            }
+        }
+    }
+
+    Ok(merge_duplicate_projects(projects, PATHS_CASE_INSENSITIVE))
+}
+
+/// Merge projects whose decoded paths name the same directory, such as `d--code-app`
+/// and `D--Code-App` on a case-insensitive filesystem. The first project's encoded
+/// path is kept; session files keep their own paths so they are still read from disk.
+fn merge_duplicate_projects(
+    projects: Vec<ProjectData>,
+    case_insensitive: bool,
+) -> Vec<ProjectData> {
+    let mut merged: Vec<(String, ProjectData)> = Vec::new();
+
+    for project in projects {
+        let key = project_group_key(&project.decoded_path, case_insensitive);
+        match merged.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, existing)) => merge_session_files(existing, project.session_files),
+            None => merged.push((key, project)),
+        }
+    }
+
+    merged.into_iter().map(|(_, project)| project).collect()
+}
+
+/// List projects across several Claude data directories, merging projects that
+/// decode to the same path. A session file present under more than one root is
+/// kept once (the largest copy) so its tokens are not counted twice.
+/// Missing roots are skipped; an error is returned only if none exist.
+pub fn list_projects_multi(
+    roots: &[&str],
+    options: &ScanOptions,
+) -> Result<Vec<ProjectData>, ReaderError> {
+    let single_root = ScanOptions {
+        data_dirs: Vec::new(),
+        ..options.clone()
+    };
+    let mut projects = Vec::new();
+    let mut found_root = false;
+
+    for root in roots {
+        let root_projects = match list_projects_with_options(Some(root), &single_root) {
+            Ok(projects) => projects,
+            Err(ReaderError::DirNotFound(dir)) => {
+                warn!("Skipping missing data directory: {}", dir);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        found_root = true;
+        projects.extend(root_projects);
+    }
+
+    if !found_root {
+        return Err(ReaderError::DirNotFound(roots.join(", ")));
+    }
+
+    Ok(merge_duplicate_projects(projects, PATHS_CASE_INSENSITIVE))
+}
+
+/// Add `files` to a project, replacing a same-named session file only with a larger copy
+fn merge_session_files(project: &mut ProjectData, files: Vec<PathBuf>) {
+    let file_len = |path: &Path| fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+
+    for file in files {
+        let existing = project
+            .session_files
+            .iter_mut()
+            .find(|f| f.file_name() == file.file_name());
+
+        match existing {
+            Some(existing) => {
+                if file_len(&file) > file_len(existing) {
+                    *existing = file;
+                }
+            }
+            None => project.session_files.push(file),
+        }
+    }
+}
+
+/// Walk `dir` looking for any directory containing session files
+fn scan_projects_recursive(
+    dir: &Path,
+    depth: usize,
+    projects: &mut Vec<ProjectData>,
+) -> Result<(), ReaderError> {
+    if depth > MAX_PROJECT_SCAN_DEPTH {
+        debug!("Max project scan depth reached at {:?}", dir);
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
 
         if path.is_dir() {
-            let encoded_path = path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("")
-                .to_string();
-
-            let decoded_path = decode_project_path(&encoded_path);
-            let display_name = get_display_name(&decoded_path);
-
-            // Find all JSONL files in this project directory
-            let pattern = path.join("*.jsonl");
-            let session_files: Vec<PathBuf> = glob(pattern.to_string_lossy().as_ref())
-                .map(|paths| paths.filter_map(Result::ok).collect())
-                .unwrap_or_default();
-
-            if !session_files.is_empty() {
-                projects.push(ProjectData {
-                    encoded_path,
-                    decoded_path,
-                    display_name,
-                    session_files,
-                });
+            if let Some(project) = read_project_dir(&path) {
+                projects.push(project);
+            }
+            scan_projects_recursive(&path, depth + 1, projects)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Find session files matching `pattern` below `projects_dir` and group them into projects
+/// by parent directory, or None if the pattern cannot be used so the default layout is read.
+/// Each project is named after its directory, skipping trailing directories the pattern
+/// names literally: `**/sessions/*.jsonl` names `D--code-app/sessions/a.jsonl` after
+/// `D--code-app`.
+fn scan_projects_glob(projects_dir: &Path, pattern: &str) -> Option<Vec<ProjectData>> {
+    let full_pattern = projects_dir.join(pattern);
+    let paths = match glob(full_pattern.to_string_lossy().as_ref()) {
+        Ok(paths) => paths,
+        Err(e) => {
+            warn!("Invalid session glob {:?}, using the default layout: {}", full_pattern, e);
+            return None;
+        }
+    };
+
+    let literal_dirs = literal_trailing_dirs(pattern);
+    let mut groups: Vec<(PathBuf, Vec<PathBuf>)> = Vec::new();
+    for file in paths.filter_map(Result::ok).filter(|path| path.is_file()) {
+        let Some(dir) = file.parent() else {
+            continue;
+        };
+        match groups.iter_mut().find(|(group_dir, _)| group_dir == dir) {
+            Some((_, files)) => files.push(file),
+            None => groups.push((dir.to_path_buf(), vec![file])),
+        }
+    }
+
+    let projects = groups
+        .into_iter()
+        .map(|(dir, files)| {
+            let named = named_ancestor(&dir, projects_dir, &literal_dirs);
+            project_data(dir_name(named), files)
+        })
+        .collect();
+
+    Some(projects)
+}
+
+/// Walk up from `dir` past each of `literal_dirs` it ends with, staying below `projects_dir`
+fn named_ancestor<'a>(dir: &'a Path, projects_dir: &Path, literal_dirs: &[String]) -> &'a Path {
+    let mut named = dir;
+    for literal in literal_dirs {
+        match named.parent() {
+            Some(parent) if parent.starts_with(projects_dir) && dir_name(named) == *literal => {
+                named = parent;
             }
+            _ => break,
         }
     }
+    named
+}
+
+/// Directory names at the end of `pattern`'s directory part that contain no wildcards,
+/// innermost first (`**/sessions/*.jsonl` gives `["sessions"]`)
+fn literal_trailing_dirs(pattern: &str) -> Vec<String> {
+    let mut dirs: Vec<&str> = pattern.split(['/', '\\']).collect();
+    dirs.pop();
+    dirs.into_iter()
+        .rev()
+        .take_while(|dir| !dir.is_empty() && !dir.contains(['*', '?', '[']))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Build project data for a directory, or None if it has no session files
+fn read_project_dir(path: &Path) -> Option<ProjectData> {
+    // Find all JSONL files (plain or gzip-compressed) in this project directory
+    let session_files: Vec<PathBuf> = SESSION_FILE_PATTERNS
+        .iter()
+        .flat_map(|pattern| {
+            let pattern = path.join(pattern);
+            glob(pattern.to_string_lossy().as_ref())
+                .map(|paths| paths.filter_map(Result::ok).collect::<Vec<_>>())
+                .unwrap_or_default()
+        })
+        .collect();
+
+    if session_files.is_empty() {
+        return None;
+    }
 
-    Ok(projects)
+    Some(project_data(dir_name(path), session_files))
+}
+
+fn dir_name(path: &Path) -> String {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+        .to_string()
+}
+
+/// Project named after its encoded directory name
+fn project_data(encoded_path: String, session_files: Vec<PathBuf>) -> ProjectData {
+    let decoded_path = decode_project_path(&encoded_path);
+    let display_name = get_display_name(&decoded_path);
+
+    ProjectData {
+        encoded_path,
+        decoded_path,
+        display_name,
+        session_files,
+    }
+}
+
+/// Whether a session file is gzip-compressed (and so cannot be read from an offset)
+pub fn is_compressed(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "gz")
+}
+
+/// Open a session file for line reading, decompressing `.gz` files transparently
+fn open_session_file(path: &Path) -> Result<Box<dyn BufRead>, ReaderError> {
+    let file = File::open(path)?;
+
+    if is_compressed(path) {
+        Ok(Box::new(BufReader::new(GzDecoder::new(file))))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// Run `op` on the bounded session parsing pool, or on rayon's global pool if the
+/// dedicated pool could not be created
+fn with_parse_pool<R: Send>(op: impl FnOnce() -> R + Send) -> R {
+    static POOL: OnceLock<Option<ThreadPool>> = OnceLock::new();
+
+    let pool = POOL.get_or_init(|| {
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(MAX_PARSE_THREADS);
+        ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|i| format!("jsonl-parse-{}", i))
+            .build()
+            .map_err(|e| warn!("Failed to build session parsing pool: {}", e))
+            .ok()
+    });
+
+    match pool {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
 }
 
-/// Read all usage entries from a JSONL file
+/// Read all usage entries from a JSONL file (optionally gzip-compressed)
 pub fn read_jsonl_file(
     path: &Path,
     pricing: &PricingCalculator,
 ) -> Result<Vec<UsageEntry>, ReaderError> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
+    read_jsonl_file_report(path, pricing).map(|report| report.entries)
+}
+
+/// Read all usage entries from a JSONL file, also counting the lines that were skipped
+/// because they could not be read or parsed
+pub fn read_jsonl_file_report(
+    path: &Path,
+    pricing: &PricingCalculator,
+) -> Result<FileReadReport, ReaderError> {
     // Use HashMap to deduplicate by message.id, keeping the last entry
     let mut entries_by_id: HashMap<String, UsageEntry> = HashMap::new();
 
+    let lines = for_each_event(path, pricing, |line_num, event, entry| {
+        // Get unique key - only deduplicate if BOTH message_id and request_id present
+        // Python: return f"{message_id}:{request_id}" if message_id and request_id else None
+        // Entries without both IDs are NOT deduplicated (all included)
+        if let Some(key) = get_dedup_key(event) {
+            // Has valid dedup key - use HashMap to keep last entry
+            entries_by_id.insert(key, entry);
+        } else {
+            // No dedup key - include entry directly (matches Python behavior)
+            // Use a unique key to prevent any deduplication
+            let unique_key = format!("no_dedup_{}_{}", line_num, entry.timestamp);
+            entries_by_id.insert(unique_key, entry);
+        }
+    })?;
+
+    Ok(FileReadReport {
+        entries: entries_by_id.into_values().collect(),
+        parse_errors: (lines.total_lines - lines.parsed_lines) as u32,
+        lines,
+    })
+}
+
+/// Call `f` with the line number, event and usage entry of every usage line in `path`.
+/// Unreadable and malformed lines are logged and skipped; returns the line counts.
+fn for_each_event<F>(
+    path: &Path,
+    pricing: &PricingCalculator,
+    mut f: F,
+) -> Result<SessionLineCounts, ReaderError>
+where
+    F: FnMut(usize, &SessionEvent, UsageEntry),
+{
+    let reader = open_session_file(path)?;
+    let mut counts = SessionLineCounts::default();
+
     for (line_num, line_result) in reader.lines().enumerate() {
         let line = match line_result {
             Ok(l) => l,
             Err(e) => {
                 debug!("Failed to read line {} in {:?}: {}", line_num, path, e);
+                counts.total_lines += 1;
                 continue;
             }
         };
@@ -105,22 +515,20 @@ pub fn read_jsonl_file(
         if line.is_empty() {
             continue;
         }
+        counts.total_lines += 1;
 
         match serde_json::from_str::<SessionEvent>(line) {
             Ok(event) => {
+                counts.parsed_lines += 1;
+                if let Some((usage, _)) = extract_tokens_and_model(&event) {
+                    counts.usage_lines += 1;
+                    counts.raw_tokens += usage.input_tokens.unwrap_or(0)
+                        + usage.output_tokens.unwrap_or(0)
+                        + usage.cache_creation_tokens.unwrap_or(0)
+                        + usage.cache_read_tokens.unwrap_or(0);
+                }
                 if let Some(entry) = process_event(&event, pricing) {
-                    // Get unique key - only deduplicate if BOTH message_id and request_id present
-                    // Python: return f"{message_id}:{request_id}" if message_id and request_id else None
-                    // Entries without both IDs are NOT deduplicated (all included)
-                    if let Some(key) = get_dedup_key(&event) {
-                        // Has valid dedup key - use HashMap to keep last entry
-                        entries_by_id.insert(key, entry);
-                    } else {
-                        // No dedup key - include entry directly (matches Python behavior)
-                        // Use a unique key to prevent any deduplication
-                        let unique_key = format!("no_dedup_{}_{}", line_num, entry.timestamp);
-                        entries_by_id.insert(unique_key, entry);
-                    }
+                    f(line_num, &event, entry);
                 }
             }
             Err(e) => {
@@ -132,7 +540,7 @@ pub fn read_jsonl_file(
         }
     }
 
-    Ok(entries_by_id.into_values().collect())
+    Ok(counts)
 }
 
 /// Process a session event into a usage entry
@@ -146,16 +554,15 @@ fn process_event(
     // Extract tokens based on event type priority
     let (tokens, model) = extract_tokens_and_model(event)?;
 
-    // Calculate cost
-    let cost_usd = event.cost.unwrap_or_else(|| {
-        pricing.calculate_cost(
-            &model,
-            tokens.input_tokens.unwrap_or(0),
-            tokens.output_tokens.unwrap_or(0),
-            tokens.cache_creation_tokens.unwrap_or(0),
-            tokens.cache_read_tokens.unwrap_or(0),
-        )
-    });
+    // Calculate cost: audited overrides win, then embedded cost, then pricing
+    // (with cache write lifetimes and the Batch API discount applied)
+    let audited_cost = event
+        .request_id
+        .as_deref()
+        .and_then(|request_id| pricing.cost_override(request_id));
+    let cost_usd = audited_cost
+        .or(event.cost)
+        .unwrap_or_else(|| pricing.calculate_usage_cost(&model, &tokens));
 
     let message_id = event
         .message_id
@@ -163,13 +570,16 @@ fn process_event(
         .or_else(|| event.message.as_ref()?.id.clone())
         .unwrap_or_default();
 
-    let request_id = event.request_id.clone().unwrap_or_else(|| "unknown".to_string());
+    let request_id = event.request_id.clone();
 
     Some(UsageEntry {
         timestamp,
         input_tokens: tokens.input_tokens.unwrap_or(0),
         output_tokens: tokens.output_tokens.unwrap_or(0),
-        cache_creation_tokens: tokens.cache_creation_tokens.unwrap_or(0),
+        cache_creation_tokens: tokens
+            .cache_creation_tokens
+            .or_else(|| tokens.cache_creation.as_ref().map(|c| c.total()))
+            .unwrap_or(0),
         cache_read_tokens: tokens.cache_read_tokens.unwrap_or(0),
         cost_usd,
         model,
@@ -178,6 +588,71 @@ fn process_event(
     })
 }
 
+/// Read the timestamp of every event in a JSONL file, in line order.
+/// Lines that fail to parse or carry no valid timestamp are skipped.
+pub fn read_line_timestamps(path: &Path) -> Result<Vec<DateTime<Utc>>, ReaderError> {
+    let reader = open_session_file(path)?;
+
+    let timestamps = reader
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<SessionEvent>(line.trim()).ok())
+        .filter_map(|event| parse_timestamp(event.timestamp.as_deref()?))
+        .collect();
+
+    Ok(timestamps)
+}
+
+/// Parse the lines of a plain (uncompressed) session file starting at byte `offset`.
+/// A final line without a newline is only consumed if it parses, since it may still be
+/// being written; otherwise it is read again on the next call.
+pub fn read_jsonl_file_from(
+    path: &Path,
+    offset: u64,
+    pricing: &PricingCalculator,
+) -> Result<AppendedEntries, ReaderError> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut reader = BufReader::new(file);
+    let mut appended = AppendedEntries {
+        entries: Vec::new(),
+        end_offset: offset,
+    };
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        let read = reader.read_until(b'\n', &mut buf)?;
+        if read == 0 {
+            break;
+        }
+
+        let complete = buf.ends_with(b"\n");
+        let line = String::from_utf8_lossy(&buf);
+        let line = line.trim();
+        let event = serde_json::from_str::<SessionEvent>(line);
+        if !complete && event.is_err() {
+            break;
+        }
+        let line_start = appended.end_offset;
+        appended.end_offset += read as u64;
+
+        match event {
+            Ok(event) => {
+                if let Some(entry) = process_event(&event, pricing) {
+                    appended.entries.push((get_dedup_key(&event), entry));
+                }
+            }
+            Err(e) if !line.is_empty() => {
+                debug!("Failed to parse JSON at byte {} in {:?}: {}", line_start, path, e);
+            }
+            Err(_) => {}
+        }
+    }
+
+    Ok(appended)
+}
+
 /// Extract tokens and model from event based on type priority
 fn extract_tokens_and_model(event: &SessionEvent) -> Option<(Usage, String)> {
     let is_assistant = event.event_type.as_deref() == Some("assistant");
@@ -222,11 +697,7 @@ fn extract_model(event: &SessionEvent) -> String {
 /// Parse ISO timestamp to DateTime<Utc>
 fn parse_timestamp(ts: &str) -> Option<DateTime<Utc>> {
     // Handle 'Z' suffix
-    let ts = if ts.ends_with('Z') {
-        &ts[..ts.len() - 1]
-    } else {
-        ts
-    };
+    let ts = ts.strip_suffix('Z').unwrap_or(ts);
 
     // Try parsing with various formats
     DateTime::parse_from_rfc3339(&format!("{}+00:00", ts))
@@ -275,26 +746,28 @@ pub fn load_project_entries(
     project: &ProjectData,
     pricing: &PricingCalculator,
 ) -> Vec<UsageEntry> {
+    // Parse files in parallel; results keep session file order so the merge is deterministic
+    let results: Vec<_> = with_parse_pool(|| {
+        project
+            .session_files
+            .par_iter()
+            .map(|session_file| (session_file, read_jsonl_file(session_file, pricing)))
+            .collect()
+    });
+
     // Use HashMap to deduplicate across all session files
     let mut entries_by_key: HashMap<String, UsageEntry> = HashMap::new();
     let mut entry_counter: usize = 0;
 
-    for session_file in &project.session_files {
-        match read_jsonl_file(session_file, pricing) {
+    for (session_file, result) in results {
+        match result {
             Ok(entries) => {
                 for entry in entries {
-                    // Python only deduplicates when BOTH message_id and request_id are present
-                    // Python: return f"{message_id}:{request_id}" if message_id and request_id else None
-                    let has_message_id = !entry.message_id.is_empty();
-                    let has_request_id = !entry.request_id.is_empty() && entry.request_id != "unknown";
-
-                    let key = if has_message_id && has_request_id {
-                        format!("{}:{}", entry.message_id, entry.request_id)
-                    } else {
+                    let key = entry_dedup_key(&entry).unwrap_or_else(|| {
                         // No deduplication - use unique key
                         entry_counter += 1;
                         format!("no_dedup_{}_{}", entry_counter, entry.timestamp)
-                    };
+                    });
 
                     // Keep the later entry (last one has final token counts)
                     entries_by_key.insert(key, entry);
@@ -308,25 +781,668 @@ pub fn load_project_entries(
 
     // Convert to vector and sort by timestamp
     let mut entries: Vec<_> = entries_by_key.into_values().collect();
-    entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    entries.sort_by_key(|e| e.timestamp);
 
     entries
 }
 
+/// A session file prepared for streaming: small files are read into memory, large
+/// files only have their dedup keys counted
+enum StreamedFile {
+    Buffered(Vec<UsageEntry>),
+    /// Occurrences of each dedup key, and the last line read so lines appended later
+    /// are left for the next load
+    Counted {
+        keys: HashMap<String, u32>,
+        last_line: Option<usize>,
+    },
+    Unreadable,
+}
+
+/// Stream the deduplicated usage entries of a project's session files to `on_entry`,
+/// for aggregating without collecting every entry. Duplicates keep the last occurrence
+/// as in `load_project_entries`; unreadable files are logged and skipped.
+pub fn for_each_project_entry<F>(project: &ProjectData, pricing: &PricingCalculator, on_entry: F)
+where
+    F: FnMut(UsageEntry),
+{
+    stream_session_files(&project.session_files, pricing, STREAM_FILE_BYTES, on_entry);
+}
+
+/// Count how often each dedup key occurs across `files`, then emit every entry at the
+/// last occurrence of its key. Files of at least `stream_bytes` are read twice rather
+/// than buffered, so only their keys are held. Returns the number of keys tracked.
+fn stream_session_files<F>(
+    files: &[PathBuf],
+    pricing: &PricingCalculator,
+    stream_bytes: u64,
+    mut on_entry: F,
+) -> usize
+where
+    F: FnMut(UsageEntry),
+{
+    let prepared: Vec<StreamedFile> = with_parse_pool(|| {
+        files
+            .par_iter()
+            .map(|path| prepare_streamed_file(path, pricing, stream_bytes))
+            .collect()
+    });
+
+    let mut remaining: HashMap<String, u32> = HashMap::new();
+    for file in &prepared {
+        match file {
+            StreamedFile::Buffered(entries) => {
+                for key in entries.iter().filter_map(entry_dedup_key) {
+                    *remaining.entry(key).or_default() += 1;
+                }
+            }
+            StreamedFile::Counted { keys, .. } => {
+                for (key, count) in keys {
+                    *remaining.entry(key.clone()).or_default() += count;
+                }
+            }
+            StreamedFile::Unreadable => {}
+        }
+    }
+    let tracked = remaining.len();
+
+    // Emit an entry once no later occurrence of its key is left
+    let mut is_last = |entry: &UsageEntry| match entry_dedup_key(entry) {
+        Some(key) => match remaining.get_mut(&key) {
+            Some(count) => {
+                *count -= 1;
+                *count == 0
+            }
+            None => false,
+        },
+        None => true,
+    };
+
+    for (path, file) in files.iter().zip(prepared) {
+        match file {
+            StreamedFile::Buffered(entries) => {
+                for entry in entries {
+                    if is_last(&entry) {
+                        on_entry(entry);
+                    }
+                }
+            }
+            StreamedFile::Counted {
+                last_line: Some(last_line),
+                ..
+            } => {
+                let result = for_each_event(path, pricing, |line_num, _, entry| {
+                    if line_num <= last_line && is_last(&entry) {
+                        on_entry(entry);
+                    }
+                });
+                if let Err(e) = result {
+                    warn!("Failed to read session file {:?}: {}", path, e);
+                }
+            }
+            StreamedFile::Counted { .. } | StreamedFile::Unreadable => {}
+        }
+    }
+
+    tracked
+}
+
+/// Buffer a small session file's entries, or count the dedup keys of a large one
+fn prepare_streamed_file(
+    path: &Path,
+    pricing: &PricingCalculator,
+    stream_bytes: u64,
+) -> StreamedFile {
+    let len = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    let result = if len < stream_bytes {
+        read_jsonl_file(path, pricing).map(StreamedFile::Buffered)
+    } else {
+        let mut keys: HashMap<String, u32> = HashMap::new();
+        let mut last_line = None;
+        for_each_event(path, pricing, |line_num, _, entry| {
+            last_line = Some(line_num);
+            if let Some(key) = entry_dedup_key(&entry) {
+                *keys.entry(key).or_default() += 1;
+            }
+        })
+        .map(|_| StreamedFile::Counted { keys, last_line })
+    };
+
+    result.unwrap_or_else(|e| {
+        warn!("Failed to read session file {:?}: {}", path, e);
+        StreamedFile::Unreadable
+    })
+}
+
+/// Dedup key of a parsed entry, `message_id:request_id`
+/// Python only deduplicates when BOTH message_id and request_id are present
+/// Python: return f"{message_id}:{request_id}" if message_id and request_id else None
+fn entry_dedup_key(entry: &UsageEntry) -> Option<String> {
+    let request_id = entry.request_id.as_deref().filter(|r| !r.is_empty())?;
+    if entry.message_id.is_empty() {
+        return None;
+    }
+    Some(format!("{}:{}", entry.message_id, request_id))
+}
+
+/// Drop entries already seen in an earlier project, so content found under several
+/// project directories (symlinks, moved projects) is counted once, for the first project
+pub fn dedup_across_projects(all_data: &mut [(ProjectData, Vec<UsageEntry>)]) {
+    let mut seen = HashSet::new();
+    for (_, entries) in all_data.iter_mut() {
+        entries.retain(|entry| !is_duplicate_entry(&mut seen, entry));
+    }
+}
+
+/// Drop entries whose dedup key appeared earlier in `entries`
+pub fn dedup_entries(entries: &mut Vec<UsageEntry>) {
+    let mut seen = HashSet::new();
+    entries.retain(|entry| !is_duplicate_entry(&mut seen, entry));
+}
+
+/// Whether `entry` was already seen by this tracker, recording it if not;
+/// entries without a dedup key are never treated as seen
+pub fn is_duplicate_entry(seen: &mut HashSet<String>, entry: &UsageEntry) -> bool {
+    entry_dedup_key(entry).is_some_and(|key| !seen.insert(key))
+}
+
 /// Load all usage entries from all projects
 pub fn load_all_entries(
     custom_path: Option<&str>,
     pricing: &PricingCalculator,
 ) -> Result<Vec<(ProjectData, Vec<UsageEntry>)>, ReaderError> {
-    let projects = list_projects(custom_path)?;
+    load_all_entries_with_options(custom_path, &ScanOptions::default(), pricing)
+}
 
-    let results: Vec<_> = projects
-        .into_iter()
-        .map(|project| {
-            let entries = load_project_entries(&project, pricing);
-            (project, entries)
-        })
-        .collect();
+/// Load all usage entries from all projects discovered with the given scan options
+pub fn load_all_entries_with_options(
+    custom_path: Option<&str>,
+    options: &ScanOptions,
+    pricing: &PricingCalculator,
+) -> Result<Vec<(ProjectData, Vec<UsageEntry>)>, ReaderError> {
+    let projects = list_projects_with_options(custom_path, options)?;
+
+    let mut results: Vec<_> = with_parse_pool(|| {
+        projects
+            .into_par_iter()
+            .map(|project| {
+                let entries = load_project_entries(&project, pricing);
+                (project, entries)
+            })
+            .collect()
+    });
+    dedup_across_projects(&mut results);
 
     Ok(results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an empty project directory under the system temp dir
+    fn temp_project_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ccm-reader-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn assistant_line(message_id: &str, request_id: Option<&str>, timestamp: &str, output_tokens: u64) -> String {
+        let mut event = serde_json::json!({
+            "type": "assistant",
+            "timestamp": timestamp,
+            "message": {
+                "id": message_id,
+                "model": "claude-sonnet-4-20250514",
+                "usage": {"input_tokens": 10, "output_tokens": output_tokens}
+            }
+        });
+        if let Some(request_id) = request_id {
+            event["requestId"] = serde_json::json!(request_id);
+        }
+        event.to_string()
+    }
+
+    fn project_with_files(dir: &Path, files: &[(&str, Vec<String>)]) -> ProjectData {
+        let session_files = files
+            .iter()
+            .map(|(name, lines)| {
+                let path = dir.join(name);
+                fs::write(&path, lines.join("\n")).unwrap();
+                path
+            })
+            .collect();
+
+        ProjectData {
+            encoded_path: "test-project".to_string(),
+            decoded_path: "test\\project".to_string(),
+            display_name: "project".to_string(),
+            session_files,
+        }
+    }
+
+    #[test]
+    fn test_case_variant_project_dirs_merge_only_when_case_insensitive() {
+        let root = temp_project_dir("case-variants");
+        let line = assistant_line("msg_1", Some("req_1"), "2025-01-01T10:00:00Z", 5);
+        for (dir, file) in [("d--code-ccm", "a.jsonl"), ("D--Code-CCM", "b.jsonl")] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(root.join(dir).join(file), &line).unwrap();
+        }
+        let read_dirs = || -> Vec<ProjectData> {
+            ["d--code-ccm", "D--Code-CCM"]
+                .iter()
+                .filter_map(|dir| read_project_dir(&root.join(dir)))
+                .collect()
+        };
+
+        let merged = merge_duplicate_projects(read_dirs(), true);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].encoded_path, "d--code-ccm");
+        assert_eq!(merged[0].session_files.len(), 2);
+        assert!(merged[0].session_files[1].starts_with(root.join("D--Code-CCM")));
+
+        let separate = merge_duplicate_projects(read_dirs(), false);
+        assert_eq!(separate.len(), 2);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_recursive_scan_finds_nested_project() {
+        let root = temp_project_dir("recursive-scan");
+        let nested = root.join("projects").join("team").join("archive").join("D--code-nested");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(
+            nested.join("session.jsonl"),
+            assistant_line("msg_1", Some("req_1"), "2025-01-01T10:00:00Z", 5),
+        )
+        .unwrap();
+        let custom_path = root.to_str();
+
+        let flat = list_projects_with_options(custom_path, &ScanOptions::new()).unwrap();
+        assert!(flat.is_empty());
+
+        let recursive =
+            list_projects_with_options(custom_path, &ScanOptions::new().with_recursive(true)).unwrap();
+        assert_eq!(recursive.len(), 1);
+        assert_eq!(recursive[0].encoded_path, "D--code-nested");
+        assert_eq!(recursive[0].session_files.len(), 1);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_session_glob_groups_by_parent_path() {
+        let root = temp_project_dir("session-glob");
+        let line = assistant_line("msg_1", Some("req_1"), "2025-01-01T10:00:00Z", 5);
+        let app = root.join("projects").join("export").join("D--code-app").join("sessions");
+        let api = root.join("projects").join("D--code-api").join("sessions");
+        fs::create_dir_all(&app).unwrap();
+        fs::create_dir_all(&api).unwrap();
+        fs::write(app.join("a.jsonl"), &line).unwrap();
+        fs::write(app.join("b.jsonl"), &line).unwrap();
+        fs::write(api.join("c.jsonl"), &line).unwrap();
+        fs::write(api.join("notes.txt"), "").unwrap();
+        let custom_path = root.to_str();
+
+        let options = ScanOptions::new().with_session_glob(Some("**/sessions/*.jsonl".into()));
+        let mut projects = list_projects_with_options(custom_path, &options).unwrap();
+        projects.sort_by(|a, b| a.encoded_path.cmp(&b.encoded_path));
+        let names: Vec<_> = projects
+            .iter()
+            .map(|p| (p.encoded_path.as_str(), p.display_name.as_str(), p.session_files.len()))
+            .collect();
+        assert_eq!(names, vec![("D--code-api", "api", 1), ("D--code-app", "app", 2)]);
+
+        let options = ScanOptions::new().with_session_glob(Some("*/*/*.jsonl".into()));
+        let projects = list_projects_with_options(custom_path, &options).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].session_files, vec![api.join("c.jsonl")]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_read_report_counts_malformed_lines() {
+        let root = temp_project_dir("parse-errors");
+        let path = root.join("session.jsonl");
+        let content = [
+            assistant_line("msg_1", Some("req_1"), "2025-01-01T10:00:00Z", 5),
+            "{not json".to_string(),
+            String::new(),
+            "{\"type\": \"assistant\", \"timestamp\":".to_string(),
+            assistant_line("msg_2", None, "2025-01-01T11:00:00Z", 7),
+        ];
+        // An invalid UTF-8 line does not stop the lines after it from being read
+        let head = content[..4].join("\n");
+        fs::write(&path, [head.as_bytes(), &b"\n\xff\n"[..], content[4].as_bytes()].concat())
+            .unwrap();
+
+        let report = read_jsonl_file_report(&path, &PricingCalculator::new()).unwrap();
+        assert_eq!(report.entries.len(), 2);
+        // Blank lines are not failures
+        assert_eq!(report.parse_errors, 3);
+        assert_eq!(report.lines.total_lines, 5);
+        assert_eq!(report.lines.parsed_lines, 2);
+        assert_eq!(report.lines.usage_lines, 2);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_gzip_session_files_read_like_plaintext() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let root = temp_project_dir("gzip-sessions");
+        let project = root.join("projects").join("-work-app");
+        fs::create_dir_all(&project).unwrap();
+        let content = [
+            assistant_line("msg_1", Some("req_1"), "2025-01-01T10:00:00Z", 5),
+            "{not json".to_string(),
+            assistant_line("msg_1", Some("req_1"), "2025-01-01T10:00:01Z", 50),
+            assistant_line("msg_2", None, "2025-01-01T11:00:00Z", 7),
+        ]
+        .join("\n");
+
+        let plain = root.join("plain.jsonl");
+        fs::write(&plain, &content).unwrap();
+        let compressed = project.join("rotated.jsonl.gz");
+        let file = File::create(&compressed).unwrap();
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        encoder.write_all(content.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let projects = list_projects(root.to_str()).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].session_files, vec![compressed.clone()]);
+
+        let pricing = PricingCalculator::new();
+        let mut from_gzip = read_jsonl_file(&compressed, &pricing).unwrap();
+        let mut from_plain = read_jsonl_file(&plain, &pricing).unwrap();
+        from_gzip.sort_by_key(|e| e.timestamp);
+        from_plain.sort_by_key(|e| e.timestamp);
+        let tokens = |entries: &[UsageEntry]| -> Vec<u64> {
+            entries.iter().map(|e| e.output_tokens).collect()
+        };
+        assert_eq!(tokens(&from_gzip), vec![50, 7]);
+        assert_eq!(tokens(&from_gzip), tokens(&from_plain));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_multi_root_merges_projects_without_double_counting() {
+        let root_a = temp_project_dir("multi-root-a");
+        let root_b = temp_project_dir("multi-root-b");
+        let missing = root_a.join("missing");
+        // Entries without a request id are not deduplicated by message key
+        let shared = assistant_line("msg_1", None, "2025-01-01T10:00:00Z", 5);
+        for root in [&root_a, &root_b] {
+            let project = root.join("projects").join("-work-app");
+            fs::create_dir_all(&project).unwrap();
+            fs::write(project.join("shared.jsonl"), &shared).unwrap();
+        }
+        fs::write(
+            root_b.join("projects").join("-work-app").join("other.jsonl"),
+            assistant_line("msg_2", None, "2025-01-01T11:00:00Z", 7),
+        )
+        .unwrap();
+
+        let roots = [
+            root_a.to_str().unwrap(),
+            root_b.to_str().unwrap(),
+            missing.to_str().unwrap(),
+        ];
+        let projects = list_projects_multi(&roots, &ScanOptions::new()).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].decoded_path, "/work/app");
+        assert_eq!(projects[0].session_files.len(), 2);
+
+        let entries = load_project_entries(&projects[0], &PricingCalculator::new());
+        let output_tokens: u64 = entries.iter().map(|e| e.output_tokens).sum();
+        assert_eq!(output_tokens, 12);
+
+        // The data dirs are only used when no custom path is given
+        let data_dirs = roots.iter().map(|r| r.to_string()).collect();
+        let options = ScanOptions::new().with_data_dirs(data_dirs);
+        assert_eq!(list_projects_with_options(None, &options).unwrap().len(), 1);
+        assert!(list_projects_multi(&[missing.to_str().unwrap()], &options).is_err());
+
+        fs::remove_dir_all(&root_a).unwrap();
+        fs::remove_dir_all(&root_b).unwrap();
+    }
+
+    #[test]
+    fn test_parallel_load_keeps_last_file_and_skips_unreadable() {
+        let dir = temp_project_dir("parallel-load");
+        let files: Vec<(String, Vec<String>)> = (0..32)
+            .map(|i| {
+                let timestamp = format!("2025-01-01T10:{:02}:00Z", i);
+                let line = assistant_line("msg_shared", Some("req_shared"), &timestamp, i);
+                (format!("{:02}.jsonl", i), vec![line])
+            })
+            .collect();
+        let files: Vec<(&str, Vec<String>)> =
+            files.iter().map(|(name, lines)| (name.as_str(), lines.clone())).collect();
+        let mut project = project_with_files(&dir, &files);
+        project.session_files.push(dir.join("missing.jsonl"));
+
+        let entries = load_project_entries(&project, &PricingCalculator::new());
+        // The duplicate from the last session file wins, as with serial reading
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].output_tokens, 31);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_streaming_large_file_tracks_only_keys() {
+        use std::io::Write;
+
+        const LINES: u64 = 500_000;
+        const UNIQUE_KEYS: u64 = 1_000;
+
+        let dir = temp_project_dir("streaming-large");
+        let path = dir.join("large.jsonl");
+        let mut writer = std::io::BufWriter::new(File::create(&path).unwrap());
+        for i in 0..LINES {
+            let key = i % UNIQUE_KEYS;
+            writeln!(
+                writer,
+                concat!(
+                    r#"{{"type":"assistant","timestamp":"2025-01-01T10:00:00Z","requestId":"r{}","#,
+                    r#""message":{{"id":"m{}","model":"claude-sonnet-4","#,
+                    r#""usage":{{"input_tokens":1,"output_tokens":{}}}}}}}"#
+                ),
+                key, key, i
+            )
+            .unwrap();
+        }
+        writer.flush().unwrap();
+        drop(writer);
+
+        let mut emitted = 0u64;
+        let mut output_tokens = 0u64;
+        let tracked = stream_session_files(&[path], &PricingCalculator::new(), 0, |entry| {
+            emitted += 1;
+            output_tokens += entry.output_tokens;
+        });
+
+        // Memory is bounded by distinct keys, not lines; the last duplicate of each key wins
+        assert_eq!(tracked as u64, UNIQUE_KEYS);
+        assert_eq!(emitted, UNIQUE_KEYS);
+        assert_eq!(output_tokens, (LINES - UNIQUE_KEYS..LINES).sum::<u64>());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_streaming_keeps_last_duplicate_across_files() {
+        let dir = temp_project_dir("streaming-files");
+        let project = project_with_files(&dir, &[
+            ("a.jsonl", vec![
+                assistant_line("msg_1", Some("req_1"), "2025-01-01T10:00:00Z", 5),
+                assistant_line("msg_2", None, "2025-01-01T10:01:00Z", 7),
+            ]),
+            ("b.jsonl", vec![
+                assistant_line("msg_1", Some("req_1"), "2025-01-01T10:02:00Z", 50),
+                assistant_line("msg_3", Some("req_3"), "2025-01-01T10:03:00Z", 9),
+            ]),
+        ]);
+        let pricing = PricingCalculator::new();
+        let mut expected: Vec<u64> = load_project_entries(&project, &pricing)
+            .iter()
+            .map(|entry| entry.output_tokens)
+            .collect();
+        expected.sort_unstable();
+
+        // Buffered and streamed files give the same entries
+        for stream_bytes in [STREAM_FILE_BYTES, 0] {
+            let mut streamed = Vec::new();
+            stream_session_files(&project.session_files, &pricing, stream_bytes, |entry| {
+                streamed.push(entry.output_tokens);
+            });
+            streamed.sort_unstable();
+            assert_eq!(streamed, expected);
+        }
+        assert_eq!(expected, vec![7, 9, 50]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_literal_unknown_request_id_is_deduplicated() {
+        let dir = temp_project_dir("unknown-request-id");
+        let project = project_with_files(
+            &dir,
+            &[
+                ("a.jsonl", vec![assistant_line("msg_1", Some("unknown"), "2025-01-01T10:00:00Z", 5)]),
+                ("b.jsonl", vec![assistant_line("msg_1", Some("unknown"), "2025-01-01T10:00:01Z", 50)]),
+            ],
+        );
+
+        let entries = load_project_entries(&project, &PricingCalculator::new());
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].request_id.as_deref(), Some("unknown"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_string_cost_is_parsed_not_recomputed() {
+        let dir = temp_project_dir("string-cost");
+        let line = assistant_line("msg_1", Some("req_1"), "2025-01-01T10:00:00Z", 5);
+        let mut event: serde_json::Value = serde_json::from_str(&line).unwrap();
+        event["costUSD"] = serde_json::json!("0.0123");
+        let project = project_with_files(&dir, &[("a.jsonl", vec![event.to_string()])]);
+
+        let entries = load_project_entries(&project, &PricingCalculator::new());
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].cost_usd, 0.0123);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_batch_service_tier_is_discounted() {
+        let event = |service_tier: Option<&str>| {
+            let mut event = serde_json::json!({
+                "type": "assistant",
+                "timestamp": "2025-01-01T10:00:00Z",
+                "message": {
+                    "id": "msg_1",
+                    "model": "claude-3-5-sonnet-20241022",
+                    "usage": {"input_tokens": 1_000_000, "output_tokens": 1_000_000}
+                }
+            });
+            if let Some(tier) = service_tier {
+                event["message"]["usage"]["service_tier"] = serde_json::json!(tier);
+            }
+            serde_json::from_value::<SessionEvent>(event).unwrap()
+        };
+        let pricing = PricingCalculator::new();
+
+        let batch = process_event(&event(Some("batch")), &pricing).unwrap();
+        assert!((batch.cost_usd - 9.0).abs() < 1e-9);
+        let standard = process_event(&event(Some("standard")), &pricing).unwrap();
+        assert!((standard.cost_usd - 18.0).abs() < 1e-9);
+        let unmarked = process_event(&event(None), &pricing).unwrap();
+        assert!((unmarked.cost_usd - 18.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cost_override_replaces_matched_entries() {
+        let dir = temp_project_dir("cost-override");
+        let project = project_with_files(
+            &dir,
+            &[(
+                "a.jsonl",
+                vec![
+                    assistant_line("msg_1", Some("req_audited"), "2025-01-01T10:00:00Z", 1000),
+                    assistant_line("msg_2", Some("req_estimated"), "2025-01-01T10:00:01Z", 1000),
+                ],
+            )],
+        );
+        let overrides = [("req_audited".to_string(), 0.5)].into_iter().collect();
+        let pricing = PricingCalculator::new().with_cost_overrides(overrides);
+
+        let entries = load_project_entries(&project, &pricing);
+        let cost = |request_id: &str| {
+            entries.iter().find(|e| e.request_id.as_deref() == Some(request_id)).unwrap().cost_usd
+        };
+        assert_eq!(cost("req_audited"), 0.5);
+        let estimated = PricingCalculator::new().calculate_cost("claude-sonnet-4-20250514", 10, 1000, 0, 0);
+        assert_eq!(cost("req_estimated"), estimated);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_request_id_is_not_deduplicated() {
+        let dir = temp_project_dir("missing-request-id");
+        let project = project_with_files(
+            &dir,
+            &[
+                ("a.jsonl", vec![assistant_line("msg_1", None, "2025-01-01T10:00:00Z", 5)]),
+                ("b.jsonl", vec![assistant_line("msg_1", None, "2025-01-01T10:00:01Z", 50)]),
+            ],
+        );
+
+        let entries = load_project_entries(&project, &PricingCalculator::new());
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.request_id.is_none()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_entry_in_two_projects_is_counted_once() {
+        let root = temp_project_dir("cross-project");
+        let shared = assistant_line("msg_1", Some("req_1"), "2025-01-01T10:00:00Z", 5);
+        for dir in ["-work-app", "-work-app-worktree"] {
+            let project = root.join("projects").join(dir);
+            fs::create_dir_all(&project).unwrap();
+            fs::write(project.join("session.jsonl"), &shared).unwrap();
+        }
+        fs::write(
+            root.join("projects").join("-work-app-worktree").join("other.jsonl"),
+            assistant_line("msg_2", Some("req_2"), "2025-01-01T11:00:00Z", 7),
+        )
+        .unwrap();
+
+        let all_data = load_all_entries(root.to_str(), &PricingCalculator::new()).unwrap();
+        assert_eq!(all_data.len(), 2);
+        let ids: Vec<&str> = all_data
+            .iter()
+            .flat_map(|(_, entries)| entries.iter().map(|e| e.message_id.as_str()))
+            .collect();
+        assert_eq!(ids.iter().filter(|id| **id == "msg_1").count(), 1);
+        assert!(ids.contains(&"msg_2"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,181 @@
+//! Named snapshots of overall statistics for before/after comparison
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::usage::models::{MetricDelta, OverallStats, SnapshotComparison, StatsSnapshot};
+
+/// Error type for snapshot operations
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON parse error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid snapshot name: {0}")]
+    InvalidName(String),
+    #[error("Snapshot not found: {0}")]
+    NotFound(String),
+    #[error("App data directory is unavailable")]
+    NoDataDir,
+}
+
+/// Get the directory where snapshots are stored
+pub fn get_snapshots_dir() -> Result<PathBuf, SnapshotError> {
+    dirs::data_dir()
+        .map(|dir| dir.join("claude-code-usage-tracker").join("snapshots"))
+        .ok_or(SnapshotError::NoDataDir)
+}
+
+/// Snapshot names map directly to file names, so only allow a safe character set
+fn snapshot_path(dir: &Path, name: &str) -> Result<PathBuf, SnapshotError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(SnapshotError::InvalidName(name.to_string()));
+    }
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+/// Save `stats` as a named snapshot in `dir`, replacing any snapshot with the same name
+pub fn save_snapshot(
+    dir: &Path,
+    name: &str,
+    stats: &OverallStats,
+    now: &DateTime<Utc>,
+) -> Result<StatsSnapshot, SnapshotError> {
+    let path = snapshot_path(dir, name)?;
+    let snapshot = StatsSnapshot {
+        name: name.to_string(),
+        created_at: now.to_rfc3339(),
+        stats: stats.clone(),
+    };
+
+    fs::create_dir_all(dir)?;
+    fs::write(path, serde_json::to_string_pretty(&snapshot)?)?;
+    Ok(snapshot)
+}
+
+/// Load a named snapshot from `dir`
+pub fn load_snapshot(dir: &Path, name: &str) -> Result<StatsSnapshot, SnapshotError> {
+    let path = snapshot_path(dir, name)?;
+    if !path.exists() {
+        return Err(SnapshotError::NotFound(name.to_string()));
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Diff a saved snapshot against current stats
+pub fn compare_snapshot(snapshot: &StatsSnapshot, current: &OverallStats) -> SnapshotComparison {
+    SnapshotComparison {
+        name: snapshot.name.clone(),
+        created_at: snapshot.created_at.clone(),
+        deltas: calculate_metric_deltas(&snapshot.stats, current),
+    }
+}
+
+/// Metrics compared between snapshots, in display order
+fn metric_values(stats: &OverallStats) -> [(&'static str, f64); 9] {
+    let cost_per_message = if stats.total_messages > 0 {
+        stats.total_cost_usd / stats.total_messages as f64
+    } else {
+        0.0
+    };
+
+    [
+        ("totalInputTokens", stats.total_input_tokens as f64),
+        ("totalOutputTokens", stats.total_output_tokens as f64),
+        ("cacheCreationTokens", stats.cache_creation_tokens as f64),
+        ("cacheReadTokens", stats.cache_read_tokens as f64),
+        ("totalCostUsd", stats.total_cost_usd),
+        ("totalMessages", stats.total_messages as f64),
+        ("totalSessions", stats.total_sessions as f64),
+        ("projectCount", stats.project_count as f64),
+        ("costPerMessage", cost_per_message),
+    ]
+}
+
+/// Calculate per-metric deltas between two sets of overall statistics
+pub fn calculate_metric_deltas(before: &OverallStats, after: &OverallStats) -> Vec<MetricDelta> {
+    metric_values(before)
+        .iter()
+        .zip(metric_values(after).iter())
+        .map(|((metric, before), (_, after))| {
+            let delta = after - before;
+            MetricDelta {
+                metric: metric.to_string(),
+                before: *before,
+                after: *after,
+                delta: (delta * 1_000_000.0).round() / 1_000_000.0,
+                percent_change: if *before != 0.0 {
+                    Some((delta / before * 100.0 * 100.0).round() / 100.0)
+                } else {
+                    None
+                },
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn temp_snapshot_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ccm-snapshot-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_save_then_compare_snapshot() {
+        let dir = temp_snapshot_dir("compare");
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+        let before = OverallStats {
+            total_input_tokens: 1000,
+            cache_read_tokens: 0,
+            total_cost_usd: 10.0,
+            total_messages: 10,
+            ..Default::default()
+        };
+        save_snapshot(&dir, "before-caching", &before, &now).unwrap();
+
+        let after = OverallStats {
+            total_input_tokens: 1500,
+            cache_read_tokens: 4000,
+            total_cost_usd: 12.0,
+            total_messages: 20,
+            ..Default::default()
+        };
+        let snapshot = load_snapshot(&dir, "before-caching").unwrap();
+        assert_eq!(snapshot.created_at, now.to_rfc3339());
+
+        let comparison = compare_snapshot(&snapshot, &after);
+        let delta = |metric: &str| comparison.deltas.iter().find(|d| d.metric == metric).unwrap();
+        assert_eq!(delta("totalInputTokens").delta, 500.0);
+        assert_eq!(delta("totalInputTokens").percent_change, Some(50.0));
+        assert_eq!(delta("cacheReadTokens").percent_change, None);
+        assert!((delta("costPerMessage").after - 0.6).abs() < 1e-9);
+        assert_eq!(delta("costPerMessage").percent_change, Some(-40.0));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_name_validation() {
+        let dir = temp_snapshot_dir("names");
+        let now = Utc::now();
+        let stats = OverallStats::default();
+        assert!(matches!(
+            save_snapshot(&dir, "../escape", &stats, &now),
+            Err(SnapshotError::InvalidName(_))
+        ));
+        assert!(matches!(load_snapshot(&dir, "missing"), Err(SnapshotError::NotFound(_))));
+    }
+}
@@ -1,15 +1,49 @@
 //! Statistics calculation for usage data
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Timelike, Utc};
+
+use crate::usage::clock::{Clock, SystemClock};
+use crate::usage::models::{
+    AppConfig, BlendedRatePoint, BurnRate, DailyCostAverage, DailyUsage, ExpensiveSession,
+    FootprintEstimate, Forecast, Granularity, HourlyUsage, MessageBudget, MessageCostPoint,
+    ModelFamilyStats, ModelStats, OverallStats, PeriodUsage, PlanProjection, PlanStatus,
+    ProjectCacheCostImpact, ProjectCacheEfficiency, ProjectPage, ProjectSortKey, ProjectStats,
+    ProjectWorkSessions, SessionBlockInfo, SpendClassification, SpendStability, TimeBucket,
+    TimeBucketUsage, TodayStats, TokenComposition, UsageData, UsageEntry, UsageSinceLastViewed,
+    WorkSession, WorkSessionReport,
+};
+use crate::usage::pricing::{
+    claude_4_5_family, get_plan_limits, round_cost, DEFAULT_COST_DECIMALS, PLAN_TYPES,
+    PricingCalculator,
+};
+use crate::usage::reader::{
+    for_each_project_entry, is_duplicate_entry, list_projects_with_options,
+    load_all_entries_with_options, ProjectData, ReaderError, ScanOptions,
+};
+use crate::usage::timezone::UsageTimezone;
+
+/// Decimal places of the burn rate's hourly cost (fewer if `cost_decimals` is lower)
+const BURN_RATE_COST_DECIMALS: u32 = 4;
 
-use chrono::{DateTime, Datelike, Timelike, Utc};
+/// Session duration in minutes (5 hours)
+pub(crate) const SESSION_DURATION_MINUTES: i64 = 300;
 
-use crate::usage::models::{BurnRate, DailyUsage, ModelStats, OverallStats, ProjectStats, UsageData, UsageEntry};
-use crate::usage::pricing::PricingCalculator;
-use crate::usage::reader::{load_all_entries, ProjectData, ReaderError};
+/// Default floor on session block duration used by the burn rate calculation
+pub const DEFAULT_MIN_BLOCK_DURATION_MINUTES: f64 = 1.0;
 
-/// Session duration in minutes (5 hours)
-const SESSION_DURATION_MINUTES: i64 = 300;
+/// Days of history whose session blocks feed the plan projections
+const PLAN_PROJECTION_WINDOW_DAYS: i64 = 30;
+
+/// Minimum days of history before spend stability is classified
+const MIN_STABILITY_DAYS: usize = 3;
+
+/// Coefficient of variation below which daily spend is considered stable
+const STABLE_CV_THRESHOLD: f64 = 0.5;
+
+/// Coefficient of variation below which daily spend is considered variable (above is erratic)
+const VARIABLE_CV_THRESHOLD: f64 = 1.0;
 
 /// Filter options for usage data
 #[derive(Debug, Default)]
@@ -20,6 +54,22 @@ pub struct FilterOptions {
     pub end_date: Option<DateTime<Utc>>,
     /// Filter by project path (decoded)
     pub project_path: Option<String>,
+    /// How project directories are discovered
+    pub scan: ScanOptions,
+    /// Floor on session block duration for burn rate (defaults to 1 minute)
+    pub min_block_minutes: Option<f64>,
+    /// CSV of audited `request_id,cost_usd` costs that supersede computed costs
+    pub cost_override_path: Option<String>,
+    /// Multiplier applied to the cache rates of batch requests (defaults to 1.0)
+    pub batch_cache_multiplier: Option<f64>,
+    /// Keep only the most recent N days in `UsageData.daily_usage` (None = full history)
+    pub max_daily_history_days: Option<u32>,
+    /// How entries are grouped into session blocks
+    pub session: SessionConfig,
+    /// Zone usage is bucketed into dates in
+    pub timezone: UsageTimezone,
+    /// Decimal places of reported costs (defaults to 6)
+    pub cost_decimals: Option<u32>,
 }
 
 impl FilterOptions {
@@ -38,6 +88,61 @@ impl FilterOptions {
         self
     }
 
+    pub fn with_scan_options(mut self, scan: ScanOptions) -> Self {
+        self.scan = scan;
+        self
+    }
+
+    pub fn with_min_block_duration(mut self, minutes: f64) -> Self {
+        self.min_block_minutes = Some(minutes);
+        self
+    }
+
+    pub fn with_cost_overrides(mut self, path: Option<String>) -> Self {
+        self.cost_override_path = path;
+        self
+    }
+
+    pub fn with_batch_cache_multiplier(mut self, multiplier: f64) -> Self {
+        self.batch_cache_multiplier = Some(multiplier);
+        self
+    }
+
+    pub fn with_max_daily_history(mut self, days: Option<u32>) -> Self {
+        self.max_daily_history_days = days;
+        self
+    }
+
+    pub fn with_session(mut self, session: SessionConfig) -> Self {
+        self.session = session;
+        self
+    }
+
+    pub fn with_timezone(mut self, timezone: UsageTimezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    pub fn with_cost_decimals(mut self, decimals: u32) -> Self {
+        self.cost_decimals = Some(decimals);
+        self
+    }
+
+    /// Decimal places reported costs are rounded to
+    pub fn cost_decimals(&self) -> u32 {
+        self.cost_decimals.unwrap_or(DEFAULT_COST_DECIMALS)
+    }
+
+    /// Build the pricing calculator, loading cost overrides when configured
+    pub fn pricing(&self) -> PricingCalculator {
+        let pricing =
+            PricingCalculator::new().with_cost_override_file(self.cost_override_path.as_deref());
+        match self.batch_cache_multiplier {
+            Some(multiplier) => pricing.with_batch_cache_multiplier(multiplier),
+            None => pricing,
+        }
+    }
+
     /// Check if an entry passes the filter
     pub fn matches(&self, entry: &UsageEntry, project_path: Option<&str>) -> bool {
         // Check date range
@@ -65,10 +170,103 @@ impl FilterOptions {
     }
 }
 
+/// How entries are grouped into session blocks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionConfig {
+    /// Length of a block from its hour-rounded start
+    pub duration_minutes: u32,
+    /// Also start a new block when consecutive messages are more than this many minutes
+    /// apart (None = split on the fixed block length only)
+    pub gap_minutes: Option<u32>,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            duration_minutes: SESSION_DURATION_MINUTES as u32,
+            gap_minutes: None,
+        }
+    }
+}
+
+impl SessionConfig {
+    /// Build session options from the app configuration
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            duration_minutes: config.session_duration_minutes.max(1),
+            gap_minutes: config.session_gap_minutes,
+        }
+    }
+
+    /// Length of a session block
+    pub fn duration(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.duration_minutes as i64)
+    }
+
+    /// Whether an idle stretch from `last` to `next` ends the session
+    fn is_gap(&self, last: &DateTime<Utc>, next: &DateTime<Utc>) -> bool {
+        self.gap_minutes
+            .is_some_and(|gap| *next - *last > chrono::Duration::minutes(gap as i64))
+    }
+
+    /// Whether an entry at `next` starts a new block after the block that began at
+    /// `block_start` and last had activity at `last`
+    fn starts_new_block(
+        &self,
+        block_start: &DateTime<Utc>,
+        last: &DateTime<Utc>,
+        next: &DateTime<Utc>,
+    ) -> bool {
+        *next >= *block_start + self.duration() || self.is_gap(last, next)
+    }
+}
+
+/// Start of the hour containing `timestamp`, where a session block starting then begins
+fn hour_start(timestamp: &DateTime<Utc>) -> DateTime<Utc> {
+    timestamp
+        .with_minute(0).unwrap()
+        .with_second(0).unwrap()
+        .with_nanosecond(0).unwrap()
+}
+
+/// Timestamp of the first entry of the earliest session block that starts at or after
+/// `since`, given every entry timestamp in ascending order. Blocks chain from the very
+/// first entry, so where one begins depends on the whole history before it.
+fn first_block_entry_since(
+    timestamps: &[DateTime<Utc>],
+    since: &DateTime<Utc>,
+    session: &SessionConfig,
+) -> Option<DateTime<Utc>> {
+    // Start and last activity of the block being walked
+    let mut block: Option<(DateTime<Utc>, DateTime<Utc>)> = None;
+
+    for timestamp in timestamps {
+        match &mut block {
+            Some((start, last)) if !session.starts_new_block(start, last, timestamp) => {
+                *last = *timestamp;
+            }
+            _ => {
+                let start = hour_start(timestamp);
+                if start >= *since {
+                    return Some(*timestamp);
+                }
+                block = Some((start, *timestamp));
+            }
+        }
+    }
+
+    None
+}
+
 /// Normalize model name for consistent grouping
 fn normalize_model_name(model: &str) -> String {
     let model_lower = model.to_lowercase();
 
+    // Merge dated and undated claude-4.5 names
+    if let Some(family) = claude_4_5_family(&model_lower) {
+        return format!("claude-{}-4-5", family);
+    }
+
     // Keep new claude-4 model names as-is
     if model_lower.contains("claude-opus-4-")
         || model_lower.contains("claude-sonnet-4-")
@@ -106,79 +304,110 @@ fn normalize_model_name(model: &str) -> String {
     model.to_string()
 }
 
-/// Calculate model distribution from entries
-fn calculate_model_distribution(entries: &[UsageEntry]) -> Vec<ModelStats> {
-    let mut model_map: HashMap<String, ModelStats> = HashMap::new();
-    let mut total_tokens: u64 = 0;
+/// Get the model family (opus, sonnet, haiku) for a model name, or "other"
+pub fn model_family(model: &str) -> &'static str {
+    let model_lower = model.to_lowercase();
 
-    for entry in entries {
-        let model_key = normalize_model_name(&entry.model);
-        let entry_total = entry.input_tokens + entry.output_tokens;
-        total_tokens += entry_total;
+    if model_lower.contains("opus") {
+        "opus"
+    } else if model_lower.contains("sonnet") {
+        "sonnet"
+    } else if model_lower.contains("haiku") {
+        "haiku"
+    } else {
+        "other"
+    }
+}
 
-        let stats = model_map.entry(model_key.clone()).or_insert_with(|| ModelStats {
-            model: model_key,
+/// Group a flat model distribution by family with family subtotals.
+/// Families are sorted by total tokens descending, as are the models within each family.
+pub fn group_model_distribution(
+    models: &[ModelStats],
+    cost_decimals: u32,
+) -> Vec<ModelFamilyStats> {
+    let mut family_map: HashMap<&'static str, ModelFamilyStats> = HashMap::new();
+    let total_tokens: u64 = models.iter().map(|m| m.total_tokens).sum();
+
+    for model in models {
+        let family = model_family(&model.model);
+        let stats = family_map.entry(family).or_insert_with(|| ModelFamilyStats {
+            family: family.to_string(),
             ..Default::default()
         });
 
-        stats.input_tokens += entry.input_tokens;
-        stats.output_tokens += entry.output_tokens;
-        stats.cache_creation_tokens += entry.cache_creation_tokens;
-        stats.cache_read_tokens += entry.cache_read_tokens;
-        stats.cost_usd += entry.cost_usd;
-        stats.message_count += 1;
-        stats.total_tokens += entry_total;
+        stats.input_tokens += model.input_tokens;
+        stats.output_tokens += model.output_tokens;
+        stats.cache_creation_tokens += model.cache_creation_tokens;
+        stats.cache_read_tokens += model.cache_read_tokens;
+        stats.total_tokens += model.total_tokens;
+        stats.cost_usd += model.cost_usd;
+        stats.message_count += model.message_count;
+        stats.models.push(model.clone());
     }
 
-    // Calculate percentages and round costs
-    let mut model_list: Vec<_> = model_map
+    let mut family_list: Vec<_> = family_map
         .into_values()
-        .map(|mut m| {
-            m.percentage = if total_tokens > 0 {
-                (m.total_tokens as f64 / total_tokens as f64) * 100.0
+        .map(|mut f| {
+            f.percentage = if total_tokens > 0 {
+                (f.total_tokens as f64 / total_tokens as f64) * 100.0
             } else {
                 0.0
             };
-            m.cost_usd = (m.cost_usd * 1_000_000.0).round() / 1_000_000.0;
-            m.percentage = (m.percentage * 100.0).round() / 100.0;
-            m
+            f.percentage = (f.percentage * 100.0).round() / 100.0;
+            f.cost_usd = round_cost(f.cost_usd, cost_decimals);
+            f.models.sort_by_key(|m| std::cmp::Reverse(m.total_tokens));
+            f
         })
         .collect();
 
-    // Sort by total tokens descending
-    model_list.sort_by(|a, b| b.total_tokens.cmp(&a.total_tokens));
-    model_list
+    family_list.sort_by_key(|f| std::cmp::Reverse(f.total_tokens));
+    family_list
 }
 
 /// Session block for proportional burn rate calculation (matches Python's block structure)
 #[derive(Debug)]
-struct SessionBlock {
+pub(crate) struct SessionBlock {
     start_time: DateTime<Utc>,
     actual_end_time: DateTime<Utc>,
     total_tokens: u64,  // input + output only (like Python's totalTokens)
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
     total_cost: f64,
+    message_count: u32,
+    /// Per-model token/cost breakdown keyed by normalized model name
+    model_stats: HashMap<String, ModelStats>,
     is_active: bool,
 }
 
 /// Transform entries into session blocks (5-hour blocks starting at hour boundary)
-/// Matches Python's SessionAnalyzer.transform_to_blocks
-fn transform_to_blocks(entries: &[UsageEntry]) -> Vec<SessionBlock> {
+/// Matches Python's SessionAnalyzer.transform_to_blocks. Shared with the cache's
+/// incremental path so both compute identical blocks. With `session.gap_minutes` set, an
+/// idle stretch longer than the gap also ends the block.
+pub(crate) fn transform_to_blocks(
+    entries: &[UsageEntry],
+    now: &DateTime<Utc>,
+    session: &SessionConfig,
+) -> Vec<SessionBlock> {
     if entries.is_empty() {
         return Vec::new();
     }
 
     let mut blocks: Vec<SessionBlock> = Vec::new();
-    let session_duration = chrono::Duration::hours(5);
+    let session_duration = session.duration();
 
     let mut current_block: Option<SessionBlock> = None;
 
     for entry in entries {
         let should_create_new = match &current_block {
             None => true,
-            Some(block) => {
-                // Check if entry is past block's end time
-                entry.timestamp >= block.start_time + session_duration
-            }
+            // Check if entry is past block's end time or follows a long idle gap
+            Some(block) => session.starts_new_block(
+                &block.start_time,
+                &block.actual_end_time,
+                &entry.timestamp,
+            ),
         };
 
         if should_create_new {
@@ -188,16 +417,19 @@ fn transform_to_blocks(entries: &[UsageEntry]) -> Vec<SessionBlock> {
             }
 
             // Create new block - round start time to hour boundary
-            let start_time = entry.timestamp
-                .with_minute(0).unwrap()
-                .with_second(0).unwrap()
-                .with_nanosecond(0).unwrap();
+            let start_time = hour_start(&entry.timestamp);
 
             current_block = Some(SessionBlock {
                 start_time,
                 actual_end_time: entry.timestamp,
                 total_tokens: 0,
+                input_tokens: 0,
+                output_tokens: 0,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
                 total_cost: 0.0,
+                message_count: 0,
+                model_stats: HashMap::new(),
                 is_active: false,
             });
         }
@@ -206,16 +438,34 @@ fn transform_to_blocks(entries: &[UsageEntry]) -> Vec<SessionBlock> {
         if let Some(ref mut block) = current_block {
             // Python's totalTokens only includes input + output (no cache tokens)
             block.total_tokens += entry.input_tokens + entry.output_tokens;
+            block.input_tokens += entry.input_tokens;
+            block.output_tokens += entry.output_tokens;
+            block.cache_creation_tokens += entry.cache_creation_tokens;
+            block.cache_read_tokens += entry.cache_read_tokens;
             block.total_cost += entry.cost_usd;
+            block.message_count += 1;
+            let model = normalize_model_name(&entry.model);
+            let stats = block.model_stats.entry(model.clone()).or_insert_with(|| ModelStats {
+                model,
+                ..Default::default()
+            });
+            stats.input_tokens += entry.input_tokens;
+            stats.output_tokens += entry.output_tokens;
+            stats.cache_creation_tokens += entry.cache_creation_tokens;
+            stats.cache_read_tokens += entry.cache_read_tokens;
+            stats.total_tokens += entry.input_tokens + entry.output_tokens;
+            stats.cost_usd += entry.cost_usd;
+            stats.message_count += 1;
             block.actual_end_time = entry.timestamp;
         }
     }
 
     // Finalize last block
     if let Some(mut block) = current_block {
-        // Mark active if end_time is in the future
-        let now = Utc::now();
-        if block.start_time + session_duration > now {
+        // Mark active if end_time is in the future and the session has not gone idle
+        if block.start_time + session_duration > *now
+            && !session.is_gap(&block.actual_end_time, now)
+        {
             block.is_active = true;
         }
         blocks.push(block);
@@ -225,8 +475,20 @@ fn transform_to_blocks(entries: &[UsageEntry]) -> Vec<SessionBlock> {
 }
 
 /// Calculate hourly burn rate using block-based proportional allocation
-/// Matches Python's calculate_hourly_burn_rate in calculations.py
-fn calculate_hourly_burn_rate(blocks: &[SessionBlock], current_time: &DateTime<Utc>) -> (f64, f64) {
+/// Matches Python's calculate_hourly_burn_rate in calculations.py, except that block
+/// durations are floored at `min_block_minutes` so a block holding a single message
+/// does not claim its whole token count for a few seconds of activity. Entries stamped
+/// slightly in the future (clock skew between synced machines) count as happening now.
+///
+/// Returns `(tokens_per_minute, cost_per_hour)`. Tokens are averaged over the whole hour
+/// as in Python; cost is divided by the span from the earliest activity in the window to
+/// now (floored at `min_block_minutes`) and scaled to 60 minutes, so a 20-minute session
+/// reports a true hourly rate rather than the cost accumulated so far.
+pub(crate) fn calculate_hourly_burn_rate(
+    blocks: &[SessionBlock],
+    current_time: &DateTime<Utc>,
+    min_block_minutes: f64,
+) -> (f64, f64) {
     if blocks.is_empty() {
         return (0.0, 0.0);
     }
@@ -234,11 +496,19 @@ fn calculate_hourly_burn_rate(blocks: &[SessionBlock], current_time: &DateTime<U
     let one_hour_ago = *current_time - chrono::Duration::hours(1);
     let mut total_tokens: f64 = 0.0;
     let mut total_cost: f64 = 0.0;
+    let mut window_start: Option<DateTime<Utc>> = None;
 
     for block in blocks {
+        // A block whose hour-rounded start is still in the future starts this hour instead
+        let block_start = if block.start_time > *current_time {
+            hour_start(current_time)
+        } else {
+            block.start_time
+        };
+
         // Determine session end time (current time if active, actual_end_time otherwise)
         let session_actual_end = if block.is_active {
-            *current_time
+            block.actual_end_time.max(*current_time)
         } else {
             block.actual_end_time
         };
@@ -249,8 +519,8 @@ fn calculate_hourly_burn_rate(blocks: &[SessionBlock], current_time: &DateTime<U
         }
 
         // Calculate overlap with the last hour
-        let session_start_in_hour = if block.start_time > one_hour_ago {
-            block.start_time
+        let session_start_in_hour = if block_start > one_hour_ago {
+            block_start
         } else {
             one_hour_ago
         };
@@ -266,246 +536,1705 @@ fn calculate_hourly_burn_rate(blocks: &[SessionBlock], current_time: &DateTime<U
         }
 
         // Calculate proportional tokens
-        let total_session_duration = (session_actual_end - block.start_time).num_seconds() as f64 / 60.0;
+        let total_session_duration = ((session_end_in_hour - block_start).num_seconds() as f64
+            / 60.0)
+            .max(min_block_minutes);
         let hour_duration = (session_end_in_hour - session_start_in_hour).num_seconds() as f64 / 60.0;
 
         if total_session_duration > 0.0 {
             let proportion = hour_duration / total_session_duration;
             total_tokens += block.total_tokens as f64 * proportion;
             total_cost += block.total_cost * proportion;
+            window_start = Some(window_start.map_or(session_start_in_hour, |start| {
+                start.min(session_start_in_hour)
+            }));
         }
     }
 
-    // Return tokens per minute (divide by 60)
-    if total_tokens > 0.0 {
-        (total_tokens / 60.0, total_cost / 60.0 * 60.0) // tokens/min, cost/hour
-    } else {
-        (0.0, 0.0)
+    match window_start {
+        Some(start) if total_tokens > 0.0 => {
+            let active_minutes =
+                ((*current_time - start).num_seconds() as f64 / 60.0).max(min_block_minutes);
+            (total_tokens / 60.0, total_cost / active_minutes * 60.0)
+        }
+        _ => (0.0, 0.0),
     }
 }
 
-/// Calculate time to reset based on session start time
-fn calculate_time_to_reset(session_start: Option<&DateTime<Utc>>, now: &DateTime<Utc>) -> u32 {
-    match session_start {
-        Some(start) => {
-            let elapsed_minutes = (*now - *start).num_minutes();
-            if elapsed_minutes < 0 {
-                return SESSION_DURATION_MINUTES as u32;
-            }
-            let remaining = SESSION_DURATION_MINUTES - (elapsed_minutes % SESSION_DURATION_MINUTES);
-            remaining.max(0) as u32
+/// Calculate message usage against the plan's message limit for the active session block.
+/// The projection extrapolates the block's message rate so far; it is only reported
+/// when the limit would be reached before the block resets.
+pub fn calculate_message_budget(
+    entries: &[UsageEntry],
+    plan_type: &str,
+    now: &DateTime<Utc>,
+    session: &SessionConfig,
+) -> MessageBudget {
+    let limits = get_plan_limits(plan_type);
+    let mut budget = MessageBudget {
+        plan_type: plan_type.to_string(),
+        message_limit: limits.message_limit,
+        messages_remaining: limits.message_limit,
+        ..Default::default()
+    };
+
+    let blocks = transform_to_blocks(entries, now, session);
+    let active_block = match blocks.last() {
+        Some(block) if block.is_active => block,
+        _ => return budget,
+    };
+
+    budget.messages_used = active_block.message_count;
+    budget.messages_remaining = limits.message_limit.saturating_sub(active_block.message_count);
+    budget.session_start_time = Some(active_block.start_time.to_rfc3339());
+
+    let elapsed_minutes = ((*now - active_block.start_time).num_seconds() as f64 / 60.0).max(1.0);
+    let messages_per_minute = active_block.message_count as f64 / elapsed_minutes;
+    budget.messages_per_hour = (messages_per_minute * 60.0 * 100.0).round() / 100.0;
+
+    if budget.messages_remaining == 0 {
+        budget.projected_limit_time = Some(now.to_rfc3339());
+    } else if messages_per_minute > 0.0 {
+        let minutes_to_limit = budget.messages_remaining as f64 / messages_per_minute;
+        let projected = *now + chrono::Duration::seconds((minutes_to_limit * 60.0) as i64);
+        let block_end = active_block.start_time + session.duration();
+        if projected < block_end {
+            budget.projected_limit_time = Some(projected.to_rfc3339());
         }
-        None => SESSION_DURATION_MINUTES as u32,
     }
+
+    budget
 }
 
-/// Calculate project statistics from entries
-fn calculate_project_stats(project: &ProjectData, entries: &[UsageEntry]) -> ProjectStats {
-    let mut stats = ProjectStats {
-        project_path: project.decoded_path.clone(),
-        display_name: project.display_name.clone(),
-        session_count: project.session_files.len() as u32,
+/// Calculate the active session block's tokens, cost and messages against the plan's
+/// limits. With no active block the usage and percentages are all zero.
+pub fn calculate_plan_status(
+    entries: &[UsageEntry],
+    plan_type: &str,
+    now: &DateTime<Utc>,
+    session: &SessionConfig,
+    cost_decimals: u32,
+) -> PlanStatus {
+    let limits = get_plan_limits(plan_type);
+    let mut status = PlanStatus {
+        plan_type: plan_type.to_string(),
+        token_limit: limits.token_limit,
+        cost_limit: limits.cost_limit,
+        message_limit: limits.message_limit,
         ..Default::default()
     };
 
-    for entry in entries {
-        stats.total_input_tokens += entry.input_tokens;
-        stats.total_output_tokens += entry.output_tokens;
-        stats.cache_creation_tokens += entry.cache_creation_tokens;
-        stats.cache_read_tokens += entry.cache_read_tokens;
-        stats.total_cost_usd += entry.cost_usd;
-        stats.message_count += 1;
+    let blocks = transform_to_blocks(entries, now, session);
+    let active_block = match blocks.last() {
+        Some(block) if block.is_active => block,
+        _ => return status,
+    };
 
-        // Update activity timestamps
-        let ts = entry.timestamp.to_rfc3339();
-        match &stats.first_activity {
-            None => stats.first_activity = Some(ts.clone()),
-            Some(first) if ts < *first => stats.first_activity = Some(ts.clone()),
-            _ => {}
-        }
-        match &stats.last_activity {
-            None => stats.last_activity = Some(ts.clone()),
-            Some(last) if ts > *last => stats.last_activity = Some(ts.clone()),
-            _ => {}
+    let percent_of = |used: f64, limit: f64| {
+        if limit > 0.0 {
+            (used / limit * 100.0 * 100.0).round() / 100.0
+        } else {
+            0.0
         }
-    }
+    };
 
-    // Round cost
-    stats.total_cost_usd = (stats.total_cost_usd * 1_000_000.0).round() / 1_000_000.0;
+    status.session_start_time = Some(active_block.start_time.to_rfc3339());
+    status.tokens_used = active_block.total_tokens;
+    status.cost_used_usd = round_cost(active_block.total_cost, cost_decimals);
+    status.messages_used = active_block.message_count;
+    status.token_percent = percent_of(active_block.total_tokens as f64, limits.token_limit as f64);
+    status.cost_percent = percent_of(active_block.total_cost, limits.cost_limit);
+    status.message_percent =
+        percent_of(active_block.message_count as f64, limits.message_limit as f64);
 
-    stats
+    status
 }
 
-/// Calculate daily usage from entries
-fn calculate_daily_usage(entries: &[UsageEntry]) -> Vec<DailyUsage> {
-    let mut daily_map: HashMap<String, DailyUsage> = HashMap::new();
+/// Project how session blocks in the last `PLAN_PROJECTION_WINDOW_DAYS` days would fare
+/// under each plan tier. A block counts as hitting a plan's limit when its tokens, cost
+/// or messages reach that plan's limit; overage is the cost above the cost limit.
+pub fn calculate_plan_projections(
+    entries: &[UsageEntry],
+    now: &DateTime<Utc>,
+    session: &SessionConfig,
+    cost_decimals: u32,
+) -> Vec<PlanProjection> {
+    let window_start = *now - chrono::Duration::days(PLAN_PROJECTION_WINDOW_DAYS);
+    let recent: Vec<UsageEntry> = entries
+        .iter()
+        .filter(|e| e.timestamp >= window_start)
+        .cloned()
+        .collect();
+    let blocks = transform_to_blocks(&recent, now, session);
+
+    PLAN_TYPES
+        .iter()
+        .map(|plan_type| {
+            let limits = get_plan_limits(plan_type);
+            let mut sessions_over_limit = 0u32;
+            let mut overage = 0.0;
+
+            for block in &blocks {
+                if block.total_tokens >= limits.token_limit
+                    || block.total_cost >= limits.cost_limit
+                    || block.message_count >= limits.message_limit
+                {
+                    sessions_over_limit += 1;
+                }
+                overage += (block.total_cost - limits.cost_limit).max(0.0);
+            }
 
-    for entry in entries {
-        let date_key = format!(
-            "{:04}-{:02}-{:02}",
-            entry.timestamp.year(),
-            entry.timestamp.month(),
-            entry.timestamp.day()
-        );
+            let limit_hit_percent = if blocks.is_empty() {
+                0.0
+            } else {
+                sessions_over_limit as f64 / blocks.len() as f64 * 100.0
+            };
 
-        let daily = daily_map.entry(date_key.clone()).or_insert_with(|| DailyUsage {
-            date: date_key,
-            ..Default::default()
-        });
+            PlanProjection {
+                plan_type: plan_type.to_string(),
+                token_limit: limits.token_limit,
+                cost_limit: limits.cost_limit,
+                message_limit: limits.message_limit,
+                sessions_analyzed: blocks.len() as u32,
+                sessions_over_limit,
+                limit_hit_percent: (limit_hit_percent * 100.0).round() / 100.0,
+                estimated_overage_usd: round_cost(overage, cost_decimals),
+            }
+        })
+        .collect()
+}
 
-        daily.input_tokens += entry.input_tokens;
-        daily.output_tokens += entry.output_tokens;
-        daily.cache_creation_tokens += entry.cache_creation_tokens;
-        daily.cache_read_tokens += entry.cache_read_tokens;
-        daily.cost_usd += entry.cost_usd;
-        daily.message_count += 1;
+/// Split the aggregated token totals into percentages by token type.
+/// All percentages are zero when there are no tokens.
+pub fn calculate_token_composition(stats: &OverallStats) -> TokenComposition {
+    let total_tokens = stats.total_input_tokens
+        + stats.total_output_tokens
+        + stats.cache_creation_tokens
+        + stats.cache_read_tokens;
+
+    let percent = |tokens: u64| {
+        if total_tokens == 0 {
+            0.0
+        } else {
+            (tokens as f64 / total_tokens as f64 * 100.0 * 100.0).round() / 100.0
+        }
+    };
+
+    TokenComposition {
+        total_tokens,
+        input_percent: percent(stats.total_input_tokens),
+        output_percent: percent(stats.total_output_tokens),
+        cache_creation_percent: percent(stats.cache_creation_tokens),
+        cache_read_percent: percent(stats.cache_read_tokens),
     }
+}
 
-    // Round costs and sort by date
-    let mut daily_list: Vec<_> = daily_map
-        .into_values()
-        .map(|mut d| {
-            d.cost_usd = (d.cost_usd * 1_000_000.0).round() / 1_000_000.0;
-            d
-        })
-        .collect();
+/// Estimate energy (kWh) and carbon (kg CO2) for `total_tokens` using the given factors.
+/// This is a rough, linear estimate; real figures depend on hardware, model and grid.
+pub fn estimate_footprint(
+    total_tokens: u64,
+    kwh_per_million_tokens: f64,
+    co2_kg_per_kwh: f64,
+) -> FootprintEstimate {
+    let energy_kwh = total_tokens as f64 / 1_000_000.0 * kwh_per_million_tokens;
+    let co2_kg = energy_kwh * co2_kg_per_kwh;
+
+    FootprintEstimate {
+        total_tokens,
+        energy_kwh: (energy_kwh * 1_000_000.0).round() / 1_000_000.0,
+        co2_kg: (co2_kg * 1_000_000.0).round() / 1_000_000.0,
+        kwh_per_million_tokens,
+        co2_kg_per_kwh,
+    }
+}
 
-    daily_list.sort_by(|a, b| a.date.cmp(&b.date));
-    daily_list
+/// Find the session block with the highest total cost across all entries
+pub fn find_most_expensive_session(
+    entries: &[UsageEntry],
+    now: &DateTime<Utc>,
+    session: &SessionConfig,
+    cost_decimals: u32,
+) -> Option<ExpensiveSession> {
+    let blocks = transform_to_blocks(entries, now, session);
+    let block = blocks
+        .iter()
+        .max_by(|a, b| a.total_cost.total_cmp(&b.total_cost))?;
+
+    let dominant_model = block
+        .model_stats
+        .values()
+        .max_by(|a, b| a.cost_usd.total_cmp(&b.cost_usd))
+        .map(|stats| stats.model.clone())
+        .unwrap_or_default();
+
+    Some(ExpensiveSession {
+        start_time: block.start_time.to_rfc3339(),
+        end_time: block.actual_end_time.to_rfc3339(),
+        input_tokens: block.input_tokens,
+        output_tokens: block.output_tokens,
+        cache_creation_tokens: block.cache_creation_tokens,
+        cache_read_tokens: block.cache_read_tokens,
+        cost_usd: round_cost(block.total_cost, cost_decimals),
+        message_count: block.message_count,
+        dominant_model,
+    })
 }
 
-/// Calculate overall statistics with advanced metrics
-fn calculate_overall_stats(projects: &[ProjectStats], all_entries: &[UsageEntry]) -> OverallStats {
-    let mut stats = OverallStats {
-        project_count: projects.len() as u32,
-        ..Default::default()
-    };
+/// Build the serializable session blocks with per-model token/cost breakdowns. Idle
+/// stretches of at least one session length between blocks are returned as gap blocks,
+/// like Python's SessionAnalyzer does.
+pub fn calculate_session_blocks(
+    entries: &[UsageEntry],
+    now: &DateTime<Utc>,
+    session: &SessionConfig,
+    cost_decimals: u32,
+) -> Vec<SessionBlockInfo> {
+    let session_duration = session.duration();
+    let mut result: Vec<SessionBlockInfo> = Vec::new();
+    let mut previous_end: Option<DateTime<Utc>> = None;
+
+    for block in transform_to_blocks(entries, now, session) {
+        if let Some(gap_start) = previous_end {
+            if block.start_time - gap_start >= session_duration {
+                result.push(SessionBlockInfo {
+                    start_time: gap_start.to_rfc3339(),
+                    end_time: block.start_time.to_rfc3339(),
+                    actual_end_time: block.start_time.to_rfc3339(),
+                    is_gap: true,
+                    ..Default::default()
+                });
+            }
+        }
+        previous_end = Some(block.actual_end_time);
+
+        let mut models: Vec<ModelStats> = block
+            .model_stats
+            .into_values()
+            .map(|mut stats| {
+                stats.percentage = if block.total_tokens > 0 {
+                    let share = stats.total_tokens as f64 / block.total_tokens as f64;
+                    (share * 10000.0).round() / 100.0
+                } else {
+                    0.0
+                };
+                stats.cost_usd = round_cost(stats.cost_usd, cost_decimals);
+                stats
+            })
+            .collect();
+        models.sort_by(|a, b| b.cost_usd.total_cmp(&a.cost_usd).then(a.model.cmp(&b.model)));
+
+        result.push(SessionBlockInfo {
+            start_time: block.start_time.to_rfc3339(),
+            end_time: (block.start_time + session_duration).to_rfc3339(),
+            actual_end_time: block.actual_end_time.to_rfc3339(),
+            is_active: block.is_active,
+            is_gap: false,
+            input_tokens: block.input_tokens,
+            output_tokens: block.output_tokens,
+            cache_creation_tokens: block.cache_creation_tokens,
+            cache_read_tokens: block.cache_read_tokens,
+            total_tokens: block.total_tokens,
+            cost_usd: round_cost(block.total_cost, cost_decimals),
+            message_count: block.message_count,
+            models,
+        });
+    }
+
+    result
+}
+
+/// Calculate time to reset based on session start time
+pub(crate) fn calculate_time_to_reset(
+    session_start: Option<&DateTime<Utc>>,
+    now: &DateTime<Utc>,
+    session: &SessionConfig,
+) -> u32 {
+    let duration_minutes = session.duration_minutes as i64;
+    match session_start {
+        Some(start) => {
+            let elapsed_minutes = (*now - *start).num_minutes();
+            if elapsed_minutes < 0 {
+                return session.duration_minutes;
+            }
+            let remaining = duration_minutes - (elapsed_minutes % duration_minutes);
+            remaining.max(0) as u32
+        }
+        None => session.duration_minutes,
+    }
+}
 
-    for project in projects {
-        stats.total_input_tokens += project.total_input_tokens;
-        stats.total_output_tokens += project.total_output_tokens;
-        stats.cache_creation_tokens += project.cache_creation_tokens;
-        stats.cache_read_tokens += project.cache_read_tokens;
-        stats.total_cost_usd += project.total_cost_usd;
-        stats.total_messages += project.message_count;
-        stats.total_sessions += project.session_count;
+/// Average cost and tokens per message, rounded; both 0 without messages
+fn per_message_averages(
+    cost_usd: f64,
+    tokens: u64,
+    messages: u32,
+    cost_decimals: u32,
+) -> (f64, f64) {
+    if messages == 0 {
+        return (0.0, 0.0);
     }
+    let messages = messages as f64;
+    (
+        round_cost(cost_usd / messages, cost_decimals),
+        (tokens as f64 / messages * 100.0).round() / 100.0,
+    )
+}
 
-    // Round cost
-    stats.total_cost_usd = (stats.total_cost_usd * 1_000_000.0).round() / 1_000_000.0;
+/// Sort projects by `sort_by` (most recent / largest first) and return the page starting
+/// at `offset`. Ties fall back to the project path so pages are stable between calls.
+pub fn paginate_projects(
+    mut projects: Vec<ProjectStats>,
+    offset: u32,
+    limit: u32,
+    sort_by: ProjectSortKey,
+) -> ProjectPage {
+    let total_tokens = |p: &ProjectStats| p.total_input_tokens + p.total_output_tokens;
+    projects.sort_by(|a, b| {
+        let order = match sort_by {
+            ProjectSortKey::LastActivity => b.last_activity.cmp(&a.last_activity),
+            ProjectSortKey::TotalCost => b.total_cost_usd.total_cmp(&a.total_cost_usd),
+            ProjectSortKey::TotalTokens => total_tokens(b).cmp(&total_tokens(a)),
+        };
+        order.then_with(|| a.project_path.cmp(&b.project_path))
+    });
 
-    // Calculate model distribution
-    stats.model_distribution = calculate_model_distribution(all_entries);
+    let total = projects.len() as u32;
+    let projects = projects
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect();
 
-    // Calculate session timing and burn rate
-    // Session timing uses 5-hour blocks, burn rate uses block-based proportional allocation (like Python CLI)
-    if !all_entries.is_empty() {
-        let now = Utc::now();
+    ProjectPage {
+        projects,
+        total,
+        offset,
+        limit,
+    }
+}
 
-        // Get the last 5 hours window to identify recent activity for session timing
-        let window_start = now - chrono::Duration::minutes(SESSION_DURATION_MINUTES);
+/// Projects whose display name or path contains `query` (case-insensitive). Projects whose
+/// display name or path starts with the query come first; otherwise the input order is kept.
+/// An empty query matches nothing.
+pub fn search_project_stats(projects: &[ProjectStats], query: &str) -> Vec<ProjectStats> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
 
-        // Get entries within the 5-hour window
-        let recent_entries: Vec<_> = all_entries
-            .iter()
-            .filter(|e| e.timestamp >= window_start)
-            .collect();
+    let mut matches: Vec<(bool, &ProjectStats)> = projects
+        .iter()
+        .filter_map(|p| {
+            let name = p.display_name.to_lowercase();
+            let path = p.project_path.to_lowercase();
+            if name.starts_with(&query) || path.starts_with(&query) {
+                Some((true, p))
+            } else if name.contains(&query) || path.contains(&query) {
+                Some((false, p))
+            } else {
+                None
+            }
+        })
+        .collect();
 
-        if !recent_entries.is_empty() {
-            // Find the first entry in this window
-            let first_entry_time = recent_entries.iter().map(|e| e.timestamp).min().unwrap();
+    // Stable sort keeps the incoming order within each group
+    matches.sort_by_key(|(is_prefix, _)| !is_prefix);
+    matches.into_iter().map(|(_, p)| p.clone()).collect()
+}
 
-            // Round to hour boundary like Python: start_time = round_to_hour(first_entry.timestamp)
-            let session_block_start = first_entry_time
-                .with_minute(0).unwrap()
-                .with_second(0).unwrap()
-                .with_nanosecond(0).unwrap();
+/// Rank projects by cache-read ratio (highest first). The ratio is cache-read tokens over
+/// all input-side tokens (fresh input + cache creation + cache read).
+pub fn rank_projects_by_cache_efficiency(projects: &[ProjectStats]) -> Vec<ProjectCacheEfficiency> {
+    let mut ranking: Vec<_> = projects
+        .iter()
+        .map(|p| {
+            let input_side = p.total_input_tokens + p.cache_creation_tokens + p.cache_read_tokens;
+            let ratio = if input_side > 0 {
+                p.cache_read_tokens as f64 / input_side as f64
+            } else {
+                0.0
+            };
 
-            stats.session_start_time = Some(session_block_start.to_rfc3339());
-            stats.time_to_reset_minutes = calculate_time_to_reset(Some(&session_block_start), &now);
+            ProjectCacheEfficiency {
+                project_path: p.project_path.clone(),
+                display_name: p.display_name.clone(),
+                fresh_input_tokens: p.total_input_tokens,
+                cache_creation_tokens: p.cache_creation_tokens,
+                cache_read_tokens: p.cache_read_tokens,
+                cache_read_ratio: (ratio * 10000.0).round() / 10000.0,
+            }
+        })
+        .collect();
 
-            // Calculate HOURLY burn rate using block-based proportional allocation
-            // Matches Python CLI's calculate_hourly_burn_rate in calculations.py
+    ranking.sort_by(|a, b| {
+        b.cache_read_ratio
+            .total_cmp(&a.cache_read_ratio)
+            .then_with(|| a.project_path.cmp(&b.project_path))
+    });
+    ranking
+}
 
-            // Transform all entries into session blocks (not just recent ones)
-            // Python uses all blocks that overlap with the last hour
-            let blocks = transform_to_blocks(all_entries);
+/// Compare each project's cache write/read cost with the fresh input cost the cached
+/// tokens avoided, sorted by net savings. Rates are the standard (non-batch) ones.
+pub fn calculate_project_cache_cost_impact(
+    project_entries: &[(ProjectData, Vec<UsageEntry>)],
+    pricing: &PricingCalculator,
+    cost_decimals: u32,
+) -> Vec<ProjectCacheCostImpact> {
+    let per_million = |tokens: u64, rate: f64| tokens as f64 / 1_000_000.0 * rate;
+    let round = |cost: f64| round_cost(cost, cost_decimals);
+
+    let mut impacts: Vec<_> = project_entries
+        .iter()
+        .filter(|(_, entries)| !entries.is_empty())
+        .map(|(project, entries)| {
+            let mut impact = ProjectCacheCostImpact {
+                project_path: project.decoded_path.clone(),
+                display_name: project.display_name.clone(),
+                ..Default::default()
+            };
 
-            // Calculate proportional burn rate
-            let (tokens_per_min, cost_per_hour) = calculate_hourly_burn_rate(&blocks, &now);
+            for entry in entries {
+                let rates = pricing.get_pricing(&entry.model);
+                impact.fresh_input_cost_usd += per_million(entry.input_tokens, rates.input);
 
-            if tokens_per_min > 0.0 {
-                stats.burn_rate = Some(BurnRate {
-                    tokens_per_minute: (tokens_per_min * 100.0).round() / 100.0,
-                    cost_per_hour: (cost_per_hour * 10000.0).round() / 10000.0,
-                });
+                let cache_tokens = entry.cache_creation_tokens + entry.cache_read_tokens;
+                if !rates.has_cache_pricing() {
+                    impact.unpriced_cache_tokens += cache_tokens;
+                    continue;
+                }
+                impact.cache_creation_cost_usd +=
+                    per_million(entry.cache_creation_tokens, rates.cache_creation_5m);
+                impact.cache_read_cost_usd +=
+                    per_million(entry.cache_read_tokens, rates.cache_read);
+                impact.avoided_input_cost_usd += per_million(cache_tokens, rates.input);
             }
-        } else {
-            stats.time_to_reset_minutes = SESSION_DURATION_MINUTES as u32;
-        }
-    } else {
-        stats.time_to_reset_minutes = SESSION_DURATION_MINUTES as u32;
-    }
 
-    stats
+            impact.net_savings_usd = round(
+                impact.avoided_input_cost_usd
+                    - impact.cache_creation_cost_usd
+                    - impact.cache_read_cost_usd,
+            );
+            impact.fresh_input_cost_usd = round(impact.fresh_input_cost_usd);
+            impact.cache_creation_cost_usd = round(impact.cache_creation_cost_usd);
+            impact.cache_read_cost_usd = round(impact.cache_read_cost_usd);
+            impact.avoided_input_cost_usd = round(impact.avoided_input_cost_usd);
+            impact
+        })
+        .collect();
+
+    impacts.sort_by(|a, b| {
+        b.net_savings_usd
+            .total_cmp(&a.net_savings_usd)
+            .then_with(|| a.project_path.cmp(&b.project_path))
+    });
+    impacts
 }
 
-/// Get complete usage data
-pub fn get_usage_data(
-    custom_path: Option<&str>,
-    filter: &FilterOptions,
-) -> Result<UsageData, ReaderError> {
-    let pricing = PricingCalculator::new();
-    let all_data = load_all_entries(custom_path, &pricing)?;
+/// Calendar date in `tz` usage at `timestamp` is attributed to. Daily buckets and
+/// today's stats both derive dates here, so the latest daily entry matches today's stats.
+pub fn usage_date(timestamp: &DateTime<Utc>, tz: &UsageTimezone) -> NaiveDate {
+    tz.date_of(timestamp)
+}
 
-    let mut all_entries: Vec<UsageEntry> = Vec::new();
-    let mut projects: Vec<ProjectStats> = Vec::new();
+/// Calculate usage per ISO week (`2025-W03`), using each entry's date in `tz`
+pub fn calculate_weekly_usage<Tz: TimeZone>(
+    entries: &[UsageEntry],
+    tz: &Tz,
+    cost_decimals: u32,
+) -> Vec<PeriodUsage> {
+    calculate_period_usage(entries, tz, cost_decimals, |date| {
+        let week = date.iso_week();
+        format!("{:04}-W{:02}", week.year(), week.week())
+    })
+}
 
-    for (project, entries) in all_data {
-        // Apply filter
-        let filtered_entries: Vec<_> = entries
-            .into_iter()
-            .filter(|e| filter.matches(e, Some(&project.decoded_path)))
-            .collect();
+/// Calculate usage per billing month, using each entry's date in `tz`. Each period is
+/// labelled with the month its cycle starts in (`2025-01`); with `billing_cycle_start_day`
+/// 1 these are calendar months.
+pub fn calculate_monthly_usage<Tz: TimeZone>(
+    entries: &[UsageEntry],
+    tz: &Tz,
+    billing_cycle_start_day: u8,
+    cost_decimals: u32,
+) -> Vec<PeriodUsage> {
+    calculate_period_usage(entries, tz, cost_decimals, |date| {
+        let start = billing_cycle_start(date, billing_cycle_start_day);
+        format!("{:04}-{:02}", start.year(), start.month())
+    })
+}
 
-        if !filtered_entries.is_empty() {
-            all_entries.extend(filtered_entries.clone());
-            projects.push(calculate_project_stats(&project, &filtered_entries));
-        }
+/// First day of the billing cycle containing `date` for cycles starting on `start_day`.
+/// Months shorter than `start_day` begin their cycle on their last day.
+pub fn billing_cycle_start(date: NaiveDate, start_day: u8) -> NaiveDate {
+    let this_month = cycle_start_in_month(date, start_day);
+    if date >= this_month {
+        return this_month;
     }
+    date.checked_sub_months(chrono::Months::new(1))
+        .map(|previous| cycle_start_in_month(previous, start_day))
+        .unwrap_or(this_month)
+}
 
-    // Sort entries by timestamp for daily calculation
-    all_entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+/// Cycle start within the month of `date`, clamped to the month's length
+fn cycle_start_in_month(date: NaiveDate, start_day: u8) -> NaiveDate {
+    let first = date.with_day(1).unwrap_or(date);
+    let days_in_month = first
+        .checked_add_months(chrono::Months::new(1))
+        .map(|next| (next - first).num_days() as u32)
+        .unwrap_or(31);
+    let day = u32::from(start_day).clamp(1, days_in_month);
+    first.with_day(day).unwrap_or(first)
+}
 
-    let daily_usage = calculate_daily_usage(&all_entries);
-    let overall_stats = calculate_overall_stats(&projects, &all_entries);
+/// Calculate usage for each of the 24 hours of `date` in `tz`, including hours without
+/// activity. Entries are attributed by their date in `tz`, as `usage_date` does for today.
+pub fn calculate_hourly_usage<Tz: TimeZone>(
+    entries: &[UsageEntry],
+    date: NaiveDate,
+    tz: &Tz,
+    cost_decimals: u32,
+) -> Vec<HourlyUsage> {
+    let mut hours: Vec<HourlyUsage> = (0..24)
+        .map(|hour| HourlyUsage {
+            hour,
+            ..Default::default()
+        })
+        .collect();
 
-    // Sort projects by last activity (most recent first)
-    projects.sort_by(|a, b| {
-        let a_time = a.last_activity.as_deref().unwrap_or("");
-        let b_time = b.last_activity.as_deref().unwrap_or("");
-        b_time.cmp(a_time)
-    });
+    for entry in entries {
+        let local = entry.timestamp.with_timezone(tz);
+        if local.date_naive() != date {
+            continue;
+        }
+        let usage = &mut hours[local.hour() as usize];
+        usage.input_tokens += entry.input_tokens;
+        usage.output_tokens += entry.output_tokens;
+        usage.cache_creation_tokens += entry.cache_creation_tokens;
+        usage.cache_read_tokens += entry.cache_read_tokens;
+        usage.total_tokens += entry.input_tokens + entry.output_tokens;
+        usage.cost_usd += entry.cost_usd;
+        usage.message_count += 1;
+    }
 
-    Ok(UsageData {
-        projects,
-        daily_usage,
-        overall_stats,
-    })
+    for usage in &mut hours {
+        usage.cost_usd = round_cost(usage.cost_usd, cost_decimals);
+    }
+    hours
 }
 
-/// Get usage data for a specific project
-pub fn get_project_usage(
-    custom_path: Option<&str>,
-    project_path: &str,
-) -> Result<Option<ProjectStats>, ReaderError> {
-    let filter = FilterOptions::new().with_project(Some(project_path.to_string()));
-    let data = get_usage_data(custom_path, &filter)?;
+/// Sum usage per period key derived from each entry's local date, sorted by period
+fn calculate_period_usage<Tz, F>(
+    entries: &[UsageEntry],
+    tz: &Tz,
+    cost_decimals: u32,
+    period_key: F,
+) -> Vec<PeriodUsage>
+where
+    Tz: TimeZone,
+    F: Fn(NaiveDate) -> String,
+{
+    let mut periods: HashMap<String, PeriodUsage> = HashMap::new();
 
-    Ok(data.projects.into_iter().next())
+    for entry in entries {
+        let key = period_key(entry.timestamp.with_timezone(tz).date_naive());
+        let usage = periods.entry(key.clone()).or_insert_with(|| PeriodUsage {
+            period: key,
+            ..Default::default()
+        });
+
+        usage.input_tokens += entry.input_tokens;
+        usage.output_tokens += entry.output_tokens;
+        usage.cache_creation_tokens += entry.cache_creation_tokens;
+        usage.cache_read_tokens += entry.cache_read_tokens;
+        usage.cost_usd += entry.cost_usd;
+        usage.message_count += 1;
+    }
+
+    let mut result: Vec<_> = periods
+        .into_values()
+        .map(|mut usage| {
+            usage.cost_usd = round_cost(usage.cost_usd, cost_decimals);
+            usage
+        })
+        .collect();
+    result.sort_by(|a, b| a.period.cmp(&b.period));
+    result
 }
 
-/// Get daily usage for a specific date range
+/// Sum usage recorded strictly after `last_viewed_at`
+pub fn calculate_usage_since(
+    entries: &[UsageEntry],
+    last_viewed_at: DateTime<Utc>,
+    cost_decimals: u32,
+) -> UsageSinceLastViewed {
+    let mut usage = UsageSinceLastViewed {
+        last_viewed_at: Some(last_viewed_at.to_rfc3339()),
+        ..Default::default()
+    };
+
+    for entry in entries.iter().filter(|e| e.timestamp > last_viewed_at) {
+        usage.input_tokens += entry.input_tokens;
+        usage.output_tokens += entry.output_tokens;
+        usage.cache_creation_tokens += entry.cache_creation_tokens;
+        usage.cache_read_tokens += entry.cache_read_tokens;
+        usage.cost_usd += entry.cost_usd;
+        usage.message_count += 1;
+    }
+    usage.total_tokens = usage.input_tokens
+        + usage.output_tokens
+        + usage.cache_creation_tokens
+        + usage.cache_read_tokens;
+    usage.cost_usd = round_cost(usage.cost_usd, cost_decimals);
+    usage
+}
+
+/// Days of recent history compared against the month-to-date rate for the forecast band
+const FORECAST_RECENT_DAYS: i64 = 7;
+
+/// Forecast cost and tokens at the end of the current billing month by extrapolating the
+/// period-to-date daily average linearly. The low/high band applies the slower and
+/// faster of the last 7 days' and the period-to-date daily cost rates.
+pub fn forecast_month_end(
+    entries: &[UsageEntry],
+    now: &DateTime<Utc>,
+    tz: &UsageTimezone,
+    billing_cycle_start_day: u8,
+    cost_decimals: u32,
+) -> Forecast {
+    let today = usage_date(now, tz);
+    let start = billing_cycle_start(today, billing_cycle_start_day);
+    let next_start = start
+        .checked_add_months(chrono::Months::new(1))
+        .map(|next| cycle_start_in_month(next, billing_cycle_start_day))
+        .unwrap_or(start + chrono::Duration::days(31));
+    let recent_start = today - chrono::Duration::days(FORECAST_RECENT_DAYS - 1);
+
+    let mut cost_to_date = 0.0;
+    let mut tokens_to_date: u64 = 0;
+    let mut recent_cost = 0.0;
+    for entry in entries {
+        let date = usage_date(&entry.timestamp, tz);
+        if date > today {
+            continue;
+        }
+        if date >= start {
+            cost_to_date += entry.cost_usd;
+            tokens_to_date += entry.input_tokens + entry.output_tokens;
+        }
+        if date >= recent_start {
+            recent_cost += entry.cost_usd;
+        }
+    }
+
+    let days_elapsed = (today - start).num_days() + 1;
+    let days_remaining = (next_start - today).num_days() - 1;
+    let daily_cost = cost_to_date / days_elapsed as f64;
+    let daily_tokens = tokens_to_date as f64 / days_elapsed as f64;
+    let recent_daily_cost = recent_cost / FORECAST_RECENT_DAYS as f64;
+
+    let project = |rate: f64| {
+        let cost = cost_to_date + rate * days_remaining as f64;
+        round_cost(cost, cost_decimals)
+    };
+
+    Forecast {
+        period_start: start.format("%Y-%m-%d").to_string(),
+        period_end: (next_start - chrono::Duration::days(1)).format("%Y-%m-%d").to_string(),
+        days_elapsed: days_elapsed as u32,
+        days_remaining: days_remaining as u32,
+        cost_to_date: round_cost(cost_to_date, cost_decimals),
+        tokens_to_date,
+        projected_cost: project(daily_cost),
+        projected_tokens: tokens_to_date + (daily_tokens * days_remaining as f64).round() as u64,
+        projected_cost_low: project(daily_cost.min(recent_daily_cost)),
+        projected_cost_high: project(daily_cost.max(recent_daily_cost)),
+    }
+}
+
+/// Keep only days within the `max_days` most recent calendar days ending at `today`
+pub fn trim_daily_history(
+    daily_usage: Vec<DailyUsage>,
+    max_days: Option<u32>,
+    today: NaiveDate,
+) -> Vec<DailyUsage> {
+    let Some(max_days) = max_days else {
+        return daily_usage;
+    };
+
+    let cutoff = today - chrono::Duration::days(max_days as i64 - 1);
+    daily_usage
+        .into_iter()
+        .filter(|d| {
+            NaiveDate::parse_from_str(&d.date, "%Y-%m-%d")
+                .map(|date| date >= cutoff)
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// Fill missing days between the first and last day of `daily_usage` with empty entries.
+/// Input must be sorted by date, as in `UsageData.daily_usage`.
+pub fn fill_daily_gaps(daily_usage: &[DailyUsage]) -> Vec<DailyUsage> {
+    let parse = |d: &DailyUsage| NaiveDate::parse_from_str(&d.date, "%Y-%m-%d").ok();
+    let (first, last) = match (
+        daily_usage.first().and_then(parse),
+        daily_usage.last().and_then(parse),
+    ) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return daily_usage.to_vec(),
+    };
+
+    fill_daily_range(daily_usage, first, last)
+}
+
+/// Longest window, in days, of recent daily usage that can be requested (about ten years)
+pub const MAX_RECENT_DAYS: u32 = 3650;
+
+/// First local date of the `days`-day window ending on `today` (a window is at least one
+/// day). Windows reaching past the earliest representable date start there.
+pub fn recent_days_start(today: NaiveDate, days: u32) -> NaiveDate {
+    today
+        .checked_sub_signed(chrono::Duration::days(days.max(1) as i64 - 1))
+        .unwrap_or(NaiveDate::MIN)
+}
+
+/// Daily usage for the `days` local dates ending on `today`, oldest first. With `fill_gaps`
+/// every date in the window is present, empty where there was no activity.
+pub fn recent_daily_usage(
+    daily_usage: &[DailyUsage],
+    today: NaiveDate,
+    days: u32,
+    fill_gaps: bool,
+) -> Vec<DailyUsage> {
+    let first = recent_days_start(today, days);
+    if fill_gaps {
+        return fill_daily_range(daily_usage, first, today);
+    }
+
+    daily_usage
+        .iter()
+        .filter(|d| {
+            NaiveDate::parse_from_str(&d.date, "%Y-%m-%d")
+                .is_ok_and(|date| date >= first && date <= today)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Usage on `date`, or an empty entry for that date if there was none
+pub fn daily_usage_on(daily_usage: &[DailyUsage], date: NaiveDate) -> DailyUsage {
+    fill_daily_range(daily_usage, date, date)
+        .pop()
+        .unwrap_or_default()
+}
+
+/// One entry per date from `first` to `last` inclusive, taken from `daily_usage` or empty
+fn fill_daily_range(
+    daily_usage: &[DailyUsage],
+    first: NaiveDate,
+    last: NaiveDate,
+) -> Vec<DailyUsage> {
+    let by_date: HashMap<&str, &DailyUsage> =
+        daily_usage.iter().map(|d| (d.date.as_str(), d)).collect();
+
+    first
+        .iter_days()
+        .take_while(|date| *date <= last)
+        .map(|date| {
+            let key = date.format("%Y-%m-%d").to_string();
+            match by_date.get(key.as_str()) {
+                Some(daily) => (*daily).clone(),
+                None => DailyUsage {
+                    date: key,
+                    ..Default::default()
+                },
+            }
+        })
+        .collect()
+}
+
+/// Calculate each day's cost alongside its trailing `window`-day moving average.
+/// Missing days are filled with zero cost so gaps lower the average rather than
+/// being skipped.
+pub fn calculate_daily_cost_moving_average(
+    daily_usage: &[DailyUsage],
+    window: usize,
+    cost_decimals: u32,
+) -> Vec<DailyCostAverage> {
+    let filled = fill_daily_gaps(daily_usage);
+    let costs: Vec<f64> = filled.iter().map(|d| d.cost_usd).collect();
+
+    filled
+        .into_iter()
+        .enumerate()
+        .map(|(i, daily)| {
+            let moving_average = if window > 0 && i + 1 >= window {
+                let sum: f64 = costs[i + 1 - window..=i].iter().sum();
+                Some(round_cost(sum / window as f64, cost_decimals))
+            } else {
+                None
+            };
+            DailyCostAverage {
+                date: daily.date,
+                cost_usd: daily.cost_usd,
+                moving_average,
+            }
+        })
+        .collect()
+}
+
+/// First day of the period containing `date` (weeks start on Monday)
+pub fn period_start(date: NaiveDate, granularity: Granularity) -> NaiveDate {
+    match granularity {
+        Granularity::Day => date,
+        Granularity::Week => {
+            date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64)
+        }
+        Granularity::Month => date.with_day(1).unwrap_or(date),
+    }
+}
+
+/// Calculate the blended cost per million tokens for each period, oldest first.
+/// Cache tokens count toward the total, so heavier caching lowers the rate.
+/// Periods without tokens are omitted. Entries are dated in `tz`.
+pub fn calculate_blended_rate_trend(
+    entries: &[UsageEntry],
+    granularity: Granularity,
+    tz: &UsageTimezone,
+    cost_decimals: u32,
+) -> Vec<BlendedRatePoint> {
+    let mut periods: HashMap<NaiveDate, (u64, f64)> = HashMap::new();
+
+    for entry in entries {
+        let start = period_start(usage_date(&entry.timestamp, tz), granularity);
+        let period = periods.entry(start).or_insert((0, 0.0));
+        period.0 += entry.input_tokens
+            + entry.output_tokens
+            + entry.cache_creation_tokens
+            + entry.cache_read_tokens;
+        period.1 += entry.cost_usd;
+    }
+
+    let mut trend: Vec<_> = periods
+        .into_iter()
+        .filter(|(_, (tokens, _))| *tokens > 0)
+        .map(|(start, (total_tokens, cost_usd))| {
+            let rate = cost_usd / total_tokens as f64 * 1_000_000.0;
+            BlendedRatePoint {
+                period_start: start.format("%Y-%m-%d").to_string(),
+                total_tokens,
+                cost_usd: round_cost(cost_usd, cost_decimals),
+                cost_per_million_tokens: round_cost(rate, cost_decimals),
+            }
+        })
+        .collect();
+
+    trend.sort_by(|a, b| a.period_start.cmp(&b.period_start));
+    trend
+}
+
+/// First day of the period following the one starting at `start`
+fn next_period_start(start: NaiveDate, granularity: Granularity) -> NaiveDate {
+    match granularity {
+        Granularity::Day => start + chrono::Duration::days(1),
+        Granularity::Week => start + chrono::Duration::days(7),
+        Granularity::Month => start
+            .checked_add_months(chrono::Months::new(1))
+            .unwrap_or(start + chrono::Duration::days(31)),
+    }
+}
+
+/// Calculate the average cost per message for every period from the first to the last
+/// period with usage, oldest first. A rising average suggests growing context ("prompt
+/// bloat"). Periods without messages are included with no average. Entries are dated
+/// in `tz`.
+pub fn calculate_avg_message_cost_trend(
+    entries: &[UsageEntry],
+    granularity: Granularity,
+    tz: &UsageTimezone,
+    cost_decimals: u32,
+) -> Vec<MessageCostPoint> {
+    let mut periods: HashMap<NaiveDate, (u32, f64)> = HashMap::new();
+
+    for entry in entries {
+        let start = period_start(usage_date(&entry.timestamp, tz), granularity);
+        let period = periods.entry(start).or_insert((0, 0.0));
+        period.0 += 1;
+        period.1 += entry.cost_usd;
+    }
+
+    let (Some(first), Some(last)) = (periods.keys().min().copied(), periods.keys().max().copied())
+    else {
+        return Vec::new();
+    };
+
+    let mut trend = Vec::new();
+    let mut start = first;
+    while start <= last {
+        let (message_count, cost_usd) = periods.get(&start).copied().unwrap_or((0, 0.0));
+        let avg_cost_per_message = (message_count > 0).then(|| {
+            round_cost(cost_usd / message_count as f64, cost_decimals)
+        });
+
+        trend.push(MessageCostPoint {
+            period_start: start.format("%Y-%m-%d").to_string(),
+            message_count,
+            cost_usd: round_cost(cost_usd, cost_decimals),
+            avg_cost_per_message,
+        });
+        start = next_period_start(start, granularity);
+    }
+
+    trend
+}
+
+/// Label for usage that falls outside every requested time bucket
+pub const UNASSIGNED_BUCKET_LABEL: &str = "unassigned";
+
+/// Validate time-of-day buckets: hours must be within a day and ranges non-empty
+pub fn validate_time_buckets(buckets: &[TimeBucket]) -> Result<(), String> {
+    for bucket in buckets {
+        if bucket.start_hour > 23 || bucket.end_hour > 24 {
+            return Err(format!(
+                "Bucket '{}' has hours outside 0-24: {}-{}",
+                bucket.label, bucket.start_hour, bucket.end_hour
+            ));
+        }
+        if bucket.start_hour == bucket.end_hour {
+            return Err(format!("Bucket '{}' has an empty hour range", bucket.label));
+        }
+    }
+    Ok(())
+}
+
+/// Split usage into labeled time-of-day buckets using each entry's hour in `tz`.
+/// Overlapping buckets are resolved in order: an entry counts toward the first bucket
+/// that contains it. Entries covered by no bucket are reported under
+/// `UNASSIGNED_BUCKET_LABEL`, which is only present when non-empty.
+pub fn calculate_time_bucket_usage<Tz: TimeZone>(
+    entries: &[UsageEntry],
+    buckets: &[TimeBucket],
+    tz: &Tz,
+    cost_decimals: u32,
+) -> Vec<TimeBucketUsage> {
+    let mut results: Vec<TimeBucketUsage> = buckets
+        .iter()
+        .map(|b| TimeBucketUsage {
+            label: b.label.clone(),
+            ..Default::default()
+        })
+        .collect();
+    let mut unassigned = TimeBucketUsage {
+        label: UNASSIGNED_BUCKET_LABEL.to_string(),
+        ..Default::default()
+    };
+
+    for entry in entries {
+        let hour = entry.timestamp.with_timezone(tz).hour();
+        let usage = match buckets.iter().position(|b| b.contains(hour)) {
+            Some(index) => &mut results[index],
+            None => &mut unassigned,
+        };
+
+        usage.input_tokens += entry.input_tokens;
+        usage.output_tokens += entry.output_tokens;
+        usage.cache_creation_tokens += entry.cache_creation_tokens;
+        usage.cache_read_tokens += entry.cache_read_tokens;
+        usage.cost_usd += entry.cost_usd;
+        usage.message_count += 1;
+    }
+
+    if unassigned.message_count > 0 {
+        results.push(unassigned);
+    }
+
+    for usage in &mut results {
+        usage.cost_usd = round_cost(usage.cost_usd, cost_decimals);
+    }
+
+    results
+}
+
+/// Calculate mean, standard deviation and coefficient of variation of daily cost
+/// over the `window_days` days before `today` (today itself is excluded).
+/// Days without activity count as zero spend, but the window never starts
+/// before the first recorded day so new users aren't penalized for empty history.
+pub fn calculate_spend_stability(
+    daily_usage: &[DailyUsage],
+    today: NaiveDate,
+    window_days: u32,
+    cost_decimals: u32,
+) -> SpendStability {
+    let window_start = today - chrono::Duration::days(window_days as i64);
+
+    let costs_by_date: HashMap<NaiveDate, f64> = daily_usage
+        .iter()
+        .filter_map(|d| {
+            NaiveDate::parse_from_str(&d.date, "%Y-%m-%d")
+                .ok()
+                .map(|date| (date, d.cost_usd))
+        })
+        .filter(|(date, _)| *date >= window_start && *date < today)
+        .collect();
+
+    let mut stability = SpendStability {
+        window_days,
+        days_counted: 0,
+        mean_daily_cost: 0.0,
+        std_dev_daily_cost: 0.0,
+        coefficient_of_variation: 0.0,
+        classification: SpendClassification::InsufficientData,
+    };
+
+    let first_day = match costs_by_date.keys().min() {
+        Some(first) => *first,
+        None => return stability,
+    };
+
+    // Fill gaps with zero spend from the first active day up to yesterday
+    let costs: Vec<f64> = first_day
+        .iter_days()
+        .take_while(|date| *date < today)
+        .map(|date| costs_by_date.get(&date).copied().unwrap_or(0.0))
+        .collect();
+
+    let n = costs.len() as f64;
+    let mean = costs.iter().sum::<f64>() / n;
+    let variance = costs.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / n;
+    let std_dev = variance.sqrt();
+    let cv = if mean > 0.0 { std_dev / mean } else { 0.0 };
+
+    stability.days_counted = costs.len() as u32;
+    stability.mean_daily_cost = round_cost(mean, cost_decimals);
+    stability.std_dev_daily_cost = round_cost(std_dev, cost_decimals);
+    stability.coefficient_of_variation = (cv * 10000.0).round() / 10000.0;
+
+    if costs.len() >= MIN_STABILITY_DAYS {
+        stability.classification = if cv < STABLE_CV_THRESHOLD {
+            SpendClassification::Stable
+        } else if cv < VARIABLE_CV_THRESHOLD {
+            SpendClassification::Variable
+        } else {
+            SpendClassification::Erratic
+        };
+    }
+
+    stability
+}
+
+/// Cache read tokens as a percentage of cache read + fresh input tokens (0 when both are 0)
+fn cache_hit_percent(cache_read_tokens: u64, input_tokens: u64) -> f64 {
+    let denominator = cache_read_tokens + input_tokens;
+    if denominator == 0 {
+        return 0.0;
+    }
+    (cache_read_tokens as f64 / denominator as f64 * 10000.0).round() / 100.0
+}
+
+/// Fill in cache hit rates and the savings of cache reads over the full input rate,
+/// per model and overall. Models without cache pricing contribute no savings.
+pub(crate) fn apply_cache_efficiency(
+    stats: &mut OverallStats,
+    pricing: &PricingCalculator,
+    cost_decimals: u32,
+) {
+    let mut savings = 0.0;
+    for model in &mut stats.model_distribution {
+        model.cache_hit_percent = cache_hit_percent(model.cache_read_tokens, model.input_tokens);
+        let rates = pricing.get_pricing(&model.model);
+        let model_savings = if rates.has_cache_pricing() {
+            model.cache_read_tokens as f64 / 1_000_000.0 * (rates.input - rates.cache_read)
+        } else {
+            0.0
+        };
+        model.cache_savings_usd = round_cost(model_savings, cost_decimals);
+        savings += model_savings;
+    }
+
+    stats.cache_hit_percent = cache_hit_percent(stats.cache_read_tokens, stats.total_input_tokens);
+    stats.cache_savings_usd = round_cost(savings, cost_decimals);
+}
+
+/// Turn accumulated per-model totals into the sorted distribution with percentages
+fn finish_model_distribution(
+    model_map: HashMap<String, ModelStats>,
+    total_tokens: u64,
+    cost_decimals: u32,
+) -> Vec<ModelStats> {
+    // Calculate percentages and round costs
+    let mut model_list: Vec<_> = model_map
+        .into_values()
+        .map(|mut m| {
+            m.percentage = if total_tokens > 0 {
+                (m.total_tokens as f64 / total_tokens as f64) * 100.0
+            } else {
+                0.0
+            };
+            m.cost_usd = round_cost(m.cost_usd, cost_decimals);
+            m.percentage = (m.percentage * 100.0).round() / 100.0;
+            m
+        })
+        .collect();
+
+    // Sort by total tokens descending
+    model_list.sort_by_key(|m| std::cmp::Reverse(m.total_tokens));
+    model_list
+}
+
+/// Compensated (Neumaier) sum of costs, so totals over hundreds of thousands of small
+/// per-entry costs do not drift the way a plain `f64` running sum does
+#[derive(Debug, Default, Clone, Copy)]
+struct CostSum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl CostSum {
+    fn add(&mut self, value: f64) {
+        let total = self.sum + value;
+        if self.sum.abs() >= value.abs() {
+            self.compensation += (self.sum - total) + value;
+        } else {
+            self.compensation += (value - total) + self.sum;
+        }
+        self.sum = total;
+    }
+
+    fn value(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+/// Running daily, model and today aggregates for a single pass over entries.
+/// Only entries recent enough to affect session timing and burn rate are retained, plus
+/// the timestamp of every entry to find where the recent session blocks begin.
+struct UsageAggregator {
+    session: SessionConfig,
+    timezone: UsageTimezone,
+    daily: HashMap<String, DailyUsage>,
+    models: HashMap<String, ModelStats>,
+    model_tokens: u64,
+    /// Cost of every entry, summed before any rounding
+    total_cost: CostSum,
+    today: TodayStats,
+    today_local: NaiveDate,
+    recent_since: DateTime<Utc>,
+    recent: Vec<UsageEntry>,
+    timestamps: Vec<DateTime<Utc>>,
+}
+
+impl UsageAggregator {
+    fn new(now: &DateTime<Utc>, session: &SessionConfig, tz: &UsageTimezone) -> Self {
+        Self {
+            session: *session,
+            timezone: *tz,
+            daily: HashMap::new(),
+            models: HashMap::new(),
+            model_tokens: 0,
+            total_cost: CostSum::default(),
+            today: Default::default(),
+            today_local: usage_date(now, tz),
+            // Session blocks overlapping the last hour start at most one session
+            // duration plus an hour ago
+            recent_since: *now - session.duration() - chrono::Duration::hours(1),
+            recent: Vec::new(),
+            timestamps: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, entry: &UsageEntry) {
+        self.total_cost.add(entry.cost_usd);
+
+        let date = usage_date(&entry.timestamp, &self.timezone);
+        let date_key = format!("{:04}-{:02}-{:02}", date.year(), date.month(), date.day());
+        let daily = self.daily.entry(date_key.clone()).or_insert_with(|| DailyUsage {
+            date: date_key,
+            ..Default::default()
+        });
+        daily.input_tokens += entry.input_tokens;
+        daily.output_tokens += entry.output_tokens;
+        daily.cache_creation_tokens += entry.cache_creation_tokens;
+        daily.cache_read_tokens += entry.cache_read_tokens;
+        daily.cost_usd += entry.cost_usd;
+        daily.message_count += 1;
+
+        let model_key = normalize_model_name(&entry.model);
+        let entry_total = entry.input_tokens + entry.output_tokens;
+        self.model_tokens += entry_total;
+        let model = self.models.entry(model_key.clone()).or_insert_with(|| ModelStats {
+            model: model_key,
+            ..Default::default()
+        });
+        model.input_tokens += entry.input_tokens;
+        model.output_tokens += entry.output_tokens;
+        model.cache_creation_tokens += entry.cache_creation_tokens;
+        model.cache_read_tokens += entry.cache_read_tokens;
+        model.cost_usd += entry.cost_usd;
+        model.message_count += 1;
+        model.total_tokens += entry_total;
+
+        // Today's stats use the same date as the daily bucket
+        if date == self.today_local {
+            self.today.input_tokens += entry.input_tokens;
+            self.today.output_tokens += entry.output_tokens;
+            self.today.cost_usd += entry.cost_usd;
+            self.today.message_count += 1;
+        }
+
+        self.timestamps.push(entry.timestamp);
+        if entry.timestamp >= self.recent_since {
+            self.recent.push(entry.clone());
+        }
+    }
+}
+
+/// Usage aggregates folded in one entry at a time, project by project, so callers can
+/// stream entries in without collecting them. `calculate_usage_data`, `get_usage_data`
+/// and the cache all fold through it, so usage totals are computed in one place.
+pub(crate) struct UsageFold {
+    aggregator: UsageAggregator,
+    projects: Vec<ProjectStats>,
+    /// Stats of the project entries are currently added to
+    current: Option<ProjectStats>,
+    cost_decimals: u32,
+}
+
+impl UsageFold {
+    pub(crate) fn new(
+        now: &DateTime<Utc>,
+        session: &SessionConfig,
+        tz: &UsageTimezone,
+        cost_decimals: u32,
+    ) -> Self {
+        Self {
+            aggregator: UsageAggregator::new(now, session, tz),
+            projects: Vec::new(),
+            current: None,
+            cost_decimals,
+        }
+    }
+
+    /// Add the following entries to `project`; the previous project is finished and
+    /// kept if it had any entries
+    pub(crate) fn start_project(&mut self, project: &ProjectData) {
+        self.finish_project();
+        self.current = Some(new_project_stats(project));
+    }
+
+    pub(crate) fn add(&mut self, entry: &UsageEntry) {
+        self.aggregator.add(entry);
+        if let Some(stats) = self.current.as_mut() {
+            add_project_entry(stats, entry);
+        }
+    }
+
+    fn finish_project(&mut self) {
+        if let Some(mut stats) = self.current.take().filter(|stats| stats.message_count > 0) {
+            finish_project_stats(&mut stats, self.cost_decimals);
+            self.projects.push(stats);
+        }
+    }
+
+    pub(crate) fn finish(
+        mut self,
+        now: &DateTime<Utc>,
+        min_block_minutes: f64,
+        pricing: &PricingCalculator,
+    ) -> UsageData {
+        self.finish_project();
+        finish_usage_data(
+            self.aggregator,
+            self.projects,
+            now,
+            min_block_minutes,
+            pricing,
+            self.cost_decimals,
+        )
+    }
+}
+
+/// Calculate UsageData from entries already loaded per project
+#[cfg(test)]
+pub(crate) fn calculate_usage_data(
+    all_data: Vec<(ProjectData, Vec<UsageEntry>)>,
+    now: &DateTime<Utc>,
+    min_block_minutes: f64,
+    session: &SessionConfig,
+    tz: &UsageTimezone,
+    pricing: &PricingCalculator,
+    cost_decimals: u32,
+) -> UsageData {
+    let mut fold = UsageFold::new(now, session, tz, cost_decimals);
+
+    for (project, entries) in &all_data {
+        fold.start_project(project);
+        for entry in entries {
+            fold.add(entry);
+        }
+    }
+
+    fold.finish(now, min_block_minutes, pricing)
+}
+
+fn new_project_stats(project: &ProjectData) -> ProjectStats {
+    ProjectStats {
+        project_path: project.decoded_path.clone(),
+        display_name: project.display_name.clone(),
+        session_count: project.session_files.len() as u32,
+        ..Default::default()
+    }
+}
+
+fn add_project_entry(stats: &mut ProjectStats, entry: &UsageEntry) {
+    stats.total_input_tokens += entry.input_tokens;
+    stats.total_output_tokens += entry.output_tokens;
+    stats.cache_creation_tokens += entry.cache_creation_tokens;
+    stats.cache_read_tokens += entry.cache_read_tokens;
+    stats.total_cost_usd += entry.cost_usd;
+    stats.message_count += 1;
+
+    let ts = entry.timestamp.to_rfc3339();
+    match &stats.first_activity {
+        None => stats.first_activity = Some(ts.clone()),
+        Some(first) if ts < *first => stats.first_activity = Some(ts.clone()),
+        _ => {}
+    }
+    match &stats.last_activity {
+        None => stats.last_activity = Some(ts.clone()),
+        Some(last) if ts > *last => stats.last_activity = Some(ts.clone()),
+        _ => {}
+    }
+}
+
+/// Round the project's cost and fill in its per-message averages
+fn finish_project_stats(stats: &mut ProjectStats, cost_decimals: u32) {
+    (stats.avg_cost_per_message, stats.avg_tokens_per_message) = per_message_averages(
+        stats.total_cost_usd,
+        stats.total_input_tokens + stats.total_output_tokens,
+        stats.message_count,
+        cost_decimals,
+    );
+    stats.total_cost_usd = round_cost(stats.total_cost_usd, cost_decimals);
+}
+
+/// Build the final UsageData from the aggregates and per-project stats
+fn finish_usage_data(
+    aggregator: UsageAggregator,
+    mut projects: Vec<ProjectStats>,
+    now: &DateTime<Utc>,
+    min_block_minutes: f64,
+    pricing: &PricingCalculator,
+    cost_decimals: u32,
+) -> UsageData {
+    let UsageAggregator {
+        session,
+        daily,
+        models,
+        model_tokens,
+        total_cost,
+        today: mut today_stats,
+        recent_since,
+        mut recent,
+        mut timestamps,
+        ..
+    } = aggregator;
+
+    let mut daily_usage: Vec<_> = daily
+        .into_values()
+        .map(|mut d| {
+            d.cost_usd = round_cost(d.cost_usd, cost_decimals);
+            d
+        })
+        .collect();
+    daily_usage.sort_by(|a, b| a.date.cmp(&b.date));
+
+    // Calculate overall stats
+    // The total cost comes from the per-entry costs, not the rounded project totals
+    let mut overall_stats = OverallStats {
+        project_count: projects.len() as u32,
+        total_cost_usd: total_cost.value(),
+        ..Default::default()
+    };
+
+    for project in &projects {
+        overall_stats.total_input_tokens += project.total_input_tokens;
+        overall_stats.total_output_tokens += project.total_output_tokens;
+        overall_stats.cache_creation_tokens += project.cache_creation_tokens;
+        overall_stats.cache_read_tokens += project.cache_read_tokens;
+        overall_stats.total_messages += project.message_count;
+        overall_stats.total_sessions += project.session_count;
+    }
+    (overall_stats.avg_cost_per_message, overall_stats.avg_tokens_per_message) =
+        per_message_averages(
+            overall_stats.total_cost_usd,
+            overall_stats.total_input_tokens + overall_stats.total_output_tokens,
+            overall_stats.total_messages,
+            cost_decimals,
+        );
+    overall_stats.total_cost_usd = round_cost(overall_stats.total_cost_usd, cost_decimals);
+
+    overall_stats.model_distribution =
+        finish_model_distribution(models, model_tokens, cost_decimals);
+    apply_cache_efficiency(&mut overall_stats, pricing, cost_decimals);
+
+    today_stats.total_tokens = today_stats.input_tokens + today_stats.output_tokens;
+    today_stats.cost_usd = round_cost(today_stats.cost_usd, cost_decimals);
+    overall_stats.today_stats = today_stats;
+
+    // Calculate session timing and burn rate
+    let window_start = *now - session.duration();
+    recent.sort_by_key(|e| e.timestamp);
+
+    match recent.iter().find(|e| e.timestamp >= window_start) {
+        Some(first_entry) => {
+            let session_block_start = hour_start(&first_entry.timestamp);
+
+            overall_stats.session_start_time = Some(session_block_start.to_rfc3339());
+            overall_stats.time_to_reset_minutes =
+                calculate_time_to_reset(Some(&session_block_start), now, &session);
+
+            // Calculate hourly burn rate using block-based proportional allocation.
+            // Blocks starting before `recent_since` end before the last hour, so only
+            // blocks from the first one starting after it are built, at the same
+            // boundaries as over the full history.
+            timestamps.sort_unstable();
+            let first_block_entry = first_block_entry_since(&timestamps, &recent_since, &session);
+            let first = first_block_entry.map_or(recent.len(), |first| {
+                recent.partition_point(|e| e.timestamp < first)
+            });
+            let blocks = transform_to_blocks(&recent[first..], now, &session);
+            let (tokens_per_min, cost_per_hour) =
+                calculate_hourly_burn_rate(&blocks, now, min_block_minutes);
+
+            if tokens_per_min > 0.0 {
+                overall_stats.burn_rate = Some(BurnRate {
+                    tokens_per_minute: (tokens_per_min * 100.0).round() / 100.0,
+                    cost_per_hour: round_cost(
+                        cost_per_hour,
+                        cost_decimals.min(BURN_RATE_COST_DECIMALS),
+                    ),
+                });
+            }
+        }
+        None => {
+            overall_stats.time_to_reset_minutes = session.duration_minutes;
+        }
+    }
+
+    // Sort projects by last activity
+    projects.sort_by(|a, b| {
+        let a_time = a.last_activity.as_deref().unwrap_or("");
+        let b_time = b.last_activity.as_deref().unwrap_or("");
+        b_time.cmp(a_time)
+    });
+
+    UsageData {
+        projects,
+        daily_usage,
+        overall_stats,
+        source_info: None,
+    }
+}
+
+/// Split a timeline into work sessions wherever consecutive messages are more than
+/// `gap_minutes` apart. Entries must be sorted by timestamp.
+pub fn split_work_sessions(
+    entries: &[UsageEntry],
+    gap_minutes: u32,
+    cost_decimals: u32,
+) -> Vec<WorkSession> {
+    let gap = chrono::Duration::minutes(gap_minutes as i64);
+    let mut sessions = Vec::new();
+    let mut current: Option<(DateTime<Utc>, DateTime<Utc>, u32, f64)> = None;
+
+    let finish = |(start, end, message_count, cost_usd): (DateTime<Utc>, DateTime<Utc>, u32, f64)| {
+        WorkSession {
+            start_time: start.to_rfc3339(),
+            end_time: end.to_rfc3339(),
+            duration_minutes: (end - start).num_seconds() as f64 / 60.0,
+            message_count,
+            cost_usd: round_cost(cost_usd, cost_decimals),
+        }
+    };
+
+    for entry in entries {
+        current = match current {
+            Some((start, end, count, cost)) if entry.timestamp - end <= gap => {
+                Some((start, entry.timestamp, count + 1, cost + entry.cost_usd))
+            }
+            previous => {
+                sessions.extend(previous.map(finish));
+                Some((entry.timestamp, entry.timestamp, 1, entry.cost_usd))
+            }
+        };
+    }
+    sessions.extend(current.map(finish));
+
+    sessions
+}
+
+/// Build work sessions per project and overall from per-project entries
+pub fn calculate_work_sessions(
+    project_entries: &[(ProjectData, Vec<UsageEntry>)],
+    gap_minutes: u32,
+    cost_decimals: u32,
+) -> WorkSessionReport {
+    let total_minutes =
+        |sessions: &[WorkSession]| sessions.iter().map(|s| s.duration_minutes).sum();
+
+    let mut projects: Vec<ProjectWorkSessions> = project_entries
+        .iter()
+        .filter(|(_, entries)| !entries.is_empty())
+        .map(|(project, entries)| {
+            let mut sorted = entries.clone();
+            sorted.sort_by_key(|e| e.timestamp);
+            let sessions = split_work_sessions(&sorted, gap_minutes, cost_decimals);
+            ProjectWorkSessions {
+                project_path: project.decoded_path.clone(),
+                display_name: project.display_name.clone(),
+                session_count: sessions.len() as u32,
+                total_duration_minutes: total_minutes(&sessions),
+                sessions,
+            }
+        })
+        .collect();
+    projects.sort_by_key(|p| std::cmp::Reverse(p.session_count));
+
+    let mut all_entries: Vec<UsageEntry> = project_entries
+        .iter()
+        .flat_map(|(_, entries)| entries.iter().cloned())
+        .collect();
+    all_entries.sort_by_key(|e| e.timestamp);
+    let sessions = split_work_sessions(&all_entries, gap_minutes, cost_decimals);
+
+    WorkSessionReport {
+        gap_minutes,
+        session_count: sessions.len() as u32,
+        total_duration_minutes: total_minutes(&sessions),
+        sessions,
+        projects,
+    }
+}
+
+/// Get work sessions separated by idle gaps longer than `gap_minutes`
+pub fn get_work_sessions(
+    custom_path: Option<&str>,
+    filter: &FilterOptions,
+    gap_minutes: u32,
+) -> Result<WorkSessionReport, ReaderError> {
+    let project_entries = load_filtered_project_entries(custom_path, filter, &filter.pricing())?;
+    Ok(calculate_work_sessions(&project_entries, gap_minutes, filter.cost_decimals()))
+}
+
+/// Get the cost impact of prompt caching per project
+pub fn get_project_cache_cost_impact(
+    custom_path: Option<&str>,
+    filter: &FilterOptions,
+) -> Result<Vec<ProjectCacheCostImpact>, ReaderError> {
+    let pricing = filter.pricing();
+    let project_entries = load_filtered_project_entries(custom_path, filter, &pricing)?;
+    Ok(calculate_project_cache_cost_impact(&project_entries, &pricing, filter.cost_decimals()))
+}
+
+/// Load entries per project, keeping only those matching `filter`
+fn load_filtered_project_entries(
+    custom_path: Option<&str>,
+    filter: &FilterOptions,
+    pricing: &PricingCalculator,
+) -> Result<Vec<(ProjectData, Vec<UsageEntry>)>, ReaderError> {
+    let all_data = load_all_entries_with_options(custom_path, &filter.scan, pricing)?;
+    Ok(all_data
+        .into_iter()
+        .map(|(project, entries)| {
+            let entries = entries
+                .into_iter()
+                .filter(|e| filter.matches(e, Some(&project.decoded_path)))
+                .collect();
+            (project, entries)
+        })
+        .collect())
+}
+
+/// Get complete usage data
+pub fn get_usage_data(
+    custom_path: Option<&str>,
+    filter: &FilterOptions,
+) -> Result<UsageData, ReaderError> {
+    get_usage_data_with_clock(custom_path, filter, &SystemClock)
+}
+
+/// Get complete usage data, evaluating time-dependent metrics against `clock`
+pub fn get_usage_data_with_clock(
+    custom_path: Option<&str>,
+    filter: &FilterOptions,
+    clock: &dyn Clock,
+) -> Result<UsageData, ReaderError> {
+    let pricing = filter.pricing();
+    let projects = list_projects_with_options(custom_path, &filter.scan)?;
+
+    let now = clock.now();
+    let min_block_minutes = filter.min_block_minutes.unwrap_or(DEFAULT_MIN_BLOCK_DURATION_MINUTES);
+    let mut fold = UsageFold::new(&now, &filter.session, &filter.timezone, filter.cost_decimals());
+    // Entries also found in an earlier project count only there
+    let mut seen = HashSet::new();
+
+    // Stream each project's entries into the aggregates rather than loading them all
+    for project in &projects {
+        fold.start_project(project);
+        for_each_project_entry(project, &pricing, |entry| {
+            if !is_duplicate_entry(&mut seen, &entry)
+                && filter.matches(&entry, Some(&project.decoded_path))
+            {
+                fold.add(&entry);
+            }
+        });
+    }
+
+    let mut data = fold.finish(&now, min_block_minutes, &pricing);
+    data.daily_usage = trim_daily_history(
+        data.daily_usage,
+        filter.max_daily_history_days,
+        usage_date(&now, &filter.timezone),
+    );
+
+    Ok(data)
+}
+
+/// Load all entries passing the filter, sorted by timestamp
+pub fn load_entries(
+    custom_path: Option<&str>,
+    filter: &FilterOptions,
+) -> Result<Vec<UsageEntry>, ReaderError> {
+    let pricing = filter.pricing();
+    let all_data = load_all_entries_with_options(custom_path, &filter.scan, &pricing)?;
+
+    let mut entries: Vec<UsageEntry> = all_data
+        .into_iter()
+        .flat_map(|(project, entries)| {
+            entries
+                .into_iter()
+                .filter(|e| filter.matches(e, Some(&project.decoded_path)))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    entries.sort_by_key(|e| e.timestamp);
+    Ok(entries)
+}
+
+/// Get usage data for a specific project
+pub fn get_project_usage(
+    custom_path: Option<&str>,
+    project_path: &str,
+) -> Result<Option<ProjectStats>, ReaderError> {
+    let filter = FilterOptions::new().with_project(Some(project_path.to_string()));
+    let data = get_usage_data(custom_path, &filter)?;
+
+    Ok(data.projects.into_iter().next())
+}
+
+/// Get daily usage for a specific date range
 pub fn get_daily_usage_range(
     custom_path: Option<&str>,
     start_date: Option<DateTime<Utc>>,
@@ -516,3 +2245,1582 @@ pub fn get_daily_usage_range(
 
     Ok(data.daily_usage)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage::clock::FixedClock;
+    use crate::usage::reader::empty_if_missing;
+    use chrono::{Local, TimeZone};
+
+    fn daily(date: &str, cost_usd: f64) -> DailyUsage {
+        DailyUsage {
+            date: date.to_string(),
+            cost_usd,
+            message_count: 1,
+            ..Default::default()
+        }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn entry_at(timestamp: DateTime<Utc>, input_tokens: u64, output_tokens: u64, cost_usd: f64) -> UsageEntry {
+        UsageEntry {
+            timestamp,
+            input_tokens,
+            output_tokens,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            cost_usd,
+            model: "claude-sonnet-4-20250514".to_string(),
+            message_id: String::new(),
+            request_id: None,
+        }
+    }
+
+    fn model(name: &str, total_tokens: u64, cost_usd: f64) -> ModelStats {
+        ModelStats {
+            model: name.to_string(),
+            input_tokens: total_tokens / 2,
+            output_tokens: total_tokens - total_tokens / 2,
+            total_tokens,
+            cost_usd,
+            message_count: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_normalize_claude_4_5_model_names() {
+        assert_eq!(normalize_model_name("claude-opus-4-5-20251101"), "claude-opus-4-5");
+        assert_eq!(normalize_model_name("claude-haiku-4-5"), "claude-haiku-4-5");
+        assert_eq!(normalize_model_name("claude-sonnet-4-5-20250929"), "claude-sonnet-4-5");
+        assert_eq!(normalize_model_name("claude-sonnet-4-20250514"), "claude-sonnet-4-20250514");
+    }
+
+    #[test]
+    fn test_missing_projects_dir_reports_empty_usage() {
+        let root = std::env::temp_dir().join(format!("ccm-stats-missing-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let filter = FilterOptions::new();
+
+        let result = get_usage_data(root.to_str(), &filter);
+        assert!(matches!(result, Err(ReaderError::DirNotFound(_))));
+
+        let data = empty_if_missing(get_usage_data(root.to_str(), &filter)).unwrap();
+        assert!(data.projects.is_empty());
+        assert!(data.daily_usage.is_empty());
+        assert_eq!(data.overall_stats.total_messages, 0);
+
+        let io_error = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+        let result: Result<UsageData, _> = empty_if_missing(Err(ReaderError::Io(io_error)));
+        assert!(matches!(result, Err(ReaderError::Io(_))));
+    }
+
+    #[test]
+    fn test_max_daily_history_trims_main_response_only() {
+        let root = std::env::temp_dir().join(format!("ccm-stats-history-{}", std::process::id()));
+        let project = root.join("projects").join("-work-app");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&project).unwrap();
+        let timestamps = ["2025-01-01T10:00:00Z", "2025-01-09T10:00:00Z", "2025-01-10T10:00:00Z"];
+        let lines: Vec<String> = timestamps
+            .iter()
+            .map(|timestamp| {
+                serde_json::json!({
+                    "type": "assistant",
+                    "timestamp": timestamp,
+                    "message": {
+                        "model": "claude-sonnet-4-20250514",
+                        "usage": {"input_tokens": 10, "output_tokens": 20}
+                    }
+                })
+                .to_string()
+            })
+            .collect();
+        std::fs::write(project.join("session.jsonl"), lines.join("\n")).unwrap();
+        let custom_path = root.to_str();
+        let clock = FixedClock(Utc.with_ymd_and_hms(2025, 1, 10, 12, 0, 0).unwrap());
+
+        let filter = FilterOptions::new().with_max_daily_history(Some(2));
+        let data = get_usage_data_with_clock(custom_path, &filter, &clock).unwrap();
+        let dates: Vec<_> = data.daily_usage.iter().map(|d| d.date.as_str()).collect();
+        assert_eq!(dates, vec!["2025-01-09", "2025-01-10"]);
+        // Totals still cover the full history
+        assert_eq!(data.overall_stats.total_messages, 3);
+
+        let daily = get_daily_usage_range(custom_path, None, None).unwrap();
+        assert_eq!(daily.len(), 3);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_streamed_usage_data_matches_loaded_entries() {
+        let root = std::env::temp_dir().join(format!("ccm-stats-stream-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let line = |request_id: &str, minute: u32, output_tokens: u64| {
+            serde_json::json!({
+                "type": "assistant",
+                "timestamp": format!("2025-03-10T14:{:02}:00Z", minute),
+                "requestId": request_id,
+                "message": {
+                    "id": format!("msg_{}", request_id),
+                    "model": "claude-sonnet-4-20250514",
+                    "usage": {"input_tokens": 10, "output_tokens": output_tokens}
+                }
+            })
+            .to_string()
+        };
+        // req_1 is repeated in b.jsonl, whose copy wins; req_3 also appears in a later
+        // project, where it is not counted again
+        let app = root.join("projects").join("-work-app");
+        let lib = root.join("projects").join("-work-lib");
+        std::fs::create_dir_all(&app).unwrap();
+        std::fs::create_dir_all(&lib).unwrap();
+        let a = [line("req_1", 0, 5), line("req_2", 5, 7)].join("\n");
+        let b = [line("req_1", 1, 50), line("req_3", 10, 9)].join("\n");
+        std::fs::write(app.join("a.jsonl"), a).unwrap();
+        std::fs::write(app.join("b.jsonl"), b).unwrap();
+        std::fs::write(lib.join("c.jsonl"), [line("req_3", 10, 9), line("req_4", 20, 3)].join("\n"))
+            .unwrap();
+
+        let now = Utc.with_ymd_and_hms(2025, 3, 10, 14, 40, 0).unwrap();
+        let filter = FilterOptions::new();
+        let pricing = filter.pricing();
+        let custom_path = root.to_str();
+        let all_data = load_all_entries_with_options(custom_path, &filter.scan, &pricing).unwrap();
+        let loaded = calculate_usage_data(
+            all_data,
+            &now,
+            DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+            &SessionConfig::default(),
+            &UsageTimezone::default(),
+            &pricing,
+            DEFAULT_COST_DECIMALS,
+        );
+        let streamed = get_usage_data_with_clock(custom_path, &filter, &FixedClock(now)).unwrap();
+
+        assert_eq!(streamed.overall_stats.total_output_tokens, 69);
+        assert_eq!(
+            serde_json::to_value(&streamed).unwrap(),
+            serde_json::to_value(&loaded).unwrap()
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_usage_data_matches_cache_full_load() {
+        use crate::usage::cache::CacheManager;
+
+        let root = std::env::temp_dir().join(format!("ccm-stats-unified-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let sessions = [
+            ("-work-app", "a.jsonl", vec![
+                ("2025-03-08T09:00:00Z", "claude-sonnet-4-20250514", 100, 200),
+                ("2025-03-10T09:30:00Z", "claude-sonnet-4-20250514", 300, 100),
+                ("2025-03-10T14:00:00Z", "claude-opus-4-20250514", 50, 400),
+            ]),
+            ("-work-lib", "b.jsonl", vec![
+                ("2025-03-09T23:30:00Z", "claude-3-5-haiku-20241022", 1_000, 20),
+                ("2025-03-10T13:45:00Z", "claude-sonnet-4-20250514", 80, 90),
+                ("2025-03-10T14:20:00Z", "claude-sonnet-4-20250514", 10, 30),
+            ]),
+        ];
+        for (project, file, events) in &sessions {
+            let dir = root.join("projects").join(project);
+            std::fs::create_dir_all(&dir).unwrap();
+            let lines: Vec<String> = events
+                .iter()
+                .map(|(timestamp, model, input, output)| {
+                    serde_json::json!({
+                        "type": "assistant",
+                        "timestamp": timestamp,
+                        "message": {
+                            "model": model,
+                            "usage": {"input_tokens": input, "output_tokens": output}
+                        }
+                    })
+                    .to_string()
+                })
+                .collect();
+            std::fs::write(dir.join(file), lines.join("\n")).unwrap();
+        }
+        let clock = FixedClock(Utc.with_ymd_and_hms(2025, 3, 10, 14, 40, 0).unwrap());
+        let pricing = PricingCalculator::new();
+
+        let data = get_usage_data_with_clock(root.to_str(), &FilterOptions::new(), &clock).unwrap();
+        let mut cache = CacheManager::new();
+        cache.set_clock(std::sync::Arc::new(clock));
+        let cached = cache.full_load(root.to_str(), &pricing).unwrap();
+
+        assert_eq!(data.overall_stats.total_messages, 6);
+        assert!(data.overall_stats.burn_rate.is_some());
+        assert_eq!(
+            serde_json::to_value(&data).unwrap(),
+            serde_json::to_value(&cached).unwrap()
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// Outputs recorded from the separate stats.rs and cache.rs computations before they
+    /// were unified, for a history of several session blocks. The 05:00 block straddles
+    /// the burn rate cutoff and the 13:00 block is active at 15:00.
+    #[test]
+    fn test_usage_data_matches_pre_unification_outputs() {
+        use crate::usage::cache::CacheManager;
+
+        let root = std::env::temp_dir().join(format!("ccm-stats-pinned-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let sessions = [
+            ("-work-app", vec![
+                ("2025-03-09T22:15:00Z", "claude-sonnet-4-20250514", 400, 600),
+                ("2025-03-10T02:40:00Z", "claude-sonnet-4-20250514", 100, 300),
+                ("2025-03-10T05:05:00Z", "claude-opus-4-20250514", 200, 800),
+                ("2025-03-10T08:50:00Z", "claude-sonnet-4-20250514", 50, 150),
+                ("2025-03-10T09:30:00Z", "claude-3-5-haiku-20241022", 1_000, 100),
+            ]),
+            ("-work-lib", vec![
+                ("2025-03-10T13:10:00Z", "claude-sonnet-4-20250514", 300, 700),
+                ("2025-03-10T14:05:00Z", "claude-sonnet-4-20250514", 120, 480),
+                ("2025-03-10T14:50:00Z", "claude-opus-4-20250514", 60, 240),
+            ]),
+        ];
+        for (project, events) in &sessions {
+            let dir = root.join("projects").join(project);
+            std::fs::create_dir_all(&dir).unwrap();
+            let lines: Vec<String> = events
+                .iter()
+                .map(|(timestamp, model, input, output)| {
+                    serde_json::json!({
+                        "type": "assistant",
+                        "timestamp": timestamp,
+                        "message": {
+                            "model": model,
+                            "usage": {"input_tokens": input, "output_tokens": output}
+                        }
+                    })
+                    .to_string()
+                })
+                .collect();
+            std::fs::write(dir.join("session.jsonl"), lines.join("\n")).unwrap();
+        }
+        let utc = UsageTimezone::parse("UTC").unwrap();
+        let usage_at = |hour, minute| {
+            let now = Utc.with_ymd_and_hms(2025, 3, 10, hour, minute, 0).unwrap();
+            let filter = FilterOptions::new()
+                .with_date_range(None, Some(now))
+                .with_timezone(utc);
+            get_usage_data_with_clock(root.to_str(), &filter, &FixedClock(now)).unwrap()
+        };
+        let summary = |data: &UsageData| {
+            let stats = &data.overall_stats;
+            let burn_rate = stats.burn_rate.as_ref();
+            (
+                stats.session_start_time.clone(),
+                stats.time_to_reset_minutes,
+                burn_rate.map(|b| (b.tokens_per_minute, b.cost_per_hour)),
+                stats.total_cost_usd,
+            )
+        };
+        let session_start = |hour| Some(format!("2025-03-10T{:02}:00:00+00:00", hour));
+
+        let data = usage_at(15, 0);
+        assert_eq!(summary(&data), (session_start(13), 180, Some((15.83, 0.0189)), 0.118635));
+        let daily: Vec<_> = data
+            .daily_usage
+            .iter()
+            .map(|d| (d.date.as_str(), d.cost_usd, d.message_count))
+            .collect();
+        assert_eq!(daily, vec![("2025-03-09", 0.0102, 1), ("2025-03-10", 0.108435, 7)]);
+        let models: Vec<_> = data
+            .overall_stats
+            .model_distribution
+            .iter()
+            .map(|m| (m.model.as_str(), m.total_tokens, m.cost_usd, m.percentage))
+            .collect();
+        assert_eq!(
+            models,
+            vec![
+                ("claude-sonnet-4-20250514", 3_200, 0.03636, 57.14),
+                ("claude-opus-4-20250514", 1_300, 0.0819, 23.21),
+                ("claude-3-5-haiku", 1_100, 0.000375, 19.64),
+            ]
+        );
+
+        // Between blocks, and late in the block that straddles the cutoff
+        assert_eq!(summary(&usage_at(11, 0)), (session_start(8), 120, None, 0.080775));
+        assert_eq!(
+            summary(&usage_at(9, 40)),
+            (session_start(5), 20, Some((8.21, 0.0141)), 0.080775)
+        );
+
+        let mut cache = CacheManager::new();
+        cache.set_clock(std::sync::Arc::new(FixedClock(
+            Utc.with_ymd_and_hms(2025, 3, 10, 15, 0, 0).unwrap(),
+        )));
+        cache.set_timezone(utc);
+        let cached = cache.full_load(root.to_str(), &PricingCalculator::new()).unwrap();
+        assert_eq!(summary(&cached), summary(&data));
+        assert_eq!(
+            serde_json::to_value(&cached.daily_usage).unwrap(),
+            serde_json::to_value(&data.daily_usage).unwrap()
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_weekly_and_monthly_usage_in_local_time() {
+        let tz = chrono::FixedOffset::east_opt(9 * 3600).unwrap();
+        let entries = vec![
+            // Sunday 2025-01-19 20:00 UTC is Monday 2025-01-20 in UTC+9 (week 4)
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 19, 20, 0, 0).unwrap(), 100, 100, 0.5),
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 15, 10, 0, 0).unwrap(), 100, 100, 0.25),
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 17, 10, 0, 0).unwrap(), 100, 100, 0.25),
+            // 2025-01-31 16:00 UTC is already February in UTC+9
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 31, 16, 0, 0).unwrap(), 10, 10, 1.0),
+        ];
+
+        let weekly = calculate_weekly_usage(&entries, &tz, DEFAULT_COST_DECIMALS);
+        let weeks: Vec<_> = weekly.iter().map(|w| (w.period.as_str(), w.message_count)).collect();
+        assert_eq!(weeks, vec![("2025-W03", 2), ("2025-W04", 1), ("2025-W05", 1)]);
+        assert_eq!(weekly[0].cost_usd, 0.5);
+
+        let monthly = calculate_monthly_usage(&entries, &tz, 1, DEFAULT_COST_DECIMALS);
+        assert_eq!(monthly.len(), 2);
+        assert_eq!(monthly[0].period, "2025-01");
+        assert_eq!(monthly[0].message_count, 3);
+        assert_eq!(monthly[0].input_tokens, 300);
+        assert_eq!(monthly[1].period, "2025-02");
+        assert_eq!(monthly[1].cost_usd, 1.0);
+    }
+
+    #[test]
+    fn test_usage_since_counts_only_later_entries() {
+        let viewed = Utc.with_ymd_and_hms(2025, 1, 20, 12, 0, 0).unwrap();
+        let entries = vec![
+            entry_at(viewed - chrono::Duration::minutes(5), 100, 100, 1.0),
+            // Seen at the moment of viewing
+            entry_at(viewed, 100, 100, 1.0),
+            entry_at(viewed + chrono::Duration::minutes(5), 10, 20, 0.25),
+            entry_at(viewed + chrono::Duration::hours(2), 30, 40, 0.5),
+        ];
+
+        let usage = calculate_usage_since(&entries, viewed, DEFAULT_COST_DECIMALS);
+        assert_eq!(usage.last_viewed_at.as_deref(), Some("2025-01-20T12:00:00+00:00"));
+        assert_eq!(usage.message_count, 2);
+        assert_eq!(usage.input_tokens, 40);
+        assert_eq!(usage.total_tokens, 100);
+        assert_eq!(usage.cost_usd, 0.75);
+    }
+
+    #[test]
+    fn test_hourly_usage_fills_all_hours_in_local_time() {
+        let tz = chrono::FixedOffset::east_opt(9 * 3600).unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 1, 20).unwrap();
+        let entries = vec![
+            // 2025-01-19 15:30 UTC is 00:30 on the 20th in UTC+9
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 19, 15, 30, 0).unwrap(), 100, 50, 0.5),
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 20, 5, 10, 0).unwrap(), 200, 100, 1.0),
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 20, 5, 50, 0).unwrap(), 200, 100, 1.0),
+            // Still the 19th in UTC+9
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 19, 14, 59, 0).unwrap(), 100, 100, 9.0),
+            // Already the 21st in UTC+9
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 20, 15, 0, 0).unwrap(), 100, 100, 9.0),
+        ];
+
+        let hourly = calculate_hourly_usage(&entries, date, &tz, DEFAULT_COST_DECIMALS);
+        assert_eq!(hourly.len(), 24);
+        assert!(hourly.iter().enumerate().all(|(i, h)| h.hour == i as u32));
+        assert_eq!(hourly[0].message_count, 1);
+        assert_eq!(hourly[0].total_tokens, 150);
+        assert_eq!(hourly[14].message_count, 2);
+        assert_eq!(hourly[14].cost_usd, 2.0);
+        assert_eq!(hourly[23].message_count, 0);
+        let total: f64 = hourly.iter().map(|h| h.cost_usd).sum();
+        assert_eq!(total, 2.5);
+    }
+
+    #[test]
+    fn test_forecast_month_end_with_recent_band() {
+        let local = |month: u32, day: u32| {
+            Local
+                .with_ymd_and_hms(2025, month, day, 12, 0, 0)
+                .earliest()
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let mut entries = vec![entry_at(local(3, 30), 100, 100, 5.0)];
+        entries.extend((1..=3).map(|day| entry_at(local(4, day), 100, 100, 1.0)));
+        entries.extend((8..=10).map(|day| entry_at(local(4, day), 100, 100, 3.0)));
+
+        let forecast = forecast_month_end(
+            &entries,
+            &local(4, 10),
+            &UsageTimezone::Local,
+            1,
+            DEFAULT_COST_DECIMALS,
+        );
+        assert_eq!(forecast.period_start, "2025-04-01");
+        assert_eq!(forecast.period_end, "2025-04-30");
+        assert_eq!(forecast.days_elapsed, 10);
+        assert_eq!(forecast.days_remaining, 20);
+        assert_eq!(forecast.cost_to_date, 12.0);
+        assert_eq!(forecast.tokens_to_date, 1_200);
+        // Month-to-date average of $1.20/day over the remaining 20 days
+        assert!((forecast.projected_cost - 36.0).abs() < 1e-9);
+        assert_eq!(forecast.projected_tokens, 3_600);
+        // The last 7 days ran at $9/7 per day
+        assert!((forecast.projected_cost_low - 36.0).abs() < 1e-9);
+        assert!((forecast.projected_cost_high - (12.0 + 9.0 / 7.0 * 20.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_billing_cycle_starting_on_the_15th() {
+        let tz = chrono::FixedOffset::east_opt(0).unwrap();
+        let entries = vec![
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 14, 12, 0, 0).unwrap(), 100, 100, 2.0),
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 15, 12, 0, 0).unwrap(), 100, 100, 1.0),
+            entry_at(Utc.with_ymd_and_hms(2025, 2, 14, 12, 0, 0).unwrap(), 100, 100, 1.0),
+        ];
+
+        let monthly = calculate_monthly_usage(&entries, &tz, 15, DEFAULT_COST_DECIMALS);
+        let periods: Vec<_> = monthly.iter().map(|m| (m.period.as_str(), m.cost_usd)).collect();
+        assert_eq!(periods, vec![("2024-12", 2.0), ("2025-01", 2.0)]);
+
+        let local = |month: u32, day: u32| {
+            Local
+                .with_ymd_and_hms(2025, month, day, 12, 0, 0)
+                .earliest()
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let entries = vec![
+            entry_at(local(1, 14), 100, 100, 2.0),
+            entry_at(local(1, 15), 100, 100, 1.0),
+            entry_at(local(1, 20), 100, 100, 1.0),
+        ];
+        let forecast = forecast_month_end(
+            &entries,
+            &local(1, 20),
+            &UsageTimezone::Local,
+            15,
+            DEFAULT_COST_DECIMALS,
+        );
+        assert_eq!(forecast.period_start, "2025-01-15");
+        assert_eq!(forecast.period_end, "2025-02-14");
+        assert_eq!(forecast.days_elapsed, 6);
+        assert_eq!(forecast.cost_to_date, 2.0);
+
+        // Months shorter than the start day begin their cycle on their last day
+        let date = |m: u32, d: u32| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+        assert_eq!(billing_cycle_start(date(2, 27), 31), date(1, 31));
+        assert_eq!(billing_cycle_start(date(2, 28), 31), date(2, 28));
+        assert_eq!(billing_cycle_start(date(3, 30), 31), date(2, 28));
+        assert_eq!(billing_cycle_start(date(3, 31), 31), date(3, 31));
+        assert_eq!(billing_cycle_start(date(3, 1), 1), date(3, 1));
+    }
+
+    #[test]
+    fn test_daily_cost_moving_average_fills_gaps() {
+        let daily_usage = vec![
+            daily("2025-01-01", 3.0),
+            daily("2025-01-02", 6.0),
+            // 2025-01-03 missing, counted as zero
+            daily("2025-01-04", 9.0),
+            daily("2025-01-05", 12.0),
+        ];
+
+        let points = calculate_daily_cost_moving_average(&daily_usage, 3, DEFAULT_COST_DECIMALS);
+        let dates: Vec<_> = points.iter().map(|p| p.date.as_str()).collect();
+        assert_eq!(dates, ["2025-01-01", "2025-01-02", "2025-01-03", "2025-01-04", "2025-01-05"]);
+        assert_eq!(points[2].cost_usd, 0.0);
+
+        let averages: Vec<_> = points.iter().map(|p| p.moving_average).collect();
+        assert_eq!(averages, [None, None, Some(3.0), Some(5.0), Some(7.0)]);
+    }
+
+    #[test]
+    fn test_recent_daily_usage_window() {
+        let daily_usage = vec![
+            daily("2025-01-01", 1.0),
+            daily("2025-01-03", 3.0),
+            daily("2025-01-05", 5.0),
+        ];
+        let today = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+
+        let filled = recent_daily_usage(&daily_usage, today, 4, true);
+        let dates: Vec<_> = filled.iter().map(|d| (d.date.as_str(), d.cost_usd)).collect();
+        assert_eq!(
+            dates,
+            [("2025-01-03", 3.0), ("2025-01-04", 0.0), ("2025-01-05", 5.0), ("2025-01-06", 0.0)]
+        );
+
+        let sparse = recent_daily_usage(&daily_usage, today, 4, false);
+        let dates: Vec<_> = sparse.iter().map(|d| d.date.as_str()).collect();
+        assert_eq!(dates, ["2025-01-03", "2025-01-05"]);
+
+        assert_eq!(recent_days_start(today, u32::MAX), NaiveDate::MIN);
+    }
+
+    #[test]
+    fn test_daily_usage_on_date() {
+        let daily_usage = vec![daily("2025-01-01", 1.0), daily("2025-01-03", 3.0)];
+
+        let day = daily_usage_on(&daily_usage, NaiveDate::from_ymd_opt(2025, 1, 3).unwrap());
+        assert_eq!(day.cost_usd, 3.0);
+
+        let empty = daily_usage_on(&daily_usage, NaiveDate::from_ymd_opt(2025, 1, 2).unwrap());
+        assert_eq!(empty.date, "2025-01-02");
+        assert_eq!(empty.message_count, 0);
+    }
+
+    #[test]
+    fn test_work_sessions_split_on_idle_gap() {
+        let at = |h: u32, m: u32| Utc.with_ymd_and_hms(2025, 1, 1, h, m, 0).unwrap();
+        let project = |path: &str| ProjectData {
+            encoded_path: path.to_string(),
+            decoded_path: path.to_string(),
+            display_name: path.to_string(),
+            session_files: Vec::new(),
+        };
+        let project_entries = vec![
+            (
+                project("a"),
+                vec![
+                    // Morning cluster
+                    entry_at(at(9, 0), 10, 10, 1.0),
+                    entry_at(at(9, 20), 10, 10, 1.0),
+                    entry_at(at(9, 45), 10, 10, 1.0),
+                    // Afternoon cluster after a long gap
+                    entry_at(at(14, 0), 10, 10, 2.0),
+                    entry_at(at(14, 30), 10, 10, 2.0),
+                ],
+            ),
+            (project("b"), vec![entry_at(at(9, 30), 10, 10, 0.5)]),
+        ];
+
+        let report = calculate_work_sessions(&project_entries, 30, DEFAULT_COST_DECIMALS);
+        assert_eq!(report.session_count, 2);
+        assert_eq!(report.sessions[0].message_count, 4);
+        assert_eq!(report.sessions[0].duration_minutes, 45.0);
+        assert_eq!(report.sessions[1].message_count, 2);
+        assert!((report.sessions[1].cost_usd - 4.0).abs() < 1e-9);
+        assert_eq!(report.total_duration_minutes, 75.0);
+
+        assert_eq!(report.projects[0].project_path, "a");
+        assert_eq!(report.projects[0].session_count, 2);
+        assert_eq!(report.projects[1].session_count, 1);
+        assert_eq!(report.projects[1].total_duration_minutes, 0.0);
+
+        // A gap exactly at the threshold does not split
+        let report = calculate_work_sessions(
+            &project_entries[..1],
+            4 * 60 + 15,
+            DEFAULT_COST_DECIMALS,
+        );
+        assert_eq!(report.session_count, 1);
+    }
+
+    #[test]
+    fn test_period_start() {
+        // 2025-01-08 is a Wednesday
+        assert_eq!(period_start(date("2025-01-08"), Granularity::Day), date("2025-01-08"));
+        assert_eq!(period_start(date("2025-01-08"), Granularity::Week), date("2025-01-06"));
+        assert_eq!(period_start(date("2025-01-08"), Granularity::Month), date("2025-01-01"));
+    }
+
+    #[test]
+    fn test_avg_message_cost_trend_rises_with_context() {
+        let day = |d: u32, cost: f64| {
+            entry_at(Utc.with_ymd_and_hms(2025, 1, d, 10, 0, 0).unwrap(), 100, 100, cost)
+        };
+        // Weeks of 2025-01-06, 01-13 (no usage) and 01-20, with costlier messages later
+        let entries = vec![day(6, 0.01), day(7, 0.03), day(20, 0.05), day(21, 0.07)];
+        let utc = UsageTimezone::parse("UTC").unwrap();
+
+        let trend = calculate_avg_message_cost_trend(
+            &entries,
+            Granularity::Week,
+            &utc,
+            DEFAULT_COST_DECIMALS,
+        );
+        let starts: Vec<_> = trend.iter().map(|p| p.period_start.as_str()).collect();
+        assert_eq!(starts, vec!["2025-01-06", "2025-01-13", "2025-01-20"]);
+        assert_eq!(trend[0].avg_cost_per_message, Some(0.02));
+        assert_eq!(trend[1].message_count, 0);
+        assert_eq!(trend[1].avg_cost_per_message, None);
+        assert_eq!(trend[2].avg_cost_per_message, Some(0.06));
+        assert!(trend[2].avg_cost_per_message > trend[0].avg_cost_per_message);
+
+        let monthly = calculate_avg_message_cost_trend(
+            &entries,
+            Granularity::Month,
+            &utc,
+            DEFAULT_COST_DECIMALS,
+        );
+        assert_eq!(monthly.len(), 1);
+        assert!(calculate_avg_message_cost_trend(
+            &[],
+            Granularity::Day,
+            &utc,
+            DEFAULT_COST_DECIMALS,
+        ).is_empty());
+
+        // Sunday 2025-01-05 20:00 UTC is already Monday in Tokyo
+        let late = entry_at(Utc.with_ymd_and_hms(2025, 1, 5, 20, 0, 0).unwrap(), 100, 100, 0.01);
+        let tokyo = UsageTimezone::parse("Asia/Tokyo").unwrap();
+        let late = std::slice::from_ref(&late);
+        let in_utc = calculate_avg_message_cost_trend(
+            late,
+            Granularity::Week,
+            &utc,
+            DEFAULT_COST_DECIMALS,
+        );
+        assert_eq!(in_utc[0].period_start, "2024-12-30");
+        let in_tokyo = calculate_avg_message_cost_trend(
+            late,
+            Granularity::Week,
+            &tokyo,
+            DEFAULT_COST_DECIMALS,
+        );
+        assert_eq!(in_tokyo[0].period_start, "2025-01-06");
+    }
+
+    #[test]
+    fn test_blended_rate_rises_with_shift_to_opus() {
+        let at = |d: u32| Utc.with_ymd_and_hms(2025, 1, d, 12, 0, 0).unwrap();
+        let mut opus = entry_at(at(14), 200_000, 300_000, 7.5);
+        opus.model = "claude-opus-4-20250514".to_string();
+        let entries = vec![
+            // Week of Jan 6: Sonnet only, $3 per 1M tokens
+            entry_at(at(6), 500_000, 500_000, 3.0),
+            // Week of Jan 13: half the tokens move to Opus
+            entry_at(at(13), 250_000, 250_000, 1.5),
+            opus,
+            // Week of Jan 20: no tokens, excluded
+            entry_at(at(20), 0, 0, 0.0),
+        ];
+
+        let utc = UsageTimezone::parse("UTC").unwrap();
+        let trend = calculate_blended_rate_trend(
+            &entries,
+            Granularity::Week,
+            &utc,
+            DEFAULT_COST_DECIMALS,
+        );
+        assert_eq!(trend.len(), 2);
+        assert_eq!(trend[0].period_start, "2025-01-06");
+        assert!((trend[0].cost_per_million_tokens - 3.0).abs() < 1e-9);
+        assert_eq!(trend[1].period_start, "2025-01-13");
+        assert!((trend[1].cost_per_million_tokens - 9.0).abs() < 1e-9);
+        assert!(trend[1].cost_per_million_tokens > trend[0].cost_per_million_tokens);
+    }
+
+    fn bucket(label: &str, start_hour: u32, end_hour: u32) -> TimeBucket {
+        TimeBucket {
+            label: label.to_string(),
+            start_hour,
+            end_hour,
+        }
+    }
+
+    #[test]
+    fn test_time_buckets_split_business_and_after_hours() {
+        let at = |h: u32| Utc.with_ymd_and_hms(2025, 1, 6, h, 15, 0).unwrap();
+        let entries = vec![
+            entry_at(at(9), 100, 100, 1.0),
+            entry_at(at(16), 100, 100, 2.0),
+            entry_at(at(17), 100, 100, 4.0),
+            entry_at(at(2), 100, 100, 8.0),
+        ];
+        let buckets = vec![bucket("business", 9, 17), bucket("after-hours", 17, 9)];
+        assert!(validate_time_buckets(&buckets).is_ok());
+
+        let usage = calculate_time_bucket_usage(&entries, &buckets, &Utc, DEFAULT_COST_DECIMALS);
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].label, "business");
+        assert_eq!(usage[0].message_count, 2);
+        assert!((usage[0].cost_usd - 3.0).abs() < 1e-9);
+        assert_eq!(usage[1].label, "after-hours");
+        assert_eq!(usage[1].message_count, 2);
+        assert!((usage[1].cost_usd - 12.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_time_buckets_overlap_and_gap_rules() {
+        let at = |h: u32| Utc.with_ymd_and_hms(2025, 1, 6, h, 0, 0).unwrap();
+        let entries = vec![entry_at(at(10), 1, 1, 1.0), entry_at(at(20), 1, 1, 2.0)];
+        // "morning" overlaps "day"; first matching bucket wins. 20:00 is in no bucket.
+        let buckets = vec![bucket("morning", 8, 12), bucket("day", 9, 18)];
+
+        let usage = calculate_time_bucket_usage(&entries, &buckets, &Utc, DEFAULT_COST_DECIMALS);
+        assert_eq!(usage[0].message_count, 1);
+        assert_eq!(usage[1].message_count, 0);
+        assert_eq!(usage[2].label, UNASSIGNED_BUCKET_LABEL);
+        assert!((usage[2].cost_usd - 2.0).abs() < 1e-9);
+
+        assert!(validate_time_buckets(&[bucket("empty", 5, 5)]).is_err());
+        assert!(validate_time_buckets(&[bucket("bad", 25, 3)]).is_err());
+    }
+
+    #[test]
+    fn test_burn_rate_floors_single_entry_block() {
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 10).unwrap();
+        // One message five seconds into the block, observed five seconds later
+        let entries = vec![entry_at(Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 5).unwrap(), 30_000, 30_000, 1.0)];
+        let blocks = transform_to_blocks(&entries, &now, &SessionConfig::default());
+
+        let (unfloored, _) = calculate_hourly_burn_rate(&blocks, &now, 0.0);
+        let (floored, cost_per_hour) = calculate_hourly_burn_rate(&blocks, &now, 1.0);
+
+        // Without a floor the whole block lands in a 10 second window
+        assert!((unfloored - 1000.0).abs() < 1e-9);
+        // With a one minute floor only 10/60 of the block is attributed
+        assert!((floored - 1000.0 / 6.0).abs() < 1e-9);
+        // That sixth of the cost is spread over the one minute floor, then scaled to an hour
+        assert!((cost_per_hour - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_burn_rate_includes_future_dated_entry() {
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 10, 59, 0).unwrap();
+        // Another machine's clock runs two minutes ahead, pushing the block into the next hour
+        let entries = vec![entry_at(now + chrono::Duration::minutes(2), 300, 300, 0.6)];
+        let blocks = transform_to_blocks(&entries, &now, &SessionConfig::default());
+        assert!(blocks[0].is_active);
+
+        let (tokens_per_minute, cost_per_hour) = calculate_hourly_burn_rate(&blocks, &now, 1.0);
+        assert!((tokens_per_minute - 10.0).abs() < 1e-9);
+        // The block counts from 10:00, so $0.60 over 59 minutes
+        assert!((cost_per_hour - 0.6 / 59.0 * 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_burn_rate_cost_is_hourly_for_short_window() {
+        let block_start = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap();
+        let now = block_start + chrono::Duration::minutes(20);
+        let entries = vec![
+            entry_at(block_start, 600, 600, 1.0),
+            entry_at(now, 600, 600, 1.0),
+        ];
+        let blocks = transform_to_blocks(&entries, &now, &SessionConfig::default());
+
+        let (tokens_per_minute, cost_per_hour) = calculate_hourly_burn_rate(&blocks, &now, 1.0);
+        // $2 over a 20 minute window is $6 an hour
+        assert!((cost_per_hour - 6.0).abs() < 1e-9);
+        assert!((tokens_per_minute - 2400.0 / 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_time_to_reset_with_fixed_clock() {
+        let clock = FixedClock(Utc.with_ymd_and_hms(2025, 1, 1, 11, 30, 0).unwrap());
+        let entries = vec![
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 1, 10, 20, 0).unwrap(), 100, 100, 0.01),
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 1, 11, 10, 0).unwrap(), 100, 100, 0.01),
+        ];
+        let usage_at = |clock: &FixedClock| {
+            let project = ProjectData {
+                encoded_path: "-work-app".to_string(),
+                decoded_path: "/work/app".to_string(),
+                display_name: "app".to_string(),
+                session_files: Vec::new(),
+            };
+            calculate_usage_data(
+                vec![(project, entries.clone())],
+                &clock.now(),
+                DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+                &SessionConfig::default(),
+                &UsageTimezone::default(),
+                &PricingCalculator::new(),
+                DEFAULT_COST_DECIMALS,
+            )
+        };
+
+        let stats = usage_at(&clock).overall_stats;
+        let session_start = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap();
+        assert_eq!(stats.session_start_time, Some(session_start.to_rfc3339()));
+        // 90 minutes into the 300 minute block
+        assert_eq!(stats.time_to_reset_minutes, 210);
+        assert!(stats.burn_rate.is_some());
+
+        let later = FixedClock(Utc.with_ymd_and_hms(2025, 1, 1, 20, 0, 0).unwrap());
+        let stats = usage_at(&later).overall_stats;
+        assert!(stats.session_start_time.is_none());
+        assert_eq!(stats.time_to_reset_minutes, SESSION_DURATION_MINUTES as u32);
+        assert!(stats.burn_rate.is_none());
+    }
+
+    #[test]
+    fn test_burn_rate_blocks_follow_full_history() {
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+        let at = |hour, minute| Utc.with_ymd_and_hms(2025, 1, 1, hour, minute, 0).unwrap();
+        // Blocks start at 05:00 and 10:00; the 06:10 entry is past the six hour cutoff but
+        // belongs to the 05:00 block, so the active block must not start at 06:00 or 11:00
+        let entries = vec![
+            entry_at(at(5, 10), 100, 100, 0.02),
+            entry_at(at(6, 10), 100, 100, 0.02),
+            entry_at(at(10, 20), 3_000, 3_000, 0.6),
+            entry_at(at(11, 30), 300, 300, 0.06),
+        ];
+        let project = ProjectData {
+            encoded_path: "-work-app".to_string(),
+            decoded_path: "/work/app".to_string(),
+            display_name: "app".to_string(),
+            session_files: Vec::new(),
+        };
+        let session = SessionConfig::default();
+        let stats = calculate_usage_data(
+            vec![(project, entries.clone())],
+            &now,
+            DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+            &session,
+            &UsageTimezone::default(),
+            &PricingCalculator::new(),
+            DEFAULT_COST_DECIMALS,
+        )
+        .overall_stats;
+
+        // Half of the 10:00-12:00 block's 6600 tokens and $0.66 fall in the last hour
+        let burn_rate = stats.burn_rate.unwrap();
+        assert_eq!(burn_rate.tokens_per_minute, 55.0);
+        assert_eq!(burn_rate.cost_per_hour, 0.33);
+        let blocks = transform_to_blocks(&entries, &now, &session);
+        let (tokens_per_min, _) =
+            calculate_hourly_burn_rate(&blocks, &now, DEFAULT_MIN_BLOCK_DURATION_MINUTES);
+        assert_eq!(burn_rate.tokens_per_minute, tokens_per_min);
+        assert_eq!(stats.session_start_time, Some(at(10, 0).to_rfc3339()));
+        assert_eq!(stats.time_to_reset_minutes, 180);
+    }
+
+    #[test]
+    fn test_time_to_reset_with_configured_session_duration() {
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 11, 30, 0).unwrap();
+        let entries = vec![
+            // Outside a 3-hour window, inside the default 5-hour one
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 1, 7, 45, 0).unwrap(), 100, 100, 0.01),
+            entry_at(Utc.with_ymd_and_hms(2025, 1, 1, 9, 20, 0).unwrap(), 100, 100, 0.01),
+        ];
+        let config = AppConfig {
+            session_duration_minutes: 180,
+            ..AppConfig::default()
+        };
+        let project = ProjectData {
+            encoded_path: "-work-app".to_string(),
+            decoded_path: "/work/app".to_string(),
+            display_name: "app".to_string(),
+            session_files: Vec::new(),
+        };
+        let stats = calculate_usage_data(
+            vec![(project, entries)],
+            &now,
+            DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+            &SessionConfig::from_config(&config),
+            &UsageTimezone::default(),
+            &PricingCalculator::new(),
+            DEFAULT_COST_DECIMALS,
+        )
+        .overall_stats;
+
+        let session_start = Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap();
+        assert_eq!(stats.session_start_time, Some(session_start.to_rfc3339()));
+        // 150 minutes into the 180 minute block
+        assert_eq!(stats.time_to_reset_minutes, 30);
+    }
+
+    #[test]
+    fn test_late_night_utc_entry_uses_configured_timezone_date() {
+        // 22:30 on Jan 1 in New York, already Jan 2 in UTC
+        let entry_time = Utc.with_ymd_and_hms(2025, 1, 2, 3, 30, 0).unwrap();
+        let now = Utc.with_ymd_and_hms(2025, 1, 2, 4, 0, 0).unwrap();
+        let usage_in = |tz: &UsageTimezone| {
+            let project = ProjectData {
+                encoded_path: "-work-app".to_string(),
+                decoded_path: "/work/app".to_string(),
+                display_name: "app".to_string(),
+                session_files: Vec::new(),
+            };
+            calculate_usage_data(
+                vec![(project, vec![entry_at(entry_time, 100, 100, 0.5)])],
+                &now,
+                DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+                &SessionConfig::default(),
+                tz,
+                &PricingCalculator::new(),
+                DEFAULT_COST_DECIMALS,
+            )
+        };
+
+        let new_york = UsageTimezone::parse("America/New_York").unwrap();
+        let data = usage_in(&new_york);
+        assert_eq!(data.daily_usage.len(), 1);
+        assert_eq!(data.daily_usage[0].date, "2025-01-01");
+        assert_eq!(data.overall_stats.today_stats.message_count, 1);
+        assert_eq!(data.overall_stats.today_stats.cost_usd, 0.5);
+
+        let utc = UsageTimezone::parse("UTC").unwrap();
+        let data = usage_in(&utc);
+        assert_eq!(data.daily_usage[0].date, "2025-01-02");
+        assert_eq!(data.overall_stats.today_stats.message_count, 1);
+    }
+
+    #[test]
+    fn test_calculate_time_to_reset() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap();
+        let now = start + chrono::Duration::minutes(45);
+        let session = SessionConfig::default();
+        assert_eq!(calculate_time_to_reset(Some(&start), &now, &session), 255);
+        assert_eq!(calculate_time_to_reset(Some(&now), &start, &session), 300);
+        assert_eq!(calculate_time_to_reset(None, &now, &session), 300);
+    }
+
+    #[test]
+    fn test_paginate_projects_sorts_before_slicing() {
+        let project = |path: &str, cost: f64, tokens: u64, last: &str| ProjectStats {
+            project_path: path.to_string(),
+            total_cost_usd: cost,
+            total_input_tokens: tokens,
+            last_activity: Some(last.to_string()),
+            ..Default::default()
+        };
+        let projects = vec![
+            project("/a", 1.0, 300, "2025-01-03T00:00:00+00:00"),
+            project("/b", 3.0, 100, "2025-01-01T00:00:00+00:00"),
+            project("/c", 2.0, 200, "2025-01-02T00:00:00+00:00"),
+        ];
+        let paths = |page: &ProjectPage| -> Vec<String> {
+            page.projects.iter().map(|p| p.project_path.clone()).collect()
+        };
+
+        let page = paginate_projects(projects.clone(), 1, 1, ProjectSortKey::TotalCost);
+        assert_eq!(page.total, 3);
+        assert_eq!(paths(&page), vec!["/c"]);
+
+        let page = paginate_projects(projects.clone(), 0, 2, ProjectSortKey::TotalTokens);
+        assert_eq!(paths(&page), vec!["/a", "/c"]);
+
+        let page = paginate_projects(projects.clone(), 0, 10, ProjectSortKey::LastActivity);
+        assert_eq!(paths(&page), vec!["/a", "/c", "/b"]);
+
+        let page = paginate_projects(projects, 5, 10, ProjectSortKey::LastActivity);
+        assert!(page.projects.is_empty());
+        assert_eq!(page.total, 3);
+    }
+
+    #[test]
+    fn test_search_projects_ranks_prefix_matches_first() {
+        let project = |path: &str, name: &str| ProjectStats {
+            project_path: path.to_string(),
+            display_name: name.to_string(),
+            ..Default::default()
+        };
+        let projects = vec![
+            project("/work/my-react-app", "my-react-app"),
+            project("/work/api", "api"),
+            project("/work/React-Native", "React-Native"),
+        ];
+
+        let found = search_project_stats(&projects, "REACT");
+        let names: Vec<&str> = found.iter().map(|p| p.display_name.as_str()).collect();
+        assert_eq!(names, vec!["React-Native", "my-react-app"]);
+
+        // Matches against the path as well as the display name
+        assert_eq!(search_project_stats(&projects, "work/api").len(), 1);
+        assert!(search_project_stats(&projects, "  ").is_empty());
+    }
+
+    #[test]
+    fn test_configured_cost_decimals_round_reported_totals() {
+        let at = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap();
+        let entries = vec![entry_at(at, 100, 50, 0.004), entry_at(at, 100, 50, 0.004)];
+        let project = ProjectData {
+            encoded_path: "-work-app".to_string(),
+            decoded_path: "/work/app".to_string(),
+            display_name: "app".to_string(),
+            session_files: Vec::new(),
+        };
+
+        let data = calculate_usage_data(
+            vec![(project, entries)],
+            &at,
+            DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+            &SessionConfig::default(),
+            &UsageTimezone::default(),
+            &PricingCalculator::new(),
+            2,
+        );
+        // Entries are summed unrounded: 0.008 -> 0.01, not 0.00 + 0.00
+        assert_eq!(data.projects[0].total_cost_usd, 0.01);
+        assert_eq!(data.daily_usage[0].cost_usd, 0.01);
+        assert_eq!(data.overall_stats.total_cost_usd, 0.01);
+        assert_eq!(data.overall_stats.model_distribution[0].cost_usd, 0.01);
+        // Averages use the same precision
+        assert_eq!(data.projects[0].avg_cost_per_message, 0.0);
+    }
+
+    #[test]
+    fn test_total_cost_sums_entries_not_rounded_projects() {
+        let at = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap();
+        // Each project's cost rounds to $0.000000 on its own
+        let all_data = (0..1_000)
+            .map(|i| {
+                let project = ProjectData {
+                    encoded_path: format!("-work-app{}", i),
+                    decoded_path: format!("/work/app{}", i),
+                    display_name: format!("app{}", i),
+                    session_files: Vec::new(),
+                };
+                (project, vec![entry_at(at, 1, 1, 0.000_000_4)])
+            })
+            .collect();
+
+        let data = calculate_usage_data(
+            all_data,
+            &at,
+            DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+            &SessionConfig::default(),
+            &UsageTimezone::default(),
+            &PricingCalculator::new(),
+            DEFAULT_COST_DECIMALS,
+        );
+        let naive: f64 = data.projects.iter().map(|p| p.total_cost_usd).sum();
+        assert_eq!(naive, 0.0);
+        assert_eq!(data.overall_stats.total_cost_usd, 0.0004);
+
+        // A compensated sum stays exact where a plain running sum drifts
+        let mut precise = CostSum::default();
+        let mut plain = 0.0;
+        for _ in 0..1_000_000 {
+            precise.add(0.1);
+            plain += 0.1;
+        }
+        assert!((plain - 100_000.0_f64).abs() > 1e-6);
+        assert!((precise.value() - 100_000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_per_message_averages() {
+        let at = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap();
+        let entries = vec![entry_at(at, 100, 50, 0.3), entry_at(at, 300, 150, 0.1)];
+        let project = ProjectData {
+            encoded_path: "-work-app".to_string(),
+            decoded_path: "/work/app".to_string(),
+            display_name: "app".to_string(),
+            session_files: Vec::new(),
+        };
+
+        let data = calculate_usage_data(
+            vec![(project, entries)],
+            &at,
+            DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+            &SessionConfig::default(),
+            &UsageTimezone::default(),
+            &PricingCalculator::new(),
+            DEFAULT_COST_DECIMALS,
+        );
+        assert!((data.projects[0].avg_cost_per_message - 0.2).abs() < 1e-9);
+        assert_eq!(data.projects[0].avg_tokens_per_message, 300.0);
+
+        let empty = calculate_usage_data(
+            Vec::new(),
+            &at,
+            DEFAULT_MIN_BLOCK_DURATION_MINUTES,
+            &SessionConfig::default(),
+            &UsageTimezone::default(),
+            &PricingCalculator::new(),
+            DEFAULT_COST_DECIMALS,
+        );
+        assert_eq!(empty.overall_stats.avg_cost_per_message, 0.0);
+        assert_eq!(empty.overall_stats.avg_tokens_per_message, 0.0);
+    }
+
+    #[test]
+    fn test_rank_projects_by_cache_efficiency() {
+        let cold = ProjectStats {
+            project_path: "D:\\code\\cold".to_string(),
+            total_input_tokens: 9_000,
+            cache_creation_tokens: 900,
+            cache_read_tokens: 100,
+            ..Default::default()
+        };
+        let warm = ProjectStats {
+            project_path: "D:\\code\\warm".to_string(),
+            total_input_tokens: 1_000,
+            cache_creation_tokens: 1_000,
+            cache_read_tokens: 8_000,
+            ..Default::default()
+        };
+
+        let ranking = rank_projects_by_cache_efficiency(&[cold, warm]);
+        assert_eq!(ranking[0].project_path, "D:\\code\\warm");
+        assert_eq!(ranking[0].cache_read_ratio, 0.8);
+        assert_eq!(ranking[0].fresh_input_tokens, 1_000);
+        assert_eq!(ranking[1].project_path, "D:\\code\\cold");
+        assert_eq!(ranking[1].cache_read_ratio, 0.01);
+    }
+
+    #[test]
+    fn test_project_cache_cost_impact_net_savings() {
+        let path =
+            std::env::temp_dir().join(format!("ccm-stats-cache-{}.json", std::process::id()));
+        let local_model = r#"{"input": 1.0, "output": 2.0, "cache_creation": 0.0,
+            "cache_creation_1h": 0.0, "cache_read": 0.0}"#;
+        std::fs::write(&path, format!(r#"{{"local-model": {}}}"#, local_model)).unwrap();
+        let pricing = PricingCalculator::from_file(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let project = |path: &str| ProjectData {
+            encoded_path: path.to_string(),
+            decoded_path: path.to_string(),
+            display_name: path.to_string(),
+            session_files: Vec::new(),
+        };
+        let at = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap();
+        let mut heavy = entry_at(at, 100_000, 1_000, 0.0);
+        heavy.cache_creation_tokens = 200_000;
+        heavy.cache_read_tokens = 2_000_000;
+        let mut write_only = entry_at(at, 0, 1_000, 0.0);
+        write_only.cache_creation_tokens = 1_000_000;
+        let mut unpriced = entry_at(at, 0, 1_000, 0.0);
+        unpriced.model = "local-model".to_string();
+        unpriced.cache_read_tokens = 5_000;
+
+        let project_entries = vec![
+            (project("cold"), vec![write_only, unpriced]),
+            (project("warm"), vec![heavy]),
+            (project("idle"), Vec::new()),
+        ];
+        let impacts = calculate_project_cache_cost_impact(
+            &project_entries,
+            &pricing,
+            DEFAULT_COST_DECIMALS,
+        );
+        assert_eq!(impacts.len(), 2);
+
+        // Sonnet: $3 input, $3.75 cache write, $0.30 cache read per million
+        let warm = &impacts[0];
+        assert_eq!(warm.project_path, "warm");
+        assert!((warm.fresh_input_cost_usd - 0.3).abs() < 1e-9);
+        assert!((warm.cache_creation_cost_usd - 0.75).abs() < 1e-9);
+        assert!((warm.cache_read_cost_usd - 0.6).abs() < 1e-9);
+        assert!((warm.avoided_input_cost_usd - 6.6).abs() < 1e-9);
+        assert!((warm.net_savings_usd - 5.25).abs() < 1e-9);
+
+        // Cache writes that are never read back cost more than fresh input
+        let cold = &impacts[1];
+        assert_eq!(cold.project_path, "cold");
+        assert!((cold.net_savings_usd + 0.75).abs() < 1e-9);
+        assert_eq!(cold.unpriced_cache_tokens, 5_000);
+    }
+
+    #[test]
+    fn test_most_expensive_session_selects_dominant_block() {
+        let day = Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap();
+        let mut opus = entry_at(day + chrono::Duration::hours(8), 1_000, 2_000, 4.0);
+        opus.model = "claude-opus-4-20250514".to_string();
+        opus.cache_read_tokens = 500;
+        let entries = vec![
+            entry_at(day, 100, 100, 0.10),
+            entry_at(day + chrono::Duration::minutes(30), 100, 100, 0.10),
+            opus,
+            entry_at(day + chrono::Duration::hours(9), 100, 100, 0.20),
+        ];
+
+        let now = day + chrono::Duration::days(1);
+        let session =
+            find_most_expensive_session(
+                &entries,
+                &now,
+                &SessionConfig::default(),
+                DEFAULT_COST_DECIMALS,
+            ).unwrap();
+        assert_eq!(session.start_time, (day + chrono::Duration::hours(8)).to_rfc3339());
+        assert_eq!(session.end_time, (day + chrono::Duration::hours(9)).to_rfc3339());
+        assert_eq!(session.message_count, 2);
+        assert_eq!(session.input_tokens, 1_100);
+        assert_eq!(session.output_tokens, 2_100);
+        assert_eq!(session.cache_read_tokens, 500);
+        assert!((session.cost_usd - 4.2).abs() < 1e-9);
+        assert_eq!(session.dominant_model, "claude-opus-4-20250514");
+
+        assert!(find_most_expensive_session(
+            &[],
+            &now,
+            &SessionConfig::default(),
+            DEFAULT_COST_DECIMALS,
+        ).is_none());
+    }
+
+    #[test]
+    fn test_session_blocks_per_model_breakdown_sums_to_total() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 9, 15, 0).unwrap();
+        let mut opus = entry_at(start + chrono::Duration::hours(1), 2_000, 3_000, 1.5);
+        opus.model = "claude-opus-4-20250514".to_string();
+        opus.cache_read_tokens = 700;
+        let entries = vec![
+            entry_at(start, 1_000, 500, 0.25),
+            opus,
+            entry_at(start + chrono::Duration::hours(2), 400, 100, 0.05),
+        ];
+
+        let now = start + chrono::Duration::hours(3);
+        let blocks = calculate_session_blocks(
+            &entries,
+            &now,
+            &SessionConfig::default(),
+            DEFAULT_COST_DECIMALS,
+        );
+        assert_eq!(blocks.len(), 1);
+        let block = &blocks[0];
+        let block_start = Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap();
+        assert_eq!(block.start_time, block_start.to_rfc3339());
+        assert_eq!(block.end_time, (block_start + chrono::Duration::hours(5)).to_rfc3339());
+        assert!(block.is_active);
+        assert_eq!(block.message_count, 3);
+
+        assert_eq!(block.models.len(), 2);
+        assert_eq!(block.models[0].model, "claude-opus-4-20250514");
+        assert_eq!(block.models[0].cache_read_tokens, 700);
+        assert_eq!(block.models[1].message_count, 2);
+        assert_eq!(block.models.iter().map(|m| m.total_tokens).sum::<u64>(), block.total_tokens);
+        assert_eq!(block.models.iter().map(|m| m.input_tokens).sum::<u64>(), block.input_tokens);
+        assert_eq!(block.models.iter().map(|m| m.message_count).sum::<u32>(), block.message_count);
+        let model_cost: f64 = block.models.iter().map(|m| m.cost_usd).sum();
+        assert!((model_cost - block.cost_usd).abs() < 1e-9);
+        assert!((block.cost_usd - 1.8).abs() < 1e-9);
+        let share: f64 = block.models.iter().map(|m| m.percentage).sum();
+        assert!((share - 100.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_session_blocks_mark_gaps_and_reset_alignment() {
+        let day = Utc.with_ymd_and_hms(2025, 1, 1, 8, 40, 0).unwrap();
+        let entries = vec![
+            entry_at(day, 100, 100, 0.1),
+            entry_at(day + chrono::Duration::hours(4), 100, 100, 0.1),
+            // Next block starts 5h20m later; idle for more than a session length
+            entry_at(day + chrono::Duration::minutes(560), 100, 100, 0.1),
+            entry_at(day + chrono::Duration::minutes(600), 100, 100, 0.1),
+        ];
+
+        let now = day + chrono::Duration::hours(11);
+        let blocks = calculate_session_blocks(
+            &entries,
+            &now,
+            &SessionConfig::default(),
+            DEFAULT_COST_DECIMALS,
+        );
+        assert_eq!(blocks.len(), 3);
+
+        let hour = |h: u32| Utc.with_ymd_and_hms(2025, 1, 1, h, 0, 0).unwrap().to_rfc3339();
+        assert_eq!(blocks[0].start_time, hour(8));
+        assert_eq!(blocks[0].end_time, hour(13));
+        assert!(!blocks[0].is_active && !blocks[0].is_gap);
+
+        assert!(blocks[1].is_gap);
+        assert_eq!(blocks[1].start_time, (day + chrono::Duration::hours(4)).to_rfc3339());
+        assert_eq!(blocks[1].end_time, hour(18));
+        assert_eq!(blocks[1].message_count, 0);
+
+        assert_eq!(blocks[2].start_time, hour(18));
+        assert_eq!(blocks[2].end_time, hour(23));
+        assert!(blocks[2].is_active);
+        assert_eq!(blocks[2].message_count, 2);
+    }
+
+    #[test]
+    fn test_session_gap_splits_block_mid_window() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap();
+        let entries = vec![
+            entry_at(start, 100, 100, 0.1),
+            entry_at(start + chrono::Duration::minutes(30), 100, 100, 0.1),
+            // Three idle hours, still inside the first 5-hour block
+            entry_at(start + chrono::Duration::minutes(210), 100, 100, 0.1),
+        ];
+        let now = start + chrono::Duration::minutes(240);
+
+        let fixed = transform_to_blocks(&entries, &now, &SessionConfig::default());
+        assert_eq!(fixed.len(), 1);
+        assert_eq!(fixed[0].message_count, 3);
+
+        let gapped = SessionConfig {
+            gap_minutes: Some(60),
+            ..SessionConfig::default()
+        };
+        let blocks = transform_to_blocks(&entries, &now, &gapped);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].message_count, 2);
+        assert!(!blocks[0].is_active);
+        // The second block starts at the hour of the message after the gap
+        assert_eq!(blocks[1].start_time, Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap());
+        assert!(blocks[1].is_active);
+
+        // An hour and a half after the last message the session has gone idle
+        let idle = transform_to_blocks(&entries, &(now + chrono::Duration::minutes(60)), &gapped);
+        assert!(!idle[1].is_active);
+    }
+
+    #[test]
+    fn test_plan_projections_heavy_usage_favors_higher_tiers() {
+        let now = Utc.with_ymd_and_hms(2025, 1, 10, 12, 0, 0).unwrap();
+        let mut entries = Vec::new();
+        // Five heavy sessions of 40k tokens: three cost $40, two cost $20
+        for day in 1..=5 {
+            let start = Utc.with_ymd_and_hms(2025, 1, day, 9, 0, 0).unwrap();
+            let cost = if day <= 3 { 10.0 } else { 5.0 };
+            for i in 0..4 {
+                let timestamp = start + chrono::Duration::minutes(i * 10);
+                entries.push(entry_at(timestamp, 5_000, 5_000, cost));
+            }
+        }
+        // Outside the projection window
+        let old = Utc.with_ymd_and_hms(2024, 11, 1, 9, 0, 0).unwrap();
+        entries.insert(0, entry_at(old, 1, 1, 500.0));
+
+        let projections = calculate_plan_projections(
+            &entries,
+            &now,
+            &SessionConfig::default(),
+            DEFAULT_COST_DECIMALS,
+        );
+        let by_plan: HashMap<_, _> =
+            projections.iter().map(|p| (p.plan_type.as_str(), p)).collect();
+        assert_eq!(projections.len(), 3);
+
+        let (pro, max5, max20) = (by_plan["pro"], by_plan["max5"], by_plan["max20"]);
+        assert_eq!(pro.sessions_analyzed, 5);
+        assert_eq!(pro.sessions_over_limit, 5);
+        assert_eq!(pro.limit_hit_percent, 100.0);
+        assert!((pro.estimated_overage_usd - (3.0 * 22.0 + 2.0 * 2.0)).abs() < 1e-9);
+        assert_eq!(max5.sessions_over_limit, 3);
+        assert!((max5.estimated_overage_usd - 3.0 * 5.0).abs() < 1e-9);
+        assert_eq!(max20.sessions_over_limit, 0);
+        assert_eq!(max20.estimated_overage_usd, 0.0);
+    }
+
+    #[test]
+    fn test_cache_efficiency_per_model_and_overall() {
+        let model_with_cache = |name: &str, input_tokens: u64, cache_read_tokens: u64| ModelStats {
+            model: name.to_string(),
+            input_tokens,
+            cache_read_tokens,
+            ..Default::default()
+        };
+        let mut stats = OverallStats {
+            total_input_tokens: 1_000_000,
+            cache_read_tokens: 1_200_000,
+            model_distribution: vec![
+                model_with_cache("claude-sonnet-4-20250514", 1_000_000, 1_000_000),
+                model_with_cache("claude-opus-4-20250514", 0, 200_000),
+                model_with_cache("claude-3-5-haiku-20241022", 0, 0),
+            ],
+            ..Default::default()
+        };
+
+        apply_cache_efficiency(&mut stats, &PricingCalculator::new(), DEFAULT_COST_DECIMALS);
+        let models = &stats.model_distribution;
+        assert_eq!(models[0].cache_hit_percent, 50.0);
+        // 1M cache reads at $0.30 instead of $3.00
+        assert!((models[0].cache_savings_usd - 2.7).abs() < 1e-9);
+        assert_eq!(models[1].cache_hit_percent, 100.0);
+        assert!((models[1].cache_savings_usd - 2.7).abs() < 1e-9);
+        // No input at all reports zero rather than NaN
+        assert_eq!(models[2].cache_hit_percent, 0.0);
+        assert_eq!(models[2].cache_savings_usd, 0.0);
+
+        assert_eq!(stats.cache_hit_percent, 54.55);
+        assert!((stats.cache_savings_usd - 5.4).abs() < 1e-9);
+
+        let mut empty = OverallStats::default();
+        apply_cache_efficiency(&mut empty, &PricingCalculator::new(), DEFAULT_COST_DECIMALS);
+        assert_eq!(empty.cache_hit_percent, 0.0);
+        assert!(serde_json::to_string(&empty).unwrap().contains("\"cacheHitPercent\":0.0"));
+    }
+
+    #[test]
+    fn test_token_composition_percentages() {
+        let stats = OverallStats {
+            total_input_tokens: 1_000,
+            total_output_tokens: 3_000,
+            cache_creation_tokens: 2_000,
+            cache_read_tokens: 14_000,
+            ..Default::default()
+        };
+
+        let composition = calculate_token_composition(&stats);
+        assert_eq!(composition.total_tokens, 20_000);
+        assert_eq!(composition.input_percent, 5.0);
+        assert_eq!(composition.output_percent, 15.0);
+        assert_eq!(composition.cache_creation_percent, 10.0);
+        assert_eq!(composition.cache_read_percent, 70.0);
+        let sum = composition.input_percent
+            + composition.output_percent
+            + composition.cache_creation_percent
+            + composition.cache_read_percent;
+        assert!((sum - 100.0).abs() < 1e-9);
+
+        let empty = calculate_token_composition(&OverallStats::default());
+        assert_eq!(empty.total_tokens, 0);
+        assert_eq!(empty.input_percent + empty.cache_read_percent, 0.0);
+    }
+
+    #[test]
+    fn test_footprint_estimate_scales_linearly() {
+        let base = estimate_footprint(2_000_000, 0.3, 0.4);
+        assert!((base.energy_kwh - 0.6).abs() < 1e-9);
+        assert!((base.co2_kg - 0.24).abs() < 1e-9);
+
+        let tripled = estimate_footprint(6_000_000, 0.3, 0.4);
+        assert!((tripled.energy_kwh - 3.0 * base.energy_kwh).abs() < 1e-9);
+        assert!((tripled.co2_kg - 3.0 * base.co2_kg).abs() < 1e-9);
+
+        let none = estimate_footprint(0, 0.3, 0.4);
+        assert_eq!(none.energy_kwh, 0.0);
+        assert_eq!(none.co2_kg, 0.0);
+    }
+
+    #[test]
+    fn test_message_budget_partial_usage() {
+        let block_start = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap();
+        let now = block_start + chrono::Duration::minutes(100);
+        let entries: Vec<_> = (0..50)
+            .map(|_| entry_at(block_start, 100, 100, 0.01))
+            .collect();
+
+        let budget = calculate_message_budget(&entries, "pro", &now, &SessionConfig::default());
+        assert_eq!(budget.message_limit, 250);
+        assert_eq!(budget.messages_used, 50);
+        assert_eq!(budget.messages_remaining, 200);
+        assert_eq!(budget.session_start_time, Some(block_start.to_rfc3339()));
+        assert_eq!(budget.messages_per_hour, 30.0);
+        // 200 remaining at 0.5 messages/min would take 400 minutes, past the block reset
+        assert!(budget.projected_limit_time.is_none());
+    }
+
+    #[test]
+    fn test_message_budget_without_active_session() {
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 20, 0, 0).unwrap();
+        let old = vec![entry_at(now - chrono::Duration::hours(10), 100, 100, 0.01)];
+
+        for entries in [Vec::new(), old] {
+            let budget =
+                calculate_message_budget(&entries, "max5", &now, &SessionConfig::default());
+            assert_eq!(budget.messages_used, 0);
+            assert_eq!(budget.messages_remaining, 1_000);
+            assert!(budget.session_start_time.is_none());
+            assert!(budget.projected_limit_time.is_none());
+        }
+    }
+
+    #[test]
+    fn test_plan_status_percentages_in_active_block() {
+        let block_start = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap();
+        let now = block_start + chrono::Duration::minutes(90);
+        let entries: Vec<_> = (0..25)
+            .map(|_| entry_at(block_start, 300, 100, 0.36))
+            .collect();
+
+        let status = calculate_plan_status(
+            &entries,
+            "pro",
+            &now,
+            &SessionConfig::default(),
+            DEFAULT_COST_DECIMALS,
+        );
+        assert_eq!(status.token_limit, 19_000);
+        assert_eq!(status.tokens_used, 10_000);
+        assert_eq!(status.messages_used, 25);
+        assert!((status.cost_used_usd - 9.0).abs() < 1e-9);
+        assert_eq!(status.token_percent, 52.63);
+        assert_eq!(status.cost_percent, 50.0);
+        assert_eq!(status.message_percent, 10.0);
+        assert_eq!(status.session_start_time, Some(block_start.to_rfc3339()));
+
+        let later = now + chrono::Duration::hours(6);
+        let idle = calculate_plan_status(
+            &entries,
+            "pro",
+            &later,
+            &SessionConfig::default(),
+            DEFAULT_COST_DECIMALS,
+        );
+        assert_eq!(idle.tokens_used, 0);
+        assert_eq!(idle.cost_percent, 0.0);
+        assert!(idle.session_start_time.is_none());
+    }
+
+    #[test]
+    fn test_group_model_distribution_nests_variants_under_family() {
+        let models = vec![
+            model("claude-sonnet-4-20250514", 500, 2.0),
+            model("claude-opus-4-20250514", 300, 6.0),
+            model("claude-3-5-sonnet", 150, 0.5),
+            model("claude-3-5-haiku", 50, 0.01),
+        ];
+
+        let grouped = group_model_distribution(&models, DEFAULT_COST_DECIMALS);
+        let families: Vec<_> = grouped.iter().map(|f| f.family.as_str()).collect();
+        assert_eq!(families, vec!["sonnet", "opus", "haiku"]);
+
+        let sonnet = &grouped[0];
+        assert_eq!(sonnet.total_tokens, 650);
+        assert_eq!(sonnet.message_count, 2);
+        assert!((sonnet.cost_usd - 2.5).abs() < 1e-9);
+        assert_eq!(sonnet.percentage, 65.0);
+        let variants: Vec<_> = sonnet.models.iter().map(|m| m.model.as_str()).collect();
+        assert_eq!(variants, vec!["claude-sonnet-4-20250514", "claude-3-5-sonnet"]);
+
+        assert_eq!(grouped[1].models.len(), 1);
+        assert_eq!(grouped[1].total_tokens, 300);
+        assert_eq!(grouped[2].models[0].model, "claude-3-5-haiku");
+    }
+
+    #[test]
+    fn test_spend_stability_steady_vs_spiky() {
+        let today = date("2025-01-08");
+
+        let steady: Vec<_> = (1..=7)
+            .map(|d| daily(&format!("2025-01-{:02}", d), 10.0))
+            .collect();
+        let spiky = vec![
+            daily("2025-01-01", 1.0),
+            daily("2025-01-02", 40.0),
+            daily("2025-01-03", 0.5),
+            daily("2025-01-05", 1.0),
+            daily("2025-01-07", 30.0),
+        ];
+
+        let steady_stats = calculate_spend_stability(&steady, today, 30, DEFAULT_COST_DECIMALS);
+        let spiky_stats = calculate_spend_stability(&spiky, today, 30, DEFAULT_COST_DECIMALS);
+
+        assert_eq!(steady_stats.classification, SpendClassification::Stable);
+        assert_eq!(steady_stats.days_counted, 7);
+        assert!((steady_stats.mean_daily_cost - 10.0).abs() < 1e-9);
+        assert_eq!(steady_stats.std_dev_daily_cost, 0.0);
+
+        // Gap days are filled with zero spend
+        assert_eq!(spiky_stats.days_counted, 7);
+        assert_eq!(spiky_stats.classification, SpendClassification::Erratic);
+        assert_ne!(steady_stats.classification, spiky_stats.classification);
+    }
+
+    #[test]
+    fn test_spend_stability_excludes_today_and_handles_little_data() {
+        let today = date("2025-01-08");
+        let data = vec![daily("2025-01-07", 5.0), daily("2025-01-08", 500.0)];
+
+        let stats = calculate_spend_stability(&data, today, 30, DEFAULT_COST_DECIMALS);
+        assert_eq!(stats.days_counted, 1);
+        assert!((stats.mean_daily_cost - 5.0).abs() < 1e-9);
+        assert_eq!(stats.classification, SpendClassification::InsufficientData);
+
+        let empty = calculate_spend_stability(&[], today, 30, DEFAULT_COST_DECIMALS);
+        assert_eq!(empty.days_counted, 0);
+        assert_eq!(empty.classification, SpendClassification::InsufficientData);
+    }
+}
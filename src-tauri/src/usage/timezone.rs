@@ -0,0 +1,126 @@
+//! Timezone used to assign usage to calendar dates
+
+use std::fmt;
+
+use chrono::{
+    DateTime, FixedOffset, Local, MappedLocalTime, NaiveDate, NaiveDateTime, Offset, TimeZone,
+    Utc,
+};
+use chrono_tz::Tz;
+use log::warn;
+
+use crate::usage::models::AppConfig;
+
+/// Zone every date bucket (daily usage, today's stats, weeks, months, hours) is computed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UsageTimezone {
+    /// The system's local zone
+    #[default]
+    Local,
+    /// An IANA zone such as `Europe/Berlin`
+    Named(Tz),
+}
+
+impl UsageTimezone {
+    /// Parse an IANA zone name, or None if it is unknown
+    pub fn parse(name: &str) -> Option<Self> {
+        name.parse::<Tz>().ok().map(Self::Named)
+    }
+
+    /// The configured zone, falling back to the system zone when unset or unknown
+    pub fn from_config(config: &AppConfig) -> Self {
+        match config.timezone.as_deref() {
+            None => Self::Local,
+            Some(name) => Self::parse(name).unwrap_or_else(|| {
+                warn!("Unknown timezone '{}', using the system timezone", name);
+                Self::Local
+            }),
+        }
+    }
+
+    /// Calendar date of `timestamp` in this zone
+    pub fn date_of(&self, timestamp: &DateTime<Utc>) -> NaiveDate {
+        timestamp.with_timezone(self).date_naive()
+    }
+
+    fn offset(&self, offset: FixedOffset) -> UsageOffset {
+        UsageOffset {
+            zone: *self,
+            offset,
+        }
+    }
+}
+
+/// Offset of a `UsageTimezone` at some instant, remembering the zone it belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsageOffset {
+    zone: UsageTimezone,
+    offset: FixedOffset,
+}
+
+impl Offset for UsageOffset {
+    fn fix(&self) -> FixedOffset {
+        self.offset
+    }
+}
+
+impl fmt::Display for UsageOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.offset.fmt(f)
+    }
+}
+
+impl TimeZone for UsageTimezone {
+    type Offset = UsageOffset;
+
+    fn from_offset(offset: &UsageOffset) -> Self {
+        offset.zone
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<UsageOffset> {
+        match self {
+            Self::Local => Local.offset_from_local_date(local).map(|o| self.offset(o)),
+            Self::Named(tz) => tz.offset_from_local_date(local).map(|o| self.offset(o.fix())),
+        }
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<UsageOffset> {
+        match self {
+            Self::Local => Local.offset_from_local_datetime(local).map(|o| self.offset(o)),
+            Self::Named(tz) => {
+                tz.offset_from_local_datetime(local).map(|o| self.offset(o.fix()))
+            }
+        }
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> UsageOffset {
+        match self {
+            Self::Local => self.offset(Local.offset_from_utc_date(utc)),
+            Self::Named(tz) => self.offset(tz.offset_from_utc_date(utc).fix()),
+        }
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> UsageOffset {
+        match self {
+            Self::Local => self.offset(Local.offset_from_utc_datetime(utc)),
+            Self::Named(tz) => self.offset(tz.offset_from_utc_datetime(utc).fix()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_zone_follows_daylight_saving() {
+        let tz = UsageTimezone::parse("America/New_York").unwrap();
+        let winter = Utc.with_ymd_and_hms(2025, 1, 15, 3, 30, 0).unwrap();
+        let summer = Utc.with_ymd_and_hms(2025, 7, 15, 3, 30, 0).unwrap();
+
+        assert_eq!(tz.date_of(&winter), NaiveDate::from_ymd_opt(2025, 1, 14).unwrap());
+        assert_eq!(winter.with_timezone(&tz).offset().fix().local_minus_utc(), -5 * 3600);
+        assert_eq!(summer.with_timezone(&tz).offset().fix().local_minus_utc(), -4 * 3600);
+        assert!(UsageTimezone::parse("Mars/Olympus_Mons").is_none());
+    }
+}
@@ -102,6 +102,31 @@ export interface AppConfig {
   planType: string;
 }
 
+/** Error returned by backend commands; `kind` selects guidance, `message` is for display */
+export interface CommandError {
+  kind:
+    | 'dirNotFound'
+    | 'permissionDenied'
+    | 'io'
+    | 'parse'
+    | 'invalidInput'
+    | 'notFound'
+    | 'internal';
+  message: string;
+}
+
+function isCommandError(e: unknown): e is CommandError {
+  return typeof e === 'object' && e !== null && 'kind' in e && 'message' in e;
+}
+
+/** Display text for anything thrown by `invoke` */
+export function errorMessage(e: unknown): string {
+  if (isCommandError(e) || e instanceof Error) {
+    return e.message;
+  }
+  return String(e);
+}
+
 interface UseAsyncState<T> {
   data: T | null;
   loading: boolean;
@@ -126,7 +151,7 @@ export function useUsageStats(dataPath?: string): UseAsyncState<UsageData> {
       });
       setData(result);
     } catch (e) {
-      setError(e instanceof Error ? e.message : String(e));
+      setError(errorMessage(e));
     } finally {
       setLoading(false);
     }
@@ -156,7 +181,7 @@ export function useProjects(dataPath?: string): UseAsyncState<ProjectStats[]> {
       });
       setData(result);
     } catch (e) {
-      setError(e instanceof Error ? e.message : String(e));
+      setError(errorMessage(e));
     } finally {
       setLoading(false);
     }
@@ -186,7 +211,7 @@ export function useOverallStats(dataPath?: string): UseAsyncState<OverallStats>
       });
       setData(result);
     } catch (e) {
-      setError(e instanceof Error ? e.message : String(e));
+      setError(errorMessage(e));
     } finally {
       setLoading(false);
     }
@@ -222,7 +247,7 @@ export function useDailyUsage(
       });
       setData(result);
     } catch (e) {
-      setError(e instanceof Error ? e.message : String(e));
+      setError(errorMessage(e));
     } finally {
       setLoading(false);
     }
@@ -252,7 +277,7 @@ export function useDataDirectoryCheck(dataPath?: string): UseAsyncState<boolean>
       });
       setData(result);
     } catch (e) {
-      setError(e instanceof Error ? e.message : String(e));
+      setError(errorMessage(e));
     } finally {
       setLoading(false);
     }
@@ -369,7 +394,7 @@ export function useIncrementalUsageStats(
         triggerAnimation();
       }
     } catch (e) {
-      setError(e instanceof Error ? e.message : String(e));
+      setError(errorMessage(e));
     } finally {
       setLoading(false);
     }
@@ -408,7 +433,7 @@ export function useIncrementalUsageStats(
  * - Initial load: full fetch from backend
  * - Updates: listen to backend push events (no polling)
  * - Manual refresh: full fetch with loading state
- * - Heartbeat: backend sends event every 5s even if no changes
+ * - Heartbeat: backend sends an event every refresh interval even if no changes
  */
 export function usePushBasedUsageStats(
   dataPath?: string
@@ -473,7 +498,7 @@ export function usePushBasedUsageStats(
         triggerAnimation();
       }
     } catch (e) {
-      setError(e instanceof Error ? e.message : String(e));
+      setError(errorMessage(e));
     } finally {
       setLoading(false);
     }